@@ -0,0 +1,414 @@
+use crate::classfile;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const STATE_VERSION: u32 = 2;
+
+/// 上次javac构建的状态，保存在 `<target_dir>/jx-build-state.json`
+///
+/// 记录源文件和classpath的摘要，以及从class文件中读出的类到源文件、类到类的引用，
+/// 下次构建只重新编译变化的源文件和引用了它们的源文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildState {
+    pub version: u32,
+    /// javac版本参数和编译参数的摘要
+    pub options: String,
+    /// classpath中每一项的摘要，按顺序排列
+    pub classpath: Vec<(String, String)>,
+    /// 相对于项目目录的源文件路径
+    pub sources: BTreeMap<String, SourceState>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceState {
+    pub hash: String,
+    /// 由这个源文件编译出的类，以及每个类引用的项目中的其他类
+    pub classes: BTreeMap<String, BTreeSet<String>>,
+    /// 定义了编译期常量。使用处内联了常量值而不引用这个类，变化时需要全量编译
+    pub constants: bool,
+}
+
+/// 本次构建需要做的事
+pub enum Plan {
+    /// 没有可用的状态或编译参数、classpath发生变化，或者定义编译期常量的源文件有变化
+    Full,
+    UpToDate,
+    Incremental {
+        /// 需要重新编译的源文件，包括新增、修改的源文件和引用了变化的类的源文件
+        recompile: BTreeSet<String>,
+        /// 已删除的源文件
+        removed: BTreeSet<String>,
+    },
+}
+
+impl BuildState {
+    pub fn new(options: String, classpath: Vec<(String, String)>) -> Self {
+        Self {
+            version: STATE_VERSION,
+            options,
+            classpath,
+            sources: BTreeMap::new(),
+        }
+    }
+
+    /// 旧版本或损坏的状态文件当作不存在，进行全量编译
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str::<BuildState>(&content)
+            .ok()
+            .filter(|state| state.version == STATE_VERSION)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string(self)?)
+            .with_context(|| format!("无法写入构建状态 {}", path.display()))
+    }
+
+    /// 与当前的源文件摘要比较，找出需要重新编译的源文件
+    pub fn plan(&self, current: &BuildState, hashes: &BTreeMap<String, String>) -> Plan {
+        if self.options != current.options || self.classpath != current.classpath {
+            return Plan::Full;
+        }
+
+        let removed: BTreeSet<String> = self
+            .sources
+            .keys()
+            .filter(|source| !hashes.contains_key(*source))
+            .cloned()
+            .collect();
+        let mut recompile: BTreeSet<String> = hashes
+            .iter()
+            .filter(|(source, hash)| self.sources.get(*source).is_none_or(|state| &state.hash != *hash))
+            .map(|(source, _)| source.clone())
+            .collect();
+        if recompile.is_empty() && removed.is_empty() {
+            return Plan::UpToDate;
+        }
+
+        // 与Gradle相同: 找不到内联常量的使用处，只能全部重新编译
+        let constants_changed = recompile
+            .iter()
+            .chain(&removed)
+            .filter_map(|source| self.sources.get(source))
+            .any(|state| state.constants);
+        if constants_changed {
+            return Plan::Full;
+        }
+
+        // 沿类之间的引用向上传递，直到没有新的源文件需要重新编译
+        let mut changed: BTreeSet<&str> = recompile
+            .iter()
+            .chain(&removed)
+            .filter_map(|source| self.sources.get(source))
+            .flat_map(|state| state.classes.keys().map(String::as_str))
+            .collect();
+        loop {
+            let dependents: Vec<(&String, &SourceState)> = self
+                .sources
+                .iter()
+                .filter(|(source, _)| hashes.contains_key(*source) && !recompile.contains(*source))
+                .filter(|(_, state)| {
+                    state
+                        .classes
+                        .values()
+                        .any(|references| references.iter().any(|class| changed.contains(class.as_str())))
+                })
+                .collect();
+            if dependents.is_empty() {
+                break;
+            }
+            for (source, state) in dependents {
+                recompile.insert(source.clone());
+                changed.extend(state.classes.keys().map(String::as_str));
+            }
+        }
+
+        Plan::Incremental { recompile, removed }
+    }
+
+    /// 记录的class文件都还在输出目录中，被手动删除过时需要全量编译
+    pub fn outputs_exist(&self, output_dir: &Path) -> bool {
+        self.sources
+            .values()
+            .flat_map(|state| state.classes.keys())
+            .all(|class| class_path(output_dir, class).exists())
+    }
+
+    /// 删除这些源文件上次编译出的class文件，并把它们从状态中移除
+    pub fn remove_sources(&mut self, sources: &BTreeSet<String>, output_dir: &Path) -> Result<usize> {
+        let mut deleted = 0;
+        for source in sources {
+            let Some(state) = self.sources.remove(source) else {
+                continue;
+            };
+            for class in state.classes.keys() {
+                let path = class_path(output_dir, class);
+                if path.exists() {
+                    fs::remove_file(&path)
+                        .with_context(|| format!("无法删除 {}", path.display()))?;
+                    deleted += 1;
+                }
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// 读取本次编译出的class文件，按包名和`SourceFile`属性归属到源文件
+    ///
+    /// `roots`是源码目录 (相对于项目目录)，`compiled`是本次编译的源文件；
+    /// 无法确定来源的class文件会返回错误，调用方应当放弃保存状态
+    pub fn record(
+        &mut self,
+        output_dir: &Path,
+        roots: &[String],
+        compiled: &BTreeMap<String, String>,
+    ) -> Result<()> {
+        let known: BTreeSet<PathBuf> = self
+            .sources
+            .values()
+            .flat_map(|state| state.classes.keys().map(|class| class_path(output_dir, class)))
+            .collect();
+
+        let mut class_files = Vec::new();
+        collect_class_files(output_dir, &mut class_files)?;
+
+        let mut classes: Vec<classfile::ClassInfo> = Vec::new();
+        for path in class_files.into_iter().filter(|path| !known.contains(path)) {
+            let bytes = fs::read(&path).with_context(|| format!("无法读取 {}", path.display()))?;
+            let info = classfile::parse(&bytes).with_context(|| format!("无法解析 {}", path.display()))?;
+            classes.push(info);
+        }
+
+        for (source, hash) in compiled {
+            self.sources.insert(
+                source.clone(),
+                SourceState {
+                    hash: hash.clone(),
+                    classes: BTreeMap::new(),
+                    constants: false,
+                },
+            );
+        }
+
+        let mut project_classes: BTreeSet<String> = self
+            .sources
+            .values()
+            .flat_map(|state| state.classes.keys().cloned())
+            .collect();
+        project_classes.extend(classes.iter().map(|info| info.name.clone()));
+
+        for info in classes {
+            let source = owning_source(&info, roots, compiled).ok_or_else(|| {
+                anyhow::anyhow!("无法确定 {}.class 对应的源文件", info.name)
+            })?;
+            let references = info
+                .references
+                .into_iter()
+                .filter(|class| project_classes.contains(class))
+                .collect();
+            if let Some(state) = self.sources.get_mut(&source) {
+                state.constants |= info.has_constants;
+                state.classes.insert(info.name, references);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 先按源码目录加包路径匹配，包名与目录不一致时按文件名在本次编译的源文件中唯一匹配
+fn owning_source(
+    info: &classfile::ClassInfo,
+    roots: &[String],
+    compiled: &BTreeMap<String, String>,
+) -> Option<String> {
+    let relative = info.source_path();
+    for root in roots {
+        let candidate = if root.is_empty() {
+            relative.clone()
+        } else {
+            format!("{}/{}", root.trim_end_matches('/'), relative)
+        };
+        if compiled.contains_key(&candidate) {
+            return Some(candidate);
+        }
+    }
+
+    let file_name = relative.rsplit('/').next()?;
+    let mut matches = compiled
+        .keys()
+        .filter(|source| source.rsplit('/').next() == Some(file_name));
+    match (matches.next(), matches.next()) {
+        (Some(source), None) => Some(source.clone()),
+        _ => None,
+    }
+}
+
+fn class_path(output_dir: &Path, class: &str) -> PathBuf {
+    output_dir.join(format!("{}.class", class))
+}
+
+fn collect_class_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("无法读取目录 {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_class_files(&path, files)?;
+        } else if path.extension().and_then(|s| s.to_str()) == Some("class") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 源文件内容的摘要
+pub fn hash_file(path: &Path) -> Result<String> {
+    let bytes = fs::read(path).with_context(|| format!("无法读取 {}", path.display()))?;
+    Ok(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+/// classpath中一项的摘要: jar按大小和修改时间，目录按其中所有文件的相对路径、大小和修改时间
+pub fn hash_classpath_entry(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    if path.is_dir() {
+        let mut files = Vec::new();
+        for entry in walkdir::WalkDir::new(path).sort_by_file_name() {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                files.push(entry.into_path());
+            }
+        }
+        for file in files {
+            hasher.update(file.strip_prefix(path)?.to_string_lossy().as_bytes());
+            hasher.update(fingerprint(&file)?.as_bytes());
+        }
+    } else if path.exists() {
+        hasher.update(fingerprint(path)?.as_bytes());
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn fingerprint(path: &Path) -> Result<String> {
+    let metadata = fs::metadata(path).with_context(|| format!("无法读取 {}", path.display()))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    Ok(format!(":{}:{}\n", metadata.len(), modified))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A <- B <- C 的引用链，D与它们无关；Config定义了D内联使用的常量
+    fn previous() -> BuildState {
+        let mut state = BuildState::new("options".to_string(), vec![("lib/a.jar".to_string(), "1".to_string())]);
+        for (source, class, references, constants) in [
+            ("src/A.java", "A", vec![], false),
+            ("src/B.java", "B", vec!["A"], false),
+            ("src/C.java", "C", vec!["B"], false),
+            ("src/D.java", "D", vec![], false),
+            ("src/Config.java", "Config", vec![], true),
+        ] {
+            state.sources.insert(
+                source.to_string(),
+                SourceState {
+                    hash: format!("{}-1", class),
+                    classes: BTreeMap::from([(
+                        class.to_string(),
+                        references.into_iter().map(str::to_string).collect(),
+                    )]),
+                    constants,
+                },
+            );
+        }
+        state
+    }
+
+    fn hashes(changes: &[(&str, &str)], removed: &[&str]) -> BTreeMap<String, String> {
+        let mut hashes: BTreeMap<String, String> = previous()
+            .sources
+            .iter()
+            .map(|(source, state)| (source.clone(), state.hash.clone()))
+            .collect();
+        for (source, hash) in changes {
+            hashes.insert(source.to_string(), hash.to_string());
+        }
+        for source in removed {
+            hashes.remove(*source);
+        }
+        hashes
+    }
+
+    fn current() -> BuildState {
+        BuildState::new("options".to_string(), vec![("lib/a.jar".to_string(), "1".to_string())])
+    }
+
+    fn set(sources: &[&str]) -> BTreeSet<String> {
+        sources.iter().map(|source| source.to_string()).collect()
+    }
+
+    #[test]
+    fn unchanged_sources_are_up_to_date() {
+        assert!(matches!(previous().plan(&current(), &hashes(&[], &[])), Plan::UpToDate));
+    }
+
+    #[test]
+    fn changed_options_or_classpath_need_full_build() {
+        let mut changed = current();
+        changed.options = "other".to_string();
+        assert!(matches!(previous().plan(&changed, &hashes(&[], &[])), Plan::Full));
+
+        let mut changed = current();
+        changed.classpath[0].1 = "2".to_string();
+        assert!(matches!(previous().plan(&changed, &hashes(&[], &[])), Plan::Full));
+    }
+
+    #[test]
+    fn dependents_are_recompiled_transitively() {
+        let Plan::Incremental { recompile, removed } =
+            previous().plan(&current(), &hashes(&[("src/A.java", "A-2")], &[]))
+        else {
+            panic!("应当增量编译");
+        };
+        assert_eq!(recompile, set(&["src/A.java", "src/B.java", "src/C.java"]));
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn removed_sources_recompile_their_dependents() {
+        let Plan::Incremental { recompile, removed } =
+            previous().plan(&current(), &hashes(&[], &["src/B.java"]))
+        else {
+            panic!("应当增量编译");
+        };
+        assert_eq!(recompile, set(&["src/C.java"]));
+        assert_eq!(removed, set(&["src/B.java"]));
+    }
+
+    #[test]
+    fn new_sources_are_compiled_alone() {
+        let Plan::Incremental { recompile, removed } =
+            previous().plan(&current(), &hashes(&[("src/E.java", "E-1")], &[]))
+        else {
+            panic!("应当增量编译");
+        };
+        assert_eq!(recompile, set(&["src/E.java"]));
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn changed_compile_time_constants_need_full_build() {
+        let plan = previous().plan(&current(), &hashes(&[("src/Config.java", "Config-2")], &[]));
+        assert!(matches!(plan, Plan::Full));
+
+        let plan = previous().plan(&current(), &hashes(&[], &["src/Config.java"]));
+        assert!(matches!(plan, Plan::Full));
+    }
+}
@@ -0,0 +1,308 @@
+use crate::manifest::replace_string;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{Document, InlineTable, Item, Table, TableLike, Value};
+
+/// 版本目录中的库
+#[derive(Debug, Clone)]
+pub struct CatalogLibrary {
+    pub alias: String,
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: Option<String>,
+    /// `version.ref`引用的`[versions]`条目
+    pub version_ref: Option<String>,
+}
+
+/// 版本目录中的插件
+#[derive(Debug, Clone)]
+pub struct CatalogPlugin {
+    pub alias: String,
+    pub id: String,
+    pub version: Option<String>,
+}
+
+/// 版本修改的结果
+pub struct CatalogVersionChange {
+    /// 版本通过`version.ref`引用时修改的是这个`[versions]`条目
+    pub version_ref: Option<String>,
+}
+
+/// gradle/libs.versions.toml版本目录
+///
+/// 与jx.toml一样使用toml_edit读写，修改版本时只改动对应的值
+pub struct VersionCatalog {
+    path: PathBuf,
+    document: Document,
+}
+
+impl VersionCatalog {
+    /// 从项目目录向上查找版本目录，子项目使用根项目的目录
+    pub fn find(project_dir: &Path) -> Result<Option<Self>> {
+        let Some(path) = project_dir
+            .ancestors()
+            .map(|dir| dir.join("gradle").join("libs.versions.toml"))
+            .find(|path| path.exists())
+        else {
+            return Ok(None);
+        };
+
+        let content = fs::read_to_string(&path)?;
+        let document = content
+            .parse::<Document>()
+            .with_context(|| format!("无法解析版本目录 {}", path.display()))?;
+        Ok(Some(Self { path, document }))
+    }
+
+    pub fn libraries(&self) -> Vec<CatalogLibrary> {
+        let Some(libraries) = self.section("libraries") else {
+            return Vec::new();
+        };
+        libraries
+            .iter()
+            .filter_map(|(alias, item)| self.parse_library(alias, item))
+            .collect()
+    }
+
+    /// 按访问器查找库，例如`libs.commons.lang3`中的`commons.lang3`
+    pub fn library(&self, accessor: &str) -> Option<CatalogLibrary> {
+        let (alias, item) = self
+            .section("libraries")?
+            .iter()
+            .find(|(alias, _)| to_accessor(alias) == accessor)?;
+        self.parse_library(alias, item)
+    }
+
+    pub fn find_library(&self, group_id: &str, artifact_id: &str) -> Option<CatalogLibrary> {
+        self.libraries()
+            .into_iter()
+            .find(|library| library.group_id == group_id && library.artifact_id == artifact_id)
+    }
+
+    /// `libs.bundles.xxx`中的库
+    pub fn bundle(&self, accessor: &str) -> Option<Vec<CatalogLibrary>> {
+        let (_, bundle) = self
+            .section("bundles")?
+            .iter()
+            .find(|(alias, _)| to_accessor(alias) == accessor)?;
+
+        Some(
+            bundle
+                .as_array()?
+                .iter()
+                .filter_map(|alias| self.library(&to_accessor(alias.as_str()?)))
+                .collect(),
+        )
+    }
+
+    /// `libs.plugins.xxx`中的插件
+    pub fn plugin(&self, accessor: &str) -> Option<CatalogPlugin> {
+        let (alias, item) = self
+            .section("plugins")?
+            .iter()
+            .find(|(alias, _)| to_accessor(alias) == accessor)?;
+
+        if let Some(notation) = item.as_str() {
+            let (id, version) = match notation.split_once(':') {
+                Some((id, version)) => (id, Some(version.to_string())),
+                None => (notation, None),
+            };
+            return Some(CatalogPlugin {
+                alias: alias.to_string(),
+                id: id.to_string(),
+                version,
+            });
+        }
+
+        let plugin = item.as_table_like()?;
+        Some(CatalogPlugin {
+            alias: alias.to_string(),
+            id: plugin.get("id")?.as_str()?.to_string(),
+            version: plugin.get("version").and_then(|v| self.version(v)),
+        })
+    }
+
+    /// 添加库，已有库使用`version.ref`时同样在`[versions]`中声明版本
+    pub fn add_library(
+        &mut self,
+        alias: &str,
+        group_id: &str,
+        artifact_id: &str,
+        version: &str,
+    ) -> Result<()> {
+        if self.section("libraries").is_some_and(|libraries| libraries.contains_key(alias)) {
+            return Err(anyhow::anyhow!(
+                "版本目录中已有别名 {}，请使用 --alias 指定其他别名",
+                alias
+            ));
+        }
+
+        // [versions]中已有同名条目时直接写版本，避免改动其他库的版本
+        let uses_refs = self.libraries().iter().any(|library| library.version_ref.is_some())
+            && !self.section("versions").is_some_and(|versions| versions.contains_key(alias));
+
+        let mut library = InlineTable::new();
+        library.insert("module", format!("{}:{}", group_id, artifact_id).into());
+        if uses_refs {
+            let mut reference = InlineTable::new();
+            reference.set_dotted(true);
+            reference.insert("ref", alias.into());
+            library.insert("version", Value::InlineTable(reference));
+            self.section_or_insert("versions").insert(alias, toml_edit::value(version));
+        } else {
+            library.insert("version", version.into());
+        }
+
+        self.section_or_insert("libraries")
+            .insert(alias, Item::Value(Value::InlineTable(library)));
+        Ok(())
+    }
+
+    /// 修改库的版本，通过`version.ref`引用时修改`[versions]`中的条目
+    pub fn set_library_version(&mut self, alias: &str, version: &str) -> Result<CatalogVersionChange> {
+        let library = self
+            .section("libraries")
+            .and_then(|libraries| libraries.get(alias))
+            .and_then(|item| self.parse_library(alias, item))
+            .ok_or_else(|| anyhow::anyhow!("版本目录中没有库 {}", alias))?;
+
+        if let Some(reference) = &library.version_ref {
+            let item = self
+                .section_mut("versions")
+                .and_then(|versions| versions.get_mut(reference))
+                .ok_or_else(|| anyhow::anyhow!("版本目录的[versions]中没有 {}", reference))?;
+            set_version_item(item, version)
+                .ok_or_else(|| anyhow::anyhow!("无法修改版本目录中的版本 {}", reference))?;
+            return Ok(CatalogVersionChange {
+                version_ref: Some(reference.clone()),
+            });
+        }
+
+        let item = self
+            .section_mut("libraries")
+            .and_then(|libraries| libraries.get_mut(alias))
+            .expect("库已存在");
+        let updated = if item.is_str() {
+            let coordinate = format!("{}:{}:{}", library.group_id, library.artifact_id, version);
+            replace_string(item.as_value_mut().expect("字符串写法"), &coordinate).map(|_| ())
+        } else {
+            match item.as_table_like_mut().and_then(|table| table.get_mut("version")) {
+                Some(version_item) => set_version_item(version_item, version),
+                None => {
+                    let table = item.as_table_like_mut().expect("表写法");
+                    table.insert("version", toml_edit::value(version));
+                    Some(())
+                }
+            }
+        };
+        updated.ok_or_else(|| anyhow::anyhow!("无法修改版本目录中 {} 的版本", alias))?;
+
+        Ok(CatalogVersionChange {
+            version_ref: None,
+        })
+    }
+
+    /// 引用同一个`[versions]`条目的库的数量
+    pub fn version_ref_usages(&self, reference: &str) -> usize {
+        self.libraries()
+            .iter()
+            .filter(|library| library.version_ref.as_deref() == Some(reference))
+            .count()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::write(&self.path, self.document.to_string())
+            .with_context(|| format!("无法写入 {}", self.path.display()))?;
+        Ok(())
+    }
+
+    fn section(&self, name: &str) -> Option<&dyn TableLike> {
+        self.document.get(name)?.as_table_like()
+    }
+
+    fn section_mut(&mut self, name: &str) -> Option<&mut dyn TableLike> {
+        self.document.get_mut(name)?.as_table_like_mut()
+    }
+
+    fn section_or_insert(&mut self, name: &str) -> &mut dyn TableLike {
+        self.document
+            .entry(name)
+            .or_insert_with(|| Item::Table(Table::new()))
+            .as_table_like_mut()
+            .expect("版本目录的段必须是表")
+    }
+
+    /// 支持`"g:a:v"`、`{ module = "g:a", version = .. }`和`{ group = "g", name = "a", version = .. }`
+    fn parse_library(&self, alias: &str, item: &Item) -> Option<CatalogLibrary> {
+        if let Some(notation) = item.as_str() {
+            let mut parts = notation.split(':');
+            return Some(CatalogLibrary {
+                alias: alias.to_string(),
+                group_id: parts.next()?.to_string(),
+                artifact_id: parts.next()?.to_string(),
+                version: parts.next().map(str::to_string),
+                version_ref: None,
+            });
+        }
+
+        let library = item.as_table_like()?;
+        let (group_id, artifact_id) = match library.get("module").and_then(|m| m.as_str()) {
+            Some(module) => {
+                let (group_id, artifact_id) = module.split_once(':')?;
+                (group_id.to_string(), artifact_id.to_string())
+            }
+            None => (
+                library.get("group")?.as_str()?.to_string(),
+                library.get("name")?.as_str()?.to_string(),
+            ),
+        };
+        let version = library.get("version");
+        let version_ref = version
+            .and_then(|v| v.as_table_like())
+            .and_then(|v| v.get("ref"))
+            .and_then(|r| r.as_str())
+            .map(str::to_string);
+
+        Some(CatalogLibrary {
+            alias: alias.to_string(),
+            group_id,
+            artifact_id,
+            version: version.and_then(|v| self.version(v)),
+            version_ref,
+        })
+    }
+
+    /// `"1.0"`、`{ ref = "x" }`或`{ strictly/require/prefer = "1.0" }`
+    fn version(&self, item: &Item) -> Option<String> {
+        if let Some(version) = item.as_str() {
+            return Some(version.to_string());
+        }
+
+        let table = item.as_table_like()?;
+        if let Some(reference) = table.get("ref").and_then(|r| r.as_str()) {
+            return self.version(self.section("versions")?.get(reference)?);
+        }
+        ["require", "strictly", "prefer"]
+            .iter()
+            .find_map(|key| table.get(key)?.as_str().map(str::to_string))
+    }
+}
+
+/// 别名对应的访问器，别名中的`-`、`_`和`.`在访问器中都写成`.`
+pub fn to_accessor(alias: &str) -> String {
+    alias.replace(['-', '_'], ".")
+}
+
+/// 修改`"1.0"`或`{ strictly/require/prefer = "1.0" }`形式的版本
+fn set_version_item(item: &mut Item, version: &str) -> Option<()> {
+    if let Some(value @ Value::String(_)) = item.as_value_mut() {
+        return replace_string(value, version).map(|_| ());
+    }
+
+    let table = item.as_table_like_mut()?;
+    let key = ["require", "strictly", "prefer"]
+        .into_iter()
+        .find(|key| table.contains_key(key))?;
+    replace_string(table.get_mut(key)?.as_value_mut()?, version).map(|_| ())
+}
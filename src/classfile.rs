@@ -0,0 +1,492 @@
+use anyhow::Result;
+use std::collections::BTreeSet;
+
+/// 从class文件中读出的类名、源文件名和引用的其他类
+///
+/// 类名使用JVM内部格式，例如`com/example/Main$Inner`
+#[derive(Debug, Clone)]
+pub struct ClassInfo {
+    pub name: String,
+    /// `SourceFile`属性，使用`-g:none`编译时没有
+    pub source_file: Option<String>,
+    /// 常量池中的类引用以及描述符、签名里出现的类型，不包括自身
+    pub references: BTreeSet<String>,
+    /// 有带`ConstantValue`属性的字段 (static final编译期常量)。
+    /// javac会把这些常量内联到使用处，使用处的常量池中不会留下对本类的引用
+    pub has_constants: bool,
+}
+
+impl ClassInfo {
+    /// 包名对应的目录加上源文件名，例如`com/example/Main.java`
+    pub fn source_path(&self) -> String {
+        let file = match &self.source_file {
+            Some(file) => file.clone(),
+            None => {
+                // 没有SourceFile属性时按最外层类名推断
+                let simple = self.name.rsplit('/').next().unwrap_or(&self.name);
+                format!("{}.java", simple.split('$').next().unwrap_or(simple))
+            }
+        };
+        match self.name.rfind('/') {
+            Some(index) => format!("{}/{}", &self.name[..index], file),
+            None => file,
+        }
+    }
+}
+
+enum Constant {
+    Utf8(String),
+    Class(u16),
+    Other,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| anyhow::anyhow!("class文件不完整"))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn u1(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u2(&mut self) -> Result<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u4(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+/// 解析class文件的常量池和类属性
+pub fn parse(bytes: &[u8]) -> Result<ClassInfo> {
+    let mut reader = Reader { bytes, offset: 0 };
+    if reader.u4()? != 0xCAFE_BABE {
+        return Err(anyhow::anyhow!("不是有效的class文件"));
+    }
+    reader.take(4)?; // minor_version, major_version
+
+    let count = reader.u2()? as usize;
+    let mut pool: Vec<Constant> = Vec::with_capacity(count);
+    pool.push(Constant::Other); // 常量池从1开始编号
+    while pool.len() < count {
+        let tag = reader.u1()?;
+        let constant = match tag {
+            1 => {
+                let len = reader.u2()? as usize;
+                // 常量池使用modified UTF-8，类名和描述符中不会出现需要特殊处理的字符
+                Constant::Utf8(String::from_utf8_lossy(reader.take(len)?).into_owned())
+            }
+            7 => Constant::Class(reader.u2()?),
+            3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => {
+                reader.take(4)?;
+                Constant::Other
+            }
+            5 | 6 => {
+                // long和double占两个位置
+                reader.take(8)?;
+                pool.push(Constant::Other);
+                Constant::Other
+            }
+            8 | 16 | 19 | 20 => {
+                reader.take(2)?;
+                Constant::Other
+            }
+            15 => {
+                reader.take(3)?;
+                Constant::Other
+            }
+            _ => return Err(anyhow::anyhow!("未知的常量池类型: {}", tag)),
+        };
+        pool.push(constant);
+    }
+
+    let utf8 = |index: u16| match pool.get(index as usize) {
+        Some(Constant::Utf8(value)) => Ok(value.as_str()),
+        _ => Err(anyhow::anyhow!("常量池索引无效: {}", index)),
+    };
+    let class_name = |index: u16| match pool.get(index as usize) {
+        Some(Constant::Class(name)) => utf8(*name),
+        _ => Err(anyhow::anyhow!("常量池索引无效: {}", index)),
+    };
+
+    reader.take(2)?; // access_flags
+    let name = class_name(reader.u2()?)?.to_string();
+    reader.take(2)?; // super_class，已经在常量池中
+    let interfaces = reader.u2()? as usize;
+    reader.take(interfaces * 2)?;
+
+    // 字段和方法的描述符都在常量池中，这里只需要找出编译期常量
+    let mut has_constants = false;
+    let fields = reader.u2()?;
+    for _ in 0..fields {
+        reader.take(6)?;
+        let attributes = reader.u2()?;
+        for _ in 0..attributes {
+            has_constants |= utf8(reader.u2()?)? == "ConstantValue";
+            let len = reader.u4()? as usize;
+            reader.take(len)?;
+        }
+    }
+    let methods = reader.u2()?;
+    for _ in 0..methods {
+        reader.take(6)?;
+        skip_attributes(&mut reader)?;
+    }
+
+    let mut source_file = None;
+    let attributes = reader.u2()?;
+    for _ in 0..attributes {
+        let attribute = utf8(reader.u2()?)?;
+        let len = reader.u4()? as usize;
+        if attribute == "SourceFile" && len == 2 {
+            source_file = Some(utf8(reader.u2()?)?.to_string());
+        } else {
+            reader.take(len)?;
+        }
+    }
+
+    let mut references = BTreeSet::new();
+    for constant in &pool {
+        match constant {
+            Constant::Class(index) => {
+                let referenced = utf8(*index)?;
+                if referenced.starts_with('[') {
+                    descriptor_classes(referenced, &mut references);
+                } else {
+                    references.insert(referenced.to_string());
+                }
+            }
+            Constant::Utf8(value) => descriptor_classes(value, &mut references),
+            Constant::Other => {}
+        }
+    }
+    references.remove(&name);
+
+    Ok(ClassInfo {
+        name,
+        source_file,
+        references,
+        has_constants,
+    })
+}
+
+/// 逐个改写常量池中的UTF-8常量，其余内容原样复制；没有任何改动时返回`None`
+///
+/// 类名、描述符、签名和字符串常量都以UTF-8常量保存，改写它们即可重命名类而不必理解字节码
+pub fn rewrite_utf8(
+    bytes: &[u8],
+    mut rewrite: impl FnMut(&[u8]) -> Option<Vec<u8>>,
+) -> Result<Option<Vec<u8>>> {
+    let mut reader = Reader { bytes, offset: 0 };
+    if reader.u4()? != 0xCAFE_BABE {
+        return Err(anyhow::anyhow!("不是有效的class文件"));
+    }
+    reader.take(4)?;
+    let count = reader.u2()?;
+
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut copied = 0;
+    let mut changed = false;
+    let mut index = 1;
+    while index < count {
+        let tag = reader.u1()?;
+        match tag {
+            1 => {
+                let start = reader.offset - 1;
+                let len = reader.u2()? as usize;
+                let value = reader.take(len)?;
+                if let Some(new_value) = rewrite(value) {
+                    let new_len = u16::try_from(new_value.len())
+                        .map_err(|_| anyhow::anyhow!("重定位后的常量超过65535字节"))?;
+                    out.extend_from_slice(&bytes[copied..start]);
+                    out.push(1);
+                    out.extend_from_slice(&new_len.to_be_bytes());
+                    out.extend_from_slice(&new_value);
+                    copied = reader.offset;
+                    changed = true;
+                }
+            }
+            3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => {
+                reader.take(4)?;
+            }
+            5 | 6 => {
+                reader.take(8)?;
+                index += 1;
+            }
+            7 | 8 | 16 | 19 | 20 => {
+                reader.take(2)?;
+            }
+            15 => {
+                reader.take(3)?;
+            }
+            _ => return Err(anyhow::anyhow!("未知的常量池类型: {}", tag)),
+        }
+        index += 1;
+    }
+
+    if !changed {
+        return Ok(None);
+    }
+    // 常量池之后的内容只通过索引引用常量，可以原样复制
+    out.extend_from_slice(&bytes[copied..]);
+    Ok(Some(out))
+}
+
+fn skip_attributes(reader: &mut Reader) -> Result<()> {
+    let count = reader.u2()?;
+    for _ in 0..count {
+        reader.take(2)?;
+        let len = reader.u4()? as usize;
+        reader.take(len)?;
+    }
+    Ok(())
+}
+
+/// 描述符和泛型签名中`L...;`形式的类型
+///
+/// 普通字符串也会被扫描，多出来的名字不会指向项目中的类，不影响结果
+fn descriptor_classes(value: &str, references: &mut BTreeSet<String>) {
+    let mut rest = value;
+    while let Some(start) = rest.find('L') {
+        let candidate = &rest[start + 1..];
+        let end = candidate
+            .find([';', '<'])
+            .unwrap_or(candidate.len());
+        let name = &candidate[..end];
+        if end < candidate.len()
+            && !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '/' | '$' | '_'))
+        {
+            references.insert(name.to_string());
+        }
+        rest = &rest[start + 1..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    enum Entry<'a> {
+        Utf8(&'a str),
+        Class(u16),
+        Long(i64),
+        Double(f64),
+        Integer(i32),
+    }
+
+    /// 只有常量池、类名和可选的SourceFile属性的最小class文件
+    fn class_file(pool: &[Entry], this_class: u16, source_file: Option<(u16, u16)>) -> Vec<u8> {
+        class_file_with_fields(pool, this_class, source_file, &[])
+    }
+
+    /// `fields`中每一项是一个字段唯一属性的名称索引，属性内容固定为常量池索引1
+    fn class_file_with_fields(
+        pool: &[Entry],
+        this_class: u16,
+        source_file: Option<(u16, u16)>,
+        fields: &[u16],
+    ) -> Vec<u8> {
+        let mut constants = Vec::new();
+        let mut count: u16 = 1;
+        for entry in pool {
+            match entry {
+                Entry::Utf8(value) => {
+                    constants.push(1);
+                    constants.extend_from_slice(&(value.len() as u16).to_be_bytes());
+                    constants.extend_from_slice(value.as_bytes());
+                }
+                Entry::Class(index) => {
+                    constants.push(7);
+                    constants.extend_from_slice(&index.to_be_bytes());
+                }
+                Entry::Long(value) => {
+                    constants.push(5);
+                    constants.extend_from_slice(&value.to_be_bytes());
+                    count += 1;
+                }
+                Entry::Double(value) => {
+                    constants.push(6);
+                    constants.extend_from_slice(&value.to_be_bytes());
+                    count += 1;
+                }
+                Entry::Integer(value) => {
+                    constants.push(3);
+                    constants.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+            count += 1;
+        }
+
+        let mut bytes = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 52];
+        bytes.extend_from_slice(&count.to_be_bytes());
+        bytes.extend_from_slice(&constants);
+        bytes.extend_from_slice(&0x21u16.to_be_bytes()); // access_flags
+        bytes.extend_from_slice(&this_class.to_be_bytes());
+        bytes.extend_from_slice(&0u16.to_be_bytes()); // super_class
+        bytes.extend_from_slice(&[0, 0]); // interfaces
+        bytes.extend_from_slice(&(fields.len() as u16).to_be_bytes());
+        for attribute in fields {
+            bytes.extend_from_slice(&[0, 0x19, 0, 0, 0, 0]); // access_flags、name、descriptor
+            bytes.extend_from_slice(&1u16.to_be_bytes());
+            bytes.extend_from_slice(&attribute.to_be_bytes());
+            bytes.extend_from_slice(&2u32.to_be_bytes());
+            bytes.extend_from_slice(&1u16.to_be_bytes());
+        }
+        bytes.extend_from_slice(&[0, 0]); // methods
+        match source_file {
+            Some((name, value)) => {
+                bytes.extend_from_slice(&1u16.to_be_bytes());
+                bytes.extend_from_slice(&name.to_be_bytes());
+                bytes.extend_from_slice(&2u32.to_be_bytes());
+                bytes.extend_from_slice(&value.to_be_bytes());
+            }
+            None => bytes.extend_from_slice(&0u16.to_be_bytes()),
+        }
+        bytes
+    }
+
+    #[test]
+    fn parse_counts_long_and_double_as_two_slots() {
+        let bytes = class_file(
+            &[
+                Entry::Long(1),                       // 1-2
+                Entry::Utf8("com/example/Main"),      // 3
+                Entry::Class(3),                      // 4
+                Entry::Double(2.0),                   // 5-6
+                Entry::Utf8("SourceFile"),            // 7
+                Entry::Utf8("Main.java"),             // 8
+                Entry::Utf8("com/example/Other"),     // 9
+                Entry::Class(9),                      // 10
+            ],
+            4,
+            Some((7, 8)),
+        );
+
+        let info = parse(&bytes).unwrap();
+        assert_eq!(info.name, "com/example/Main");
+        assert_eq!(info.source_file.as_deref(), Some("Main.java"));
+        assert_eq!(info.references, BTreeSet::from(["com/example/Other".to_string()]));
+    }
+
+    #[test]
+    fn parse_collects_descriptor_array_and_signature_types() {
+        let bytes = class_file(
+            &[
+                Entry::Utf8("com/example/Main"),                        // 1
+                Entry::Class(1),                                        // 2
+                Entry::Utf8("(Lcom/example/A;[Lcom/example/B;)V"),      // 3
+                Entry::Utf8("[[Lcom/example/C;"),                       // 4
+                Entry::Class(4),                                        // 5
+                Entry::Utf8("Ljava/util/List<Lcom/example/D$Inner;>;"), // 6
+                Entry::Utf8("Hello, world"),                            // 7
+            ],
+            2,
+            None,
+        );
+
+        // 扫描可能多出不存在的名字(例如List中的"ist")，记录时会按项目中的类过滤
+        let info = parse(&bytes).unwrap();
+        for name in [
+            "com/example/A",
+            "com/example/B",
+            "com/example/C",
+            "com/example/D$Inner",
+            "java/util/List",
+        ] {
+            assert!(info.references.contains(name), "缺少 {}", name);
+        }
+        assert!(!info.references.contains("com/example/Main"));
+        assert!(!info.references.iter().any(|name| name.starts_with('[')));
+    }
+
+    #[test]
+    fn parse_flags_compile_time_constants() {
+        let pool = [
+            Entry::Integer(42),                // 1
+            Entry::Utf8("com/example/Config"), // 2
+            Entry::Class(2),                   // 3
+            Entry::Utf8("ConstantValue"),      // 4
+            Entry::Utf8("Deprecated"),         // 5
+        ];
+
+        let info = parse(&class_file_with_fields(&pool, 3, None, &[5, 4])).unwrap();
+        assert!(info.has_constants);
+
+        let info = parse(&class_file_with_fields(&pool, 3, None, &[5])).unwrap();
+        assert!(!info.has_constants);
+    }
+
+    #[test]
+    fn source_path_falls_back_to_outer_class_name() {
+        let info = ClassInfo {
+            name: "com/example/Main$Inner".to_string(),
+            source_file: None,
+            references: BTreeSet::new(),
+            has_constants: false,
+        };
+        assert_eq!(info.source_path(), "com/example/Main.java");
+
+        let info = ClassInfo {
+            source_file: Some("Other.java".to_string()),
+            ..info
+        };
+        assert_eq!(info.source_path(), "com/example/Other.java");
+    }
+
+    #[test]
+    fn rewrite_utf8_changes_lengths_and_keeps_the_rest() {
+        let bytes = class_file(
+            &[
+                Entry::Long(-1),                 // 1-2
+                Entry::Utf8("com/a/Main"),       // 3
+                Entry::Class(3),                 // 4
+                Entry::Integer(7),               // 5
+                Entry::Utf8("SourceFile"),       // 6
+                Entry::Utf8("Main.java"),        // 7
+                Entry::Utf8("Lcom/a/Other;"),    // 8
+            ],
+            4,
+            Some((6, 7)),
+        );
+
+        let rewritten = rewrite_utf8(&bytes, |value| {
+            let value = std::str::from_utf8(value).unwrap();
+            value
+                .contains("com/a/")
+                .then(|| value.replace("com/a/", "shaded/com/a/").into_bytes())
+        })
+        .unwrap()
+        .unwrap();
+
+        let info = parse(&rewritten).unwrap();
+        assert_eq!(info.name, "shaded/com/a/Main");
+        assert_eq!(info.source_file.as_deref(), Some("Main.java"));
+        assert_eq!(info.references, BTreeSet::from(["shaded/com/a/Other".to_string()]));
+        assert_eq!(rewritten.len(), bytes.len() + 2 * "shaded/".len());
+    }
+
+    #[test]
+    fn rewrite_utf8_returns_none_without_changes() {
+        let bytes = class_file(&[Entry::Utf8("Main"), Entry::Class(1)], 2, None);
+        assert!(rewrite_utf8(&bytes, |_| None).unwrap().is_none());
+        assert!(rewrite_utf8(b"not a class", |_| None).is_err());
+    }
+}
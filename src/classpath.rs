@@ -0,0 +1,127 @@
+use crate::config::{JxConfig, ProjectType};
+use crate::dependency::{Dependency, DependencyScope};
+use crate::lock::LockFile;
+use crate::profile::Profile;
+use crate::workspace;
+use anyhow::Result;
+use std::collections::{HashSet, VecDeque};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 项目的运行classpath: 编译输出目录加上lib/中的jar (按文件名排序)
+pub fn project_classpath(project_dir: &Path, config: &JxConfig) -> Result<Vec<PathBuf>> {
+    let mut classpath = output_dirs(project_dir, config);
+
+    let lib_dir = project_dir.join("lib");
+    if lib_dir.exists() {
+        let mut jars: Vec<PathBuf> = fs::read_dir(&lib_dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("jar"))
+            .collect();
+        jars.sort();
+        classpath.extend(jars);
+    }
+    Ok(classpath)
+}
+
+/// 按平台的分隔符拼接classpath
+pub fn join(classpath: &[PathBuf]) -> Result<OsString> {
+    std::env::join_paths(classpath).map_err(|e| anyhow::anyhow!("无法拼接classpath: {}", e))
+}
+
+/// 编译输出目录，Gradle项目使用build/下的目录
+pub fn output_dirs(project_dir: &Path, config: &JxConfig) -> Vec<PathBuf> {
+    match config.project.project_type {
+        ProjectType::Gradle => vec![
+            project_dir.join("build/classes/java/main"),
+            project_dir.join("build/resources/main"),
+        ],
+        ProjectType::Maven => vec![project_dir.join("target/classes")],
+        ProjectType::Jx => {
+            let target_dir = config.build.target_dir.as_deref().unwrap_or("target");
+            vec![project_dir.join(target_dir).join("classes")]
+        }
+    }
+}
+
+/// 注解处理器生成的源码目录，供IDE加入源码路径
+pub fn generated_sources_dir(project_dir: &Path, config: &JxConfig) -> PathBuf {
+    match config.project.project_type {
+        ProjectType::Gradle => project_dir.join("build/generated/sources/annotationProcessor/java/main"),
+        ProjectType::Maven => project_dir.join("target/generated-sources/annotations"),
+        ProjectType::Jx => {
+            let target_dir = config.build.target_dir.as_deref().unwrap_or("target");
+            project_dir.join(target_dir).join("generated-sources")
+        }
+    }
+}
+
+/// 当前profile使用的外部依赖
+///
+/// 有jx.lock时为从项目自己的直接依赖和当前profile的依赖出发能到达的锁定依赖，
+/// 不包括工作区其他成员和其他profile的依赖；没有锁文件时只有直接依赖
+pub fn profile_dependencies(project_dir: &Path, config: &JxConfig, profile: &Profile) -> Result<Vec<Dependency>> {
+    let lock_path = workspace::lock_path(project_dir)?;
+    if lock_path.exists() {
+        let lock_file = LockFile::load(&lock_path)?;
+        let roots: Vec<Dependency> = config
+            .dependencies()
+            .iter()
+            .chain(&profile.dependencies)
+            .cloned()
+            .collect();
+        return Ok(lock_file
+            .reachable_from(&roots)
+            .iter()
+            .map(|dep| dep.to_dependency())
+            .collect());
+    }
+
+    Ok(config
+        .dependencies()
+        .iter()
+        .chain(&profile.dependencies)
+        .cloned()
+        .collect())
+}
+
+/// 当前profile的注解处理器及其传递依赖，按声明顺序
+///
+/// 有jx.lock时从声明的处理器出发沿锁定的依赖图收集，同一构件也在classpath上时使用classpath锁定的条目；
+/// 没有锁文件时只有声明的处理器
+pub fn processor_dependencies(project_dir: &Path, config: &JxConfig, profile: &Profile) -> Result<Vec<Dependency>> {
+    let declared: Vec<&Dependency> = config
+        .dependencies()
+        .iter()
+        .chain(&profile.dependencies)
+        .filter(|dep| matches!(dep.scope, DependencyScope::AnnotationProcessor))
+        .collect();
+    let lock_path = workspace::lock_path(project_dir)?;
+    if declared.is_empty() || !lock_path.exists() {
+        return Ok(declared.into_iter().cloned().collect());
+    }
+
+    let lock_file = LockFile::load(&lock_path)?;
+    let mut processors = Vec::new();
+    let mut visited = HashSet::new();
+    for dependency in declared {
+        let Some(root) = lock_file.get_dependency(&dependency.group_id, &dependency.artifact_id, &dependency.version)
+        else {
+            // 声明后还没有重新锁定，缺少的jar会在编译前报告
+            processors.push(dependency.clone());
+            continue;
+        };
+        let mut queue = VecDeque::from([root.coordinate()]);
+        while let Some(coordinate) = queue.pop_front() {
+            if !visited.insert(coordinate.clone()) {
+                continue;
+            }
+            if let Some(locked) = lock_file.dependencies.get(&coordinate) {
+                processors.push(locked.to_dependency());
+                queue.extend(locked.dependencies.iter().cloned());
+            }
+        }
+    }
+    Ok(processors)
+}
@@ -1,20 +1,39 @@
+use crate::dependency::DependencyScope;
+use crate::gradle::{self, GradleScript};
+use crate::manifest::ManifestEditor;
+use crate::pom_editor::PomEditor;
+use crate::registry::MavenRegistry;
+use crate::workspace;
 use anyhow::Result;
 use std::fs;
 use std::path::Path;
 
-pub fn execute(dependency: String, scope: String) -> Result<()> {
-    let current_dir = std::env::current_dir()?;
+pub async fn execute(
+    dependency: String,
+    scope: String,
+    offline: bool,
+    managed: bool,
+    alias: Option<String>,
+    package: Option<String>,
+) -> Result<()> {
+    let current_dir = workspace::project_dir(package.as_deref())?;
     
     // 查找项目配置文件
     let config_file = if current_dir.join("jx.toml").exists() {
         "jx.toml"
     } else if current_dir.join("pom.xml").exists() {
         "pom.xml"
-    } else if current_dir.join("build.gradle").exists() {
-        "build.gradle"
+    } else if let Some(script) = gradle::build_script(&current_dir) {
+        script
     } else {
         return Err(anyhow::anyhow!("找不到项目配置文件，请先运行 'jx init'"));
     };
+    if managed && config_file != "pom.xml" {
+        return Err(anyhow::anyhow!("--managed 仅适用于pom.xml"));
+    }
+    if alias.is_some() && !config_file.starts_with("build.gradle") {
+        return Err(anyhow::anyhow!("--alias 仅适用于使用版本目录的Gradle项目"));
+    }
 
     println!("➕ 添加依赖...");
     println!("依赖: {}", dependency);
@@ -25,9 +44,11 @@ pub fn execute(dependency: String, scope: String) -> Result<()> {
     
     // 根据配置文件类型添加依赖
     let result = match config_file {
-        "jx.toml" => add_to_jx_config(&current_dir, &dep_info, &scope),
-        "pom.xml" => add_to_maven(&current_dir, &dep_info, &scope),
-        "build.gradle" => add_to_gradle(&current_dir, &dep_info, &scope),
+        "jx.toml" => add_to_jx_config(&current_dir, &dep_info, &scope, offline).await,
+        "pom.xml" => add_to_maven(&current_dir, &dep_info, &scope, offline, managed).await,
+        "build.gradle" | "build.gradle.kts" => {
+            add_to_gradle(&current_dir, &dep_info, &scope, offline, alias.as_deref()).await
+        }
         _ => Err(anyhow::anyhow!("不支持的配置文件类型")),
     };
 
@@ -69,7 +90,12 @@ fn parse_dependency_coordinate(coordinate: &str) -> Result<DependencyInfo> {
     }
 }
 
-fn add_to_jx_config(project_dir: &Path, dep_info: &DependencyInfo, _scope: &str) -> Result<()> {
+async fn add_to_jx_config(
+    project_dir: &Path,
+    dep_info: &DependencyInfo,
+    scope: &str,
+    offline: bool,
+) -> Result<()> {
     let config_path = project_dir.join("jx.toml");
     
     if !config_path.exists() {
@@ -90,133 +116,154 @@ test_class = "com.example.MainTest"
         );
         fs::write(&config_path, basic_config)?;
     }
-    
-    let config_content = fs::read_to_string(&config_path)?;
-    
-    // 简单的TOML解析和修改
-    let mut lines: Vec<String> = config_content.lines().map(|s| s.to_string()).collect();
-    
-    // 查找dependencies部分
-    let mut in_dependencies = false;
-    let mut _dependencies_start = 0;
-    
-    for (i, line) in lines.iter().enumerate() {
-        if line.trim() == "[dependencies]" {
-            in_dependencies = true;
-            _dependencies_start = i;
-            break;
+
+    let mut editor = ManifestEditor::open(&config_path)?;
+
+    let version = resolve_version(dep_info, offline).await?;
+
+    let key = format!("{}:{}", dep_info.group_id, dep_info.artifact_id);
+    let scope = DependencyScope::from_name(scope);
+    match editor.upsert_dependency(&key, &version, &scope) {
+        Some((_, old_scope)) if old_scope != scope.as_str() => {
+            println!("已将 {} 从 {} 移到 {} scope", key, old_scope, scope.as_str());
+        }
+        Some((old_version, _)) if old_version != version => {
+            println!("已将 jx.toml 中的 {} 从 {} 更新为 {}", key, old_version, version);
         }
+        Some(_) => println!("{} 已在 jx.toml 中声明", key),
+        None => println!("已添加到 jx.toml"),
     }
-    
-    if !in_dependencies {
-        // 如果没有dependencies部分，添加一个
-        lines.push("[dependencies]".to_string());
-        _dependencies_start = lines.len() - 1;
+    editor.save()
+}
+
+async fn add_to_maven(
+    project_dir: &Path,
+    dep_info: &DependencyInfo,
+    scope: &str,
+    offline: bool,
+    managed: bool,
+) -> Result<()> {
+    if scope == "annotation-processor" {
+        return Err(anyhow::anyhow!(
+            "Maven项目的注解处理器需要在maven-compiler-plugin的<annotationProcessorPaths>中配置"
+        ));
     }
-    
-    // 构建依赖行
-    let dep_line = if let Some(version) = &dep_info.version {
-        format!("{}:{} = \"{}\"", dep_info.group_id, dep_info.artifact_id, version)
+    let pom_path = project_dir.join("pom.xml");
+    let mut editor = PomEditor::open(&pom_path)?;
+    let (group_id, artifact_id) = (&dep_info.group_id, &dep_info.artifact_id);
+
+    if editor.has_dependency(group_id, artifact_id, managed) {
+        let Some(version) = &dep_info.version else {
+            return Err(anyhow::anyhow!(
+                "{}:{} 已在pom.xml中声明",
+                group_id,
+                artifact_id
+            ));
+        };
+        let change = editor.set_version(group_id, artifact_id, version, managed)?;
+        editor.save()?;
+        println!(
+            "已将 pom.xml 中的 {}:{} 从 {} 更新为 {}",
+            group_id, artifact_id, change.old_version, version
+        );
+        return Ok(());
+    }
+
+    // 已由<dependencyManagement>管理时不写<version>
+    let version = if dep_info.version.is_none()
+        && !managed
+        && editor.has_dependency(group_id, artifact_id, true)
+    {
+        println!("版本由<dependencyManagement>管理");
+        None
     } else {
-        format!("{}:{} = \"*\"", dep_info.group_id, dep_info.artifact_id)
+        Some(resolve_version(dep_info, offline).await?)
     };
-    
-    // 在dependencies部分后添加依赖
-    lines.insert(_dependencies_start + 1, dep_line);
-    
-    // 写回文件
-    fs::write(&config_path, lines.join("\n"))?;
-    
-    println!("已添加到 jx.toml");
+
+    editor.add_dependency(group_id, artifact_id, version.as_deref(), Some(scope), managed)?;
+    editor.save()?;
+
+    if managed {
+        println!("已添加到 pom.xml 的 <dependencyManagement>");
+    } else {
+        println!("已添加到 pom.xml");
+    }
     Ok(())
 }
 
-fn add_to_maven(project_dir: &Path, dep_info: &DependencyInfo, scope: &str) -> Result<()> {
-    let pom_path = project_dir.join("pom.xml");
-    let pom_content = fs::read_to_string(&pom_path)?;
-    
-    // 简单的XML解析和修改
-    let mut lines: Vec<String> = pom_content.lines().map(|s| s.to_string()).collect();
-    
-    // 查找dependencies部分
-    let mut in_dependencies = false;
-    let mut _dependencies_start = 0;
-    let mut dependencies_end = 0;
-    
-    for (i, line) in lines.iter().enumerate() {
-        if line.trim() == "<dependencies>" {
-            in_dependencies = true;
-            _dependencies_start = i;
-        } else if in_dependencies && line.trim() == "</dependencies>" {
-            dependencies_end = i;
-            break;
-        }
+/// 没有指定版本时使用Maven Central上的最新发布版本
+async fn resolve_version(dep_info: &DependencyInfo, offline: bool) -> Result<String> {
+    if let Some(version) = &dep_info.version {
+        return Ok(version.clone());
     }
-    
-    if !in_dependencies {
-        return Err(anyhow::anyhow!("在pom.xml中找不到dependencies部分"));
+    if offline {
+        return Err(anyhow::anyhow!(
+            "离线模式下无法查询最新版本，请指定版本: {}:{}:<version>",
+            dep_info.group_id,
+            dep_info.artifact_id
+        ));
     }
-    
-    // 构建依赖XML
-    let dep_xml = format!(
-        r#"        <dependency>
-            <groupId>{}</groupId>
-            <artifactId>{}</artifactId>
-            <version>{}</version>
-            <scope>{}</scope>
-        </dependency>"#,
-        dep_info.group_id,
-        dep_info.artifact_id,
-        dep_info.version.as_ref().unwrap_or(&"*".to_string()),
-        scope
-    );
-    
-    // 在</dependencies>前添加依赖
-    lines.insert(dependencies_end, dep_xml);
-    
-    // 写回文件
-    fs::write(&pom_path, lines.join("\n"))?;
-    
-    println!("已添加到 pom.xml");
-    Ok(())
+
+    let version = MavenRegistry::new()
+        .latest_release(&dep_info.group_id, &dep_info.artifact_id)
+        .await?;
+    println!("最新版本: {}", version);
+    Ok(version)
 }
 
-fn add_to_gradle(project_dir: &Path, dep_info: &DependencyInfo, scope: &str) -> Result<()> {
-    let build_gradle_path = project_dir.join("build.gradle");
-    let build_content = fs::read_to_string(&build_gradle_path)?;
-    
-    // 简单的Gradle解析和修改
-    let mut lines: Vec<String> = build_content.lines().map(|s| s.to_string()).collect();
-    
-    // 查找dependencies部分
-    let mut in_dependencies = false;
-    let mut _dependencies_start = 0;
-    
-    for (i, line) in lines.iter().enumerate() {
-        if line.trim() == "dependencies {" {
-            in_dependencies = true;
-            _dependencies_start = i;
-            break;
-        }
+async fn add_to_gradle(
+    project_dir: &Path,
+    dep_info: &DependencyInfo,
+    scope: &str,
+    offline: bool,
+    alias: Option<&str>,
+) -> Result<()> {
+    let mut script = GradleScript::open(project_dir)?;
+    let (group_id, artifact_id) = (&dep_info.group_id, &dep_info.artifact_id);
+
+    if script.has_dependency(group_id, artifact_id) {
+        let Some(version) = &dep_info.version else {
+            return Err(anyhow::anyhow!(
+                "{}:{} 已在{}中声明",
+                group_id,
+                artifact_id,
+                script.file_name()
+            ));
+        };
+        let change = script.set_version(group_id, artifact_id, version)?;
+        script.save()?;
+        let file = match change.catalog {
+            Some(_) => "gradle/libs.versions.toml",
+            None => script.file_name(),
+        };
+        println!(
+            "已将 {} 中的 {}:{} 从 {} 更新为 {}",
+            file,
+            group_id,
+            artifact_id,
+            change.old_version.as_deref().unwrap_or("未指定"),
+            version
+        );
+        return Ok(());
     }
-    
-    if !in_dependencies {
-        return Err(anyhow::anyhow!("在build.gradle中找不到dependencies部分"));
+
+    let version = resolve_version(dep_info, offline).await?;
+    let configuration = gradle::configuration(scope);
+    if script.catalog().is_some() {
+        // 项目使用版本目录时，版本写在gradle/libs.versions.toml中
+        let accessor =
+            script.add_catalog_dependency(configuration, alias, group_id, artifact_id, &version)?;
+        script.save()?;
+        println!("已添加到 gradle/libs.versions.toml，{} 中引用为 {}", script.file_name(), accessor);
+        return Ok(());
     }
-    
-    // 构建依赖行
-    let dep_line = if let Some(version) = &dep_info.version {
-        format!("    {} '{}:{}:{}'", scope, dep_info.group_id, dep_info.artifact_id, version)
-    } else {
-        format!("    {} '{}:{}'", scope, dep_info.group_id, dep_info.artifact_id)
-    };
-    
-    // 在dependencies部分后添加依赖
-    lines.insert(_dependencies_start + 1, dep_line);
-    
-    // 写回文件
-    fs::write(&build_gradle_path, lines.join("\n"))?;
-    
-    println!("已添加到 build.gradle");
+    if alias.is_some() {
+        return Err(anyhow::anyhow!("找不到版本目录 gradle/libs.versions.toml，不能使用 --alias"));
+    }
+
+    script.add_dependency(configuration, group_id, artifact_id, Some(&version));
+    script.save()?;
+
+    println!("已添加到 {}", script.file_name());
     Ok(())
 }
@@ -1,32 +1,67 @@
+use crate::commands::package;
+use crate::config::{JxConfig, ProjectType};
+use crate::gradle;
+use crate::install::ensure_offline_ready;
+use crate::javac;
+use crate::modules::{self, Module};
+use crate::profile::Profile;
+use crate::workspace::Workspace;
 use anyhow::{Context, Result};
 use std::path::Path;
 use std::process::Command;
 
-pub fn execute(mode: String, no_test: bool) -> Result<()> {
+pub fn execute(profile: String, no_test: bool, offline: bool, packages: Vec<String>) -> Result<()> {
     let current_dir = std::env::current_dir()?;
-    
+    let workspace = Workspace::discover(&current_dir)?;
+
     // 查找项目配置文件
     let config_file = if current_dir.join("jx.toml").exists() {
         "jx.toml"
     } else if current_dir.join("pom.xml").exists() {
         "pom.xml"
-    } else if current_dir.join("build.gradle").exists() {
-        "build.gradle"
+    } else if let Some(script) = gradle::build_script(&current_dir) {
+        script
+    } else if let Some(script) = gradle::settings_script(&current_dir) {
+        // 多项目构建的根目录可以只有设置脚本
+        script
     } else {
         return Err(anyhow::anyhow!("找不到项目配置文件，请先运行 'jx init'"));
     };
 
     println!("🔨 构建项目...");
-    println!("构建模式: {}", mode);
+    println!("构建配置: {}", profile);
     if no_test {
         println!("跳过测试");
     }
+    if offline {
+        println!("离线模式: 仅使用本地缓存");
+    }
+
+    // 不是jx工作区时，-p 选择Maven或Gradle的子模块
+    let selected = match &workspace {
+        Some(_) => Vec::new(),
+        None => modules::select(&current_dir, &packages)?,
+    };
+    if !selected.is_empty() {
+        let names: Vec<&str> = selected.iter().map(|module| module.name.as_str()).collect();
+        println!("构建模块: {}", names.join(", "));
+    }
 
     // 根据配置文件类型构建项目
-    let result = match config_file {
-        "jx.toml" => build_jx_project(&current_dir, &mode, no_test),
-        "pom.xml" => build_maven_project(&current_dir, &mode, no_test),
-        "build.gradle" => build_gradle_project(&current_dir, &mode, no_test),
+    let result = match (config_file, &workspace) {
+        ("jx.toml", Some(workspace)) => {
+            build_workspace(workspace, &current_dir, &packages, &profile, no_test, offline)
+        }
+        ("jx.toml", None) => Profile::load(&current_dir, &profile)
+            .and_then(|profile| build_jx_project(&current_dir, &profile, no_test, offline)),
+        ("pom.xml", _) => Profile::load(&current_dir, &profile).and_then(|profile| {
+            build_maven_project(&current_dir, &profile, no_test, offline, &selected)
+        }),
+        ("build.gradle" | "build.gradle.kts" | "settings.gradle" | "settings.gradle.kts", _) => {
+            Profile::load(&current_dir, &profile).and_then(|profile| {
+                build_gradle_project(&current_dir, &profile, no_test, offline, &selected)
+            })
+        }
         _ => Err(anyhow::anyhow!("不支持的配置文件类型")),
     };
 
@@ -42,39 +77,89 @@ pub fn execute(mode: String, no_test: bool) -> Result<()> {
     }
 }
 
-fn build_jx_project(project_dir: &Path, mode: &str, no_test: bool) -> Result<()> {
-    // 检查jx.toml中的项目类型
-    let config_path = project_dir.join("jx.toml");
-    let config_content = std::fs::read_to_string(&config_path)?;
-    
-    if config_content.contains("type = \"maven\"") {
-        build_maven_project(project_dir, mode, no_test)
-    } else if config_content.contains("type = \"gradle\"") {
-        build_gradle_project(project_dir, mode, no_test)
-    } else {
-        Err(anyhow::anyhow!("在jx.toml中找不到有效的项目类型"))
+fn build_jx_project(project_dir: &Path, profile: &Profile, no_test: bool, offline: bool) -> Result<()> {
+    if offline {
+        ensure_offline_ready(project_dir)?;
+    }
+
+    let config = JxConfig::load_from_dir(project_dir)?;
+    match config.project.project_type {
+        ProjectType::Maven => build_maven_project(project_dir, profile, no_test, offline, &[]),
+        ProjectType::Gradle => build_gradle_project(project_dir, profile, no_test, offline, &[]),
+        ProjectType::Jx => {
+            println!("使用javac构建项目...");
+            javac::build(project_dir, &config, profile)?;
+            if profile.package {
+                package::package_jx_project(project_dir, &config, profile)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// 按路径依赖的顺序构建选中的成员，被依赖的成员先构建
+fn build_workspace(
+    workspace: &Workspace,
+    current_dir: &Path,
+    packages: &[String],
+    profile: &str,
+    no_test: bool,
+    offline: bool,
+) -> Result<()> {
+    let selected = workspace.select(packages, current_dir)?;
+    let members = workspace.with_dependencies(&selected)?;
+    println!("工作区成员: {}", members.iter().map(|m| m.name()).collect::<Vec<_>>().join(" -> "));
+
+    for member in members {
+        println!("\n📦 构建 {} ({})", member.name(), member.path);
+        Profile::load(&member.dir, profile)
+            .and_then(|profile| build_jx_project(&member.dir, &profile, no_test, offline))
+            .map_err(|e| anyhow::anyhow!("成员 {} 构建失败: {}", member.name(), e))?;
     }
+    Ok(())
 }
 
-fn build_maven_project(project_dir: &Path, mode: &str, no_test: bool) -> Result<()> {
+fn build_maven_project(
+    project_dir: &Path,
+    profile: &Profile,
+    no_test: bool,
+    offline: bool,
+    modules: &[Module],
+) -> Result<()> {
     println!("使用Maven构建项目...");
     
     if !check_command_exists("mvn") {
         return Err(anyhow::anyhow!("Maven未安装，请先安装Maven"));
     }
     
+    if let Some(warning) = profile.maven_warning() {
+        println!("{}", warning);
+    }
+
     // 构建Maven命令
     let mut mvn_args = vec!["clean"];
     
-    match mode {
-        "release" => mvn_args.push("package"),
-        "debug" => mvn_args.push("compile"),
-        _ => mvn_args.push("compile"),
+    if profile.package {
+        mvn_args.push("package");
+    } else {
+        mvn_args.push("compile");
     }
     
+    let arg_line = profile.maven_arg_line();
     if no_test {
         mvn_args.push("-DskipTests");
+    } else if let Some(arg_line) = &arg_line {
+        mvn_args.push(arg_line);
     }
+
+    if offline {
+        mvn_args.push("-o");
+    }
+
+    let module_args = modules::maven_arguments(modules);
+    mvn_args.extend(module_args.iter().map(String::as_str));
+    let profile_args = profile.maven_arguments();
+    mvn_args.extend(profile_args.iter().map(String::as_str));
     
     println!("执行Maven命令: mvn {}", mvn_args.join(" "));
     
@@ -98,7 +183,13 @@ fn build_maven_project(project_dir: &Path, mode: &str, no_test: bool) -> Result<
     Ok(())
 }
 
-fn build_gradle_project(project_dir: &Path, mode: &str, no_test: bool) -> Result<()> {
+fn build_gradle_project(
+    project_dir: &Path,
+    profile: &Profile,
+    no_test: bool,
+    offline: bool,
+    modules: &[Module],
+) -> Result<()> {
     println!("使用Gradle构建项目...");
     
     if !check_command_exists("gradle") {
@@ -106,18 +197,21 @@ fn build_gradle_project(project_dir: &Path, mode: &str, no_test: bool) -> Result
     }
     
     // 构建Gradle命令
-    let mut gradle_args = vec!["clean"];
-    
-    match mode {
-        "release" => gradle_args.push("build"),
-        "debug" => gradle_args.push("compileJava"),
-        _ => gradle_args.push("compileJava"),
-    }
+    let build_task = if profile.package { "build" } else { "compileJava" };
+    let tasks = modules::gradle_tasks(modules, &["clean", build_task]);
+    let mut gradle_args: Vec<&str> = tasks.iter().map(String::as_str).collect();
     
     if no_test {
         gradle_args.push("-x");
         gradle_args.push("test");
     }
+
+    if offline {
+        gradle_args.push("--offline");
+    }
+
+    let profile_args = profile.gradle_arguments()?;
+    gradle_args.extend(profile_args.iter().map(String::as_str));
     
     println!("执行Gradle命令: gradle {}", gradle_args.join(" "));
     
@@ -1,3 +1,4 @@
+use crate::gradle;
 use anyhow::Result;
 use std::fs;
 use std::path::Path;
@@ -71,14 +72,13 @@ pub fn execute() -> Result<()> {
 
 fn detect_project_type(project_dir: &Path) -> Result<String> {
     let has_pom = project_dir.join("pom.xml").exists();
-    let has_gradle = project_dir.join("build.gradle").exists();
-    let has_settings_gradle = project_dir.join("settings.gradle").exists();
+    let has_gradle = gradle::is_gradle_project(project_dir);
     
-    if has_pom && (has_gradle || has_settings_gradle) {
+    if has_pom && has_gradle {
         Ok("both".to_string())
     } else if has_pom {
         Ok("maven".to_string())
-    } else if has_gradle || has_settings_gradle {
+    } else if has_gradle {
         Ok("gradle".to_string())
     } else {
         Ok("unknown".to_string())
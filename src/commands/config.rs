@@ -0,0 +1,157 @@
+use crate::commands::run_script;
+use crate::config::{legacy_key_lines, JxConfig, WorkspaceManifest};
+use crate::profile::Profile;
+use crate::settings::{self, Origin, Settings};
+use crate::workspace::Workspace;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// 校验jx.toml，解析失败时报告具体的行和列
+pub fn validate(file: Option<String>) -> Result<()> {
+    let config_path = match file {
+        Some(file) => PathBuf::from(file),
+        None => std::env::current_dir()?.join("jx.toml"),
+    };
+    if !config_path.exists() {
+        return Err(anyhow::anyhow!("找不到 {}", config_path.display()));
+    }
+
+    println!("🔍 校验 {}...", config_path.display());
+
+    let content = fs::read_to_string(&config_path)?;
+
+    // 工作区根配置: 校验所有成员、成员间的路径依赖以及共享锁文件的依赖版本
+    if WorkspaceManifest::load(&config_path)?.is_some() {
+        let root = config_path.parent().unwrap_or(&config_path);
+        let workspace = Workspace::load(root)?;
+        workspace.dependencies()?;
+        println!("工作区成员: {}", workspace.member_names().join(", "));
+
+        if JxConfig::parse(&content, &config_path).is_err() {
+            println!("✅ 工作区配置有效");
+            return Ok(());
+        }
+    }
+
+    let config = JxConfig::parse(&content, &config_path)?;
+
+    println!(
+        "项目: {} {} (类型: {})",
+        config.project.name,
+        config.project.version,
+        config.project.project_type.as_str()
+    );
+    if let Some(java_version) = config.java_version() {
+        println!("Java版本: {}", java_version);
+    }
+
+    let mut scopes: BTreeMap<&str, usize> = BTreeMap::new();
+    for dep in config.dependencies() {
+        *scopes.entry(dep.scope.as_str()).or_default() += 1;
+    }
+    let summary: Vec<String> = scopes
+        .iter()
+        .map(|(scope, count)| format!("{} {}", scope, count))
+        .collect();
+    if summary.is_empty() {
+        println!("依赖: 0");
+    } else {
+        println!("依赖: {} ({})", config.dependencies().len(), summary.join(", "));
+    }
+    if !config.path_dependencies().is_empty() {
+        println!("路径依赖: {}", config.path_dependencies().len());
+    }
+    if !config.profile.is_empty() {
+        // 逐个解析，报告继承关系中的环
+        for name in config.profile.keys() {
+            Profile::resolve(Some(&config), name)?;
+        }
+        let names: Vec<&str> = config.profile.keys().map(String::as_str).collect();
+        println!("Profile: {}", names.join(", "));
+    }
+    if !config.scripts.is_empty() {
+        for name in config.scripts.keys() {
+            run_script::run_order(&config, name)?;
+        }
+        let names: Vec<&str> = config.scripts.keys().map(String::as_str).collect();
+        println!("脚本: {}", names.join(", "));
+    }
+
+    let mut warnings = Vec::new();
+    for index in legacy_key_lines(&content) {
+        warnings.push(format!(
+            "第{}行: 依赖坐标没有加引号，请写成 \"groupId:artifactId\" = \"version\"",
+            index + 1
+        ));
+    }
+    for dep in config.dependencies() {
+        if dep.version == "*" {
+            warnings.push(format!(
+                "{}:{} 的版本为 \"*\"，请指定具体版本",
+                dep.group_id, dep.artifact_id
+            ));
+        }
+    }
+    for warning in &warnings {
+        println!("⚠️ {}", warning);
+    }
+
+    println!("✅ {} 有效", config_path.display());
+    Ok(())
+}
+
+/// 查看合并后的配置项
+pub fn get(key: &str, show_origin: bool) -> Result<()> {
+    let settings = Settings::load(&std::env::current_dir()?)?;
+    match settings.get(key)? {
+        Some((value, origin)) if show_origin => println!("{}\t{}", display_value(value), origin),
+        Some((value, _)) => println!("{}", display_value(value)),
+        None => println!("{} 未设置", key),
+    }
+    Ok(())
+}
+
+/// 写入用户配置或项目配置，新值被更高优先级的来源覆盖时给出提示
+pub fn set(key: &str, value: &str, local: bool) -> Result<()> {
+    let project_dir = std::env::current_dir()?;
+    let path = settings::set(key, value, local, &project_dir)?;
+    println!("已写入 {}: {} = {}", path.display(), key, value);
+
+    let settings = Settings::load(&project_dir)?;
+    if let Some((effective, origin)) = settings.get(key)? {
+        let written = match origin {
+            Origin::User(p) | Origin::Local(p) => *p == path,
+            _ => false,
+        };
+        if !written {
+            println!(
+                "⚠️  当前生效的值为 {}，来自{}",
+                display_value(effective),
+                origin
+            );
+        }
+    }
+    Ok(())
+}
+
+/// 列出合并后的所有配置项
+pub fn list(show_origin: bool) -> Result<()> {
+    let settings = Settings::load(&std::env::current_dir()?)?;
+    for (key, value, origin) in settings.entries() {
+        if show_origin {
+            println!("{} = {}\t{}", key, display_value(value), origin);
+        } else {
+            println!("{} = {}", key, display_value(value));
+        }
+    }
+    Ok(())
+}
+
+fn display_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
@@ -1,3 +1,9 @@
+use crate::catalog::to_accessor;
+use crate::classpath;
+use crate::config::JxConfig;
+use crate::gradle::{self, GradleScript};
+use crate::modules;
+use crate::pom::load_local_pom;
 use anyhow::Result;
 use std::fs;
 use std::path::Path;
@@ -17,6 +23,9 @@ pub fn execute() -> Result<()> {
     
     // 显示项目基本信息
     display_project_info(&project_info);
+
+    // 显示Maven/Gradle子模块
+    display_modules(&current_dir)?;
     
     // 显示依赖信息
     display_dependencies(&current_dir, &project_type)?;
@@ -47,17 +56,16 @@ struct ProjectInfo {
 
 fn detect_project_type(project_dir: &Path) -> Result<String> {
     let has_pom = project_dir.join("pom.xml").exists();
-    let has_gradle = project_dir.join("build.gradle").exists();
-    let has_settings_gradle = project_dir.join("settings.gradle").exists();
+    let has_gradle = gradle::is_gradle_project(project_dir);
     let has_jx = project_dir.join("jx.toml").exists();
     
     if has_jx {
         Ok("jx".to_string())
-    } else if has_pom && (has_gradle || has_settings_gradle) {
+    } else if has_pom && has_gradle {
         Ok("Maven + Gradle".to_string())
     } else if has_pom {
         Ok("Maven".to_string())
-    } else if has_gradle || has_settings_gradle {
+    } else if has_gradle {
         Ok("Gradle".to_string())
     } else {
         Ok("未知".to_string())
@@ -123,87 +131,42 @@ fn get_maven_project_info(project_dir: &Path) -> Result<ProjectInfo> {
 }
 
 fn get_gradle_project_info(project_dir: &Path) -> Result<ProjectInfo> {
-    let build_gradle_path = project_dir.join("build.gradle");
-    let build_content = fs::read_to_string(&build_gradle_path)?;
-    
-    let mut info = ProjectInfo {
-        name: "未知".to_string(),
-        version: "未知".to_string(),
-        description: None,
-        group_id: None,
+    let name = gradle::root_project_name(project_dir).unwrap_or_else(|| "未知".to_string());
+    // 多项目构建的根目录可以只有设置脚本
+    if gradle::build_script(project_dir).is_none() {
+        let mut info = get_generic_project_info(project_dir)?;
+        info.name = name;
+        return Ok(info);
+    }
+    let script = GradleScript::open(project_dir)?;
+
+    Ok(ProjectInfo {
+        name,
+        version: script.property("version").unwrap_or_else(|| "未知".to_string()),
+        description: script.property("description"),
+        group_id: script.property("group"),
         artifact_id: None,
         packaging: None,
-        java_version: None,
+        java_version: script.java_version(),
         source_encoding: None,
-    };
-    
-    let lines: Vec<&str> = build_content.lines().collect();
-    
-    for line in lines {
-        let line = line.trim();
-        
-        if line.starts_with("rootProject.name") {
-            if let Some(quote_start) = line.find('\'') {
-                if let Some(quote_end) = line.rfind('\'') {
-                    info.name = line[quote_start+1..quote_end].to_string();
-                }
-            }
-        } else if line.starts_with("version") {
-            if let Some(quote_start) = line.find('\'') {
-                if let Some(quote_end) = line.rfind('\'') {
-                    info.version = line[quote_start+1..quote_end].to_string();
-                }
-            }
-        } else if line.starts_with("group") {
-            if let Some(quote_start) = line.find('\'') {
-                if let Some(quote_end) = line.rfind('\'') {
-                    info.group_id = Some(line[quote_start+1..quote_end].to_string());
-                }
-            }
-        } else if line.starts_with("sourceCompatibility") {
-            if let Some(quote_start) = line.find('\'') {
-                if let Some(quote_end) = line.rfind('\'') {
-                    info.java_version = Some(line[quote_start+1..quote_end].to_string());
-                }
-            }
-        }
-    }
-    
-    Ok(info)
+    })
 }
 
 fn get_jx_project_info(project_dir: &Path) -> Result<ProjectInfo> {
-    let jx_path = project_dir.join("jx.toml");
-    let jx_content = fs::read_to_string(&jx_path)?;
-    
-    let mut info = ProjectInfo {
-        name: "未知".to_string(),
-        version: "未知".to_string(),
-        description: None,
-        group_id: None,
+    let config = JxConfig::load_from_dir(project_dir)?;
+    let java_version = config.java_version().map(str::to_string);
+    let project = config.project;
+
+    Ok(ProjectInfo {
+        name: project.name,
+        version: project.version,
+        description: project.description,
+        group_id: project.group_id,
         artifact_id: None,
         packaging: None,
-        java_version: None,
+        java_version,
         source_encoding: None,
-    };
-    
-    let lines: Vec<&str> = jx_content.lines().collect();
-    
-    for line in lines {
-        let line = line.trim();
-        
-        if line.starts_with("name = \"") {
-            info.name = line[8..line.len()-1].to_string();
-        } else if line.starts_with("version = \"") {
-            info.version = line[11..line.len()-1].to_string();
-        } else if line.starts_with("description = \"") {
-            info.description = Some(line[15..line.len()-1].to_string());
-        } else if line.starts_with("java_version = \"") {
-            info.java_version = Some(line[16..line.len()-1].to_string());
-        }
-    }
-    
-    Ok(info)
+    })
 }
 
 fn get_generic_project_info(project_dir: &Path) -> Result<ProjectInfo> {
@@ -256,6 +219,25 @@ fn display_project_info(info: &ProjectInfo) {
     }
 }
 
+fn display_modules(project_dir: &Path) -> Result<()> {
+    let modules = modules::detect(project_dir)?;
+    if modules.is_empty() {
+        return Ok(());
+    }
+
+    println!("\n🧩 模块 ({}个):", modules.len());
+    println!("{}", "─".repeat(40));
+    for module in &modules {
+        if module.path == module.name {
+            println!("  {}", module.name);
+        } else {
+            println!("  {} ({})", module.name, module.path);
+        }
+    }
+    println!("使用 'jx build -p <模块>' 或 'jx test -p <模块>' 只处理指定模块");
+    Ok(())
+}
+
 fn display_dependencies(project_dir: &Path, project_type: &str) -> Result<()> {
     println!("\n📦 依赖信息:");
     println!("{}", "─".repeat(40));
@@ -286,10 +268,31 @@ fn display_dependencies(project_dir: &Path, project_type: &str) -> Result<()> {
             }
         }
     }
+
+    if project_type == "Gradle" && gradle::build_script(project_dir).is_some() {
+        display_gradle_plugins(project_dir)?;
+    }
     
     Ok(())
 }
 
+fn display_gradle_plugins(project_dir: &Path) -> Result<()> {
+    let plugins = GradleScript::open(project_dir)?.plugins();
+    if plugins.is_empty() {
+        return Ok(());
+    }
+
+    println!("\n🔌 Gradle插件 ({}个):", plugins.len());
+    for plugin in plugins {
+        let version = plugin.version.map(|v| format!(" {}", v)).unwrap_or_default();
+        match plugin.alias {
+            Some(alias) => println!("  {}{} (libs.plugins.{})", plugin.id, version, to_accessor(&alias)),
+            None => println!("  {}{}", plugin.id, version),
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 struct DependencyInfo {
     coordinate: String,
@@ -297,131 +300,61 @@ struct DependencyInfo {
 }
 
 fn read_maven_dependencies(project_dir: &Path) -> Result<Vec<DependencyInfo>> {
-    let pom_path = project_dir.join("pom.xml");
-    let pom_content = fs::read_to_string(&pom_path)?;
-    
-    let mut dependencies = Vec::new();
-    let lines: Vec<&str> = pom_content.lines().collect();
-    
-    let mut in_dependencies = false;
-    let mut current_dep: Option<HashMap<String, String>> = None;
-    
-    for line in lines {
-        let line = line.trim();
-        
-        if line == "<dependencies>" {
-            in_dependencies = true;
-        } else if line == "</dependencies>" {
-            in_dependencies = false;
-            break;
-        } else if in_dependencies {
-            if line == "<dependency>" {
-                current_dep = Some(HashMap::new());
-            } else if line == "</dependency>" {
-                if let Some(dep) = current_dep.take() {
-                    if let (Some(group_id), Some(artifact_id), Some(version)) = (
-                        dep.get("groupId"), dep.get("artifactId"), dep.get("version")
-                    ) {
-                        let scope = dep.get("scope").unwrap_or(&"compile".to_string()).clone();
-                        let coordinate = format!("{}:{}:{}", group_id, artifact_id, version);
-                        dependencies.push(DependencyInfo { coordinate, scope });
-                    }
-                }
-            } else if line.starts_with("<") && line.ends_with(">") && !line.starts_with("</") {
-                if let Some(dep) = &mut current_dep {
-                    let content = line.trim_start_matches('<').trim_end_matches('>');
-                    if let Some(colon_pos) = content.find('>') {
-                        let tag_name = &content[..colon_pos];
-                        let value = &content[colon_pos + 1..];
-                        
-                        if !tag_name.is_empty() && !value.is_empty() {
-                            dep.insert(tag_name.to_string(), value.to_string());
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    Ok(dependencies)
+    let pom = load_local_pom(project_dir)?;
+
+    Ok(pom
+        .effective_dependencies()
+        .into_iter()
+        .map(|dep| DependencyInfo {
+            coordinate: format!(
+                "{}:{}:{}",
+                dep.group_id,
+                dep.artifact_id,
+                dep.version.as_deref().unwrap_or("*")
+            ),
+            scope: dep.scope.unwrap_or_else(|| "compile".to_string()),
+        })
+        .collect())
 }
 
 fn read_gradle_dependencies(project_dir: &Path) -> Result<Vec<DependencyInfo>> {
-    let build_gradle_path = project_dir.join("build.gradle");
-    let build_content = fs::read_to_string(&build_gradle_path)?;
-    
-    let mut dependencies = Vec::new();
-    let lines: Vec<&str> = build_content.lines().collect();
-    
-    let mut in_dependencies = false;
-    
-    for line in lines {
-        let line = line.trim();
-        
-        if line == "dependencies {" {
-            in_dependencies = true;
-        } else if line == "}" && in_dependencies {
-            in_dependencies = false;
-            break;
-        } else if in_dependencies && line.contains("'") {
-            let parts: Vec<&str> = line.split('\'').collect();
-            if parts.len() >= 2 {
-                let dep_coord = parts[1];
-                let coord_parts: Vec<&str> = dep_coord.split(':').collect();
-                
-                if coord_parts.len() >= 2 {
-                    let group_id = coord_parts[0];
-                    let artifact_id = coord_parts[1];
-                    let version = coord_parts.get(2).unwrap_or(&"*");
-                    
-                    let scope = if line.contains("implementation") { "implementation" }
-                               else if line.contains("compileOnly") { "compileOnly" }
-                               else if line.contains("runtimeOnly") { "runtimeOnly" }
-                               else if line.contains("testImplementation") { "testImplementation" }
-                               else { "implementation" };
-                    
-                    let coordinate = format!("{}:{}:{}", group_id, artifact_id, version);
-                    dependencies.push(DependencyInfo { coordinate, scope: scope.to_string() });
-                }
-            }
-        }
+    if gradle::build_script(project_dir).is_none() {
+        return Ok(Vec::new());
     }
-    
-    Ok(dependencies)
+    let script = GradleScript::open(project_dir)?;
+
+    Ok(script
+        .dependencies()
+        .into_iter()
+        .map(|dep| {
+            let mut coordinate = format!(
+                "{}:{}:{}",
+                dep.group_id,
+                dep.artifact_id,
+                dep.version.as_deref().unwrap_or("*")
+            );
+            if let Some(alias) = &dep.alias {
+                coordinate.push_str(&format!(" (libs.{})", to_accessor(alias)));
+            }
+            DependencyInfo {
+                coordinate,
+                scope: dep.configuration,
+            }
+        })
+        .collect())
 }
 
 fn read_jx_dependencies(project_dir: &Path) -> Result<Vec<DependencyInfo>> {
-    let jx_path = project_dir.join("jx.toml");
-    let jx_content = fs::read_to_string(&jx_path)?;
-    
-    let mut dependencies = Vec::new();
-    let lines: Vec<&str> = jx_content.lines().collect();
-    
-    let mut in_dependencies = false;
-    
-    for line in lines {
-        let line = line.trim();
-        
-        if line == "[dependencies]" {
-            in_dependencies = true;
-        } else if line.starts_with('[') && line != "[dependencies]" {
-            in_dependencies = false;
-        } else if in_dependencies && line.contains('=') {
-            let parts: Vec<&str> = line.split('=').collect();
-            if parts.len() == 2 {
-                let dep_coord = parts[0].trim();
-                let version = parts[1].trim().trim_matches('"');
-                
-                let coordinate = format!("{}:{}", dep_coord, version);
-                dependencies.push(DependencyInfo { 
-                    coordinate, 
-                    scope: "compile".to_string() 
-                });
-            }
-        }
-    }
-    
-    Ok(dependencies)
+    let config = JxConfig::load_from_dir(project_dir)?;
+
+    Ok(config
+        .dependencies()
+        .iter()
+        .map(|dep| DependencyInfo {
+            coordinate: dep.coordinate(),
+            scope: dep.scope.as_str().to_string(),
+        })
+        .collect())
 }
 
 fn get_scope_icon(scope: &str) -> &str {
@@ -431,6 +364,7 @@ fn get_scope_icon(scope: &str) -> &str {
         "test" => "🧪 测试",
         "provided" => "⚡ 提供",
         "system" => "💻 系统",
+        "annotation-processor" | "annotationProcessor" => "🔧 注解处理器",
         "implementation" => "📦 实现",
         "compileOnly" => "📝 仅编译",
         "runtimeOnly" => "🔄 仅运行时",
@@ -468,6 +402,21 @@ fn display_build_info(project_dir: &Path, project_type: &str) -> Result<()> {
                 println!("Gradle缓存目录: {} ({} bytes)", gradle_dir.display(), gradle_size);
             }
         }
+        "jx" => {
+            let config = JxConfig::load_from_dir(project_dir)?;
+            for output_dir in classpath::output_dirs(project_dir, &config) {
+                if output_dir.exists() {
+                    println!("编译输出目录: {}", output_dir.display());
+                } else {
+                    println!("编译输出目录: 不存在");
+                }
+            }
+            // IDE需要把它加入源码路径才能解析生成的类
+            let generated_dir = classpath::generated_sources_dir(project_dir, &config);
+            if generated_dir.exists() {
+                println!("注解处理器生成的源码: {}", generated_dir.display());
+            }
+        }
         _ => {}
     }
     
@@ -502,6 +451,9 @@ fn display_file_stats(project_dir: &Path) -> Result<()> {
                     Some("java") => java_files += 1,
                     Some("xml") => xml_files += 1,
                     Some("gradle") => gradle_files += 1,
+                    Some("kts") if path.to_string_lossy().ends_with(".gradle.kts") => {
+                        gradle_files += 1
+                    }
                     Some("toml") => toml_files += 1,
                     _ => {}
                 }
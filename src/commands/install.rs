@@ -1,8 +1,21 @@
+use crate::dependency::{Dependency, DependencyScope};
+use crate::download::Downloader;
+use crate::gradle;
+use crate::install::{is_production, lib_dependencies, resolve_and_lock, Installer};
+use crate::lock::{LockFile, LockedDependency};
+use crate::workspace;
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::path::Path;
 use std::process::Command;
 
-pub fn execute(_file: Option<String>, _production: bool, force: bool) -> Result<()> {
+pub async fn execute(
+    _file: Option<String>,
+    _production: bool,
+    force: bool,
+    offline: bool,
+    locked: bool,
+) -> Result<()> {
     let current_dir = std::env::current_dir()?;
     
     // 查找项目配置文件
@@ -10,20 +23,27 @@ pub fn execute(_file: Option<String>, _production: bool, force: bool) -> Result<
         "jx.toml"
     } else if current_dir.join("pom.xml").exists() {
         "pom.xml"
-    } else if current_dir.join("build.gradle").exists() {
-        "build.gradle"
+    } else if let Some(script) = gradle::build_script(&current_dir) {
+        script
     } else {
         return Err(anyhow::anyhow!("找不到项目配置文件，请先运行 'jx init'"));
     };
 
     println!("📦 开始安装依赖...");
     println!("配置文件: {}", config_file);
+    if offline {
+        println!("离线模式: 仅使用本地缓存");
+    }
 
     // 根据配置文件类型选择安装方式
-    let result = if config_file == "pom.xml" {
-        install_from_maven(&current_dir, _production, force)
-    } else if config_file == "build.gradle" {
-        install_from_gradle(&current_dir, _production, force)
+    let result = if config_file == "jx.toml" {
+        install_from_jx(&current_dir, _production, force, offline, locked).await
+    } else if locked {
+        Err(anyhow::anyhow!("--locked/--frozen 仅适用于jx.toml项目"))
+    } else if config_file == "pom.xml" {
+        install_from_maven(&current_dir, _production, force, offline)
+    } else if config_file.starts_with("build.gradle") {
+        install_from_gradle(&current_dir, _production, force, offline)
     } else {
         Err(anyhow::anyhow!("不支持的配置文件类型: {}", config_file))
     };
@@ -40,7 +60,86 @@ pub fn execute(_file: Option<String>, _production: bool, force: bool) -> Result<
     }
 }
 
-fn install_from_maven(project_dir: &Path, production: bool, force: bool) -> Result<()> {
+async fn install_from_jx(
+    project_dir: &Path,
+    production: bool,
+    force: bool,
+    offline: bool,
+    locked: bool,
+) -> Result<()> {
+    println!("使用jx安装依赖...");
+
+    let (lock_path, manifest) = workspace::lock_manifest(project_dir)?;
+
+    // 锁文件与jx.toml一致时直接使用，不重新解析
+    let lock_file = if lock_path.exists() && (!force || locked) {
+        let lock_file = LockFile::load(&lock_path)?;
+        let drift = lock_file.drift(&manifest);
+
+        if drift.is_empty() {
+            println!("使用锁文件: jx.lock");
+            lock_file
+        } else if locked {
+            return Err(anyhow::anyhow!(
+                "jx.toml 与 jx.lock 不一致:\n  {}\n请去掉 --locked/--frozen 运行 'jx install' 更新锁文件",
+                drift.join("\n  ")
+            ));
+        } else {
+            println!("jx.toml 已变更，重新解析依赖:");
+            for change in &drift {
+                println!("  {}", change);
+            }
+            resolve_and_lock(&lock_path, &manifest, HashMap::new(), offline).await?
+        }
+    } else if locked {
+        return Err(anyhow::anyhow!(
+            "找不到jx.lock，--locked/--frozen 需要已有的锁文件，请先运行 'jx install'"
+        ));
+    } else {
+        resolve_and_lock(&lock_path, &manifest, HashMap::new(), offline).await?
+    };
+
+    let locked_dependencies: Vec<LockedDependency> = lib_dependencies(project_dir, &lock_file)?
+        .into_iter()
+        .filter(|dep| !production || is_production(dep))
+        .collect();
+
+    if locked_dependencies.is_empty() {
+        println!("没有需要安装的依赖");
+        return Ok(());
+    }
+
+    // 注解处理器只下载到缓存，不放进lib/，以免出现在运行classpath上
+    let (processors, libraries): (Vec<&LockedDependency>, Vec<&LockedDependency>) = locked_dependencies
+        .iter()
+        .partition(|dep| dep.scope == DependencyScope::AnnotationProcessor.as_str());
+
+    // 缓存中的jar必须与锁定的校验和一致，全部通过后才复制到lib/，被篡改的jar不会进入classpath
+    if !processors.is_empty() {
+        println!("下载 {} 个注解处理器依赖到缓存", processors.len());
+        let dependencies: Vec<Dependency> = processors.iter().map(|dep| dep.to_dependency()).collect();
+        let cache_paths = Downloader::new()
+            .offline(offline)
+            .download_all(&dependencies)
+            .await?;
+        for (locked_dep, cache_path) in processors.iter().zip(&cache_paths) {
+            lock_file.check_checksum(locked_dep, Path::new(cache_path))?;
+        }
+    }
+    if !libraries.is_empty() {
+        let dependencies: Vec<Dependency> = libraries.iter().map(|dep| dep.to_dependency()).collect();
+        Installer::new()
+            .offline(offline)
+            .install_dependencies(&dependencies, |index, cache_path| {
+                lock_file.check_checksum(libraries[index], cache_path)
+            })
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn install_from_maven(project_dir: &Path, production: bool, force: bool, offline: bool) -> Result<()> {
     println!("使用Maven安装依赖...");
     
     // 检查Maven是否安装
@@ -59,6 +158,10 @@ fn install_from_maven(project_dir: &Path, production: bool, force: bool) -> Resu
         mvn_args.push("-U"); // 强制更新
     }
 
+    if offline {
+        mvn_args.push("-o");
+    }
+
     println!("正在解析Maven依赖...");
 
     // 执行Maven命令
@@ -76,8 +179,13 @@ fn install_from_maven(project_dir: &Path, production: bool, force: bool) -> Resu
     println!("Maven依赖解析完成");
     println!("正在下载依赖...");
     
+    let mut copy_args = vec!["dependency:copy-dependencies"];
+    if offline {
+        copy_args.push("-o");
+    }
+
     let download_output = Command::new("mvn")
-        .arg("dependency:copy-dependencies")
+        .args(&copy_args)
         .current_dir(project_dir)
         .output()
         .context("下载Maven依赖失败")?;
@@ -92,7 +200,7 @@ fn install_from_maven(project_dir: &Path, production: bool, force: bool) -> Resu
     Ok(())
 }
 
-fn install_from_gradle(project_dir: &Path, _production: bool, force: bool) -> Result<()> {
+fn install_from_gradle(project_dir: &Path, _production: bool, force: bool, offline: bool) -> Result<()> {
     println!("使用Gradle安装依赖...");
     
     // 检查Gradle是否安装
@@ -107,6 +215,10 @@ fn install_from_gradle(project_dir: &Path, _production: bool, force: bool) -> Re
         gradle_args.push("--refresh-dependencies");
     }
 
+    if offline {
+        gradle_args.push("--offline");
+    }
+
     println!("正在解析Gradle依赖...");
 
     // 执行Gradle命令
@@ -124,8 +236,13 @@ fn install_from_gradle(project_dir: &Path, _production: bool, force: bool) -> Re
     println!("Gradle依赖解析完成");
     println!("正在下载依赖...");
     
+    let mut build_args = vec!["build"];
+    if offline {
+        build_args.push("--offline");
+    }
+
     let download_output = Command::new("gradle")
-        .arg("build")
+        .args(&build_args)
         .current_dir(project_dir)
         .output()
         .context("下载Gradle依赖失败")?;
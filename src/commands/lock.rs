@@ -0,0 +1,423 @@
+use crate::config::JxConfig;
+use crate::dependency::Dependency;
+use crate::install::resolve_and_lock;
+use crate::lock::{parse_gradle_lockfile, GradleLockEntry, LockChange, LockChangeKind, LockFile};
+use crate::registry::MavenRegistry;
+use crate::workspace;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+pub async fn execute(check: bool, offline: bool, upgrade_packages: Vec<String>) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    if !current_dir.join("jx.toml").exists() {
+        return Err(anyhow::anyhow!("jx lock 仅适用于jx.toml项目"));
+    }
+
+    // 工作区成员共用根目录的jx.lock，锁定所有成员的依赖
+    let (lock_path, manifest) = workspace::lock_manifest(&current_dir)?;
+
+    if !lock_path.exists() {
+        if check {
+            return Err(anyhow::anyhow!("找不到jx.lock，请运行 'jx lock' 生成锁文件"));
+        }
+        resolve_and_lock(&lock_path, &manifest, HashMap::new(), offline).await?;
+        return Ok(());
+    }
+
+    let lock_file = LockFile::load(&lock_path)?;
+    let drift = lock_file.drift(&manifest);
+
+    if !upgrade_packages.is_empty() {
+        if !drift.is_empty() {
+            return Err(anyhow::anyhow!(
+                "jx.toml 与 jx.lock 不一致:\n  {}\n请先运行 'jx lock' 更新锁文件",
+                drift.join("\n  ")
+            ));
+        }
+        return upgrade_packages_in_lock(&lock_path, lock_file, &manifest, &upgrade_packages, offline)
+            .await;
+    }
+
+    if check {
+        // 只报告，不写入
+        let mut reasons = drift;
+        if reasons.is_empty() && !lock_file.is_canonical(&lock_path)? {
+            reasons.push("锁文件格式需要更新 (旧版本格式或条目未排序)".to_string());
+        }
+
+        if reasons.is_empty() {
+            println!("✅ jx.lock 已是最新");
+            return Ok(());
+        }
+
+        return Err(anyhow::anyhow!(
+            "jx.lock 需要更新:\n  {}\n请运行 'jx lock' 更新锁文件",
+            reasons.join("\n  ")
+        ));
+    }
+
+    if drift.is_empty() {
+        if lock_file.is_canonical(&lock_path)? {
+            println!("✅ jx.lock 已是最新");
+        } else {
+            lock_file.save(&lock_path)?;
+            println!("🔒 已将 jx.lock 更新为版本 {} 格式", crate::lock::LOCK_VERSION);
+        }
+        return Ok(());
+    }
+
+    println!("jx.toml 已变更，重新解析依赖:");
+    for change in &drift {
+        println!("  {}", change);
+    }
+    resolve_and_lock(&lock_path, &manifest, HashMap::new(), offline).await?;
+
+    Ok(())
+}
+
+/// 只重新解析指定的构件及仅由它们引入的依赖，其余依赖保持锁定的版本
+async fn upgrade_packages_in_lock(
+    lock_path: &Path,
+    lock_file: LockFile,
+    manifest: &[Dependency],
+    specs: &[String],
+    offline: bool,
+) -> Result<()> {
+    let mut targets: Vec<(String, Option<String>)> = Vec::new();
+    for spec in specs {
+        let parts: Vec<&str> = spec.split(':').collect();
+        let (key, version) = match parts.as_slice() {
+            [g, a] => (format!("{}:{}", g, a), None),
+            [g, a, v] => (format!("{}:{}", g, a), Some(v.to_string())),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "无效的构件: {} (格式: groupId:artifactId[:version])",
+                    spec
+                ))
+            }
+        };
+
+        let locked = lock_file
+            .dependencies
+            .values()
+            .find(|dep| dep.ga_key() == key)
+            .ok_or_else(|| anyhow::anyhow!("jx.lock 中没有 {}", key))?;
+        if locked.direct {
+            return Err(anyhow::anyhow!(
+                "{} 是直接依赖，请修改jx.toml中的版本或使用 'jx update {}'",
+                key,
+                key
+            ));
+        }
+        targets.push((key, version));
+    }
+
+    let keys: HashSet<String> = targets.iter().map(|(key, _)| key.clone()).collect();
+    let released = lock_file.exclusive_dependencies(&keys);
+
+    let mut pinned: HashMap<String, String> = lock_file
+        .dependencies
+        .values()
+        .filter(|dep| !released.contains(&dep.ga_key()))
+        .map(|dep| (dep.ga_key(), dep.version.clone()))
+        .collect();
+
+    let mut registry = MavenRegistry::new();
+    for (key, version) in targets {
+        let version = match version {
+            Some(version) => version,
+            None if offline => {
+                return Err(anyhow::anyhow!(
+                    "离线模式下无法查询最新版本，请指定版本: {}:<version>",
+                    key
+                ))
+            }
+            None => {
+                let (group_id, artifact_id) = key.split_once(':').unwrap();
+                registry.latest_release(group_id, artifact_id).await?
+            }
+        };
+        println!("⬆️  升级 {} 到 {}", key, version);
+        pinned.insert(key, version);
+    }
+
+    let new_lock = resolve_and_lock(lock_path, manifest, pinned, offline).await?;
+    let changes = LockFile::diff(&lock_file, &new_lock);
+
+    println!("\n📊 版本变化:");
+    if changes.is_empty() {
+        println!("依赖图没有变化");
+    } else {
+        print_changes(&changes);
+    }
+
+    Ok(())
+}
+
+/// 从jx.lock导出gradle.lockfile、<dependencyManagement>块或BOM POM
+pub fn export(format: String, output: Option<String>, group_id: Option<String>) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let lock_path = workspace::lock_path(&current_dir)?;
+    if !lock_path.exists() {
+        return Err(anyhow::anyhow!("找不到jx.lock，请先运行 'jx lock'"));
+    }
+    let lock_file = LockFile::load(&lock_path)?;
+
+    let content = match format.as_str() {
+        "gradle" => lock_file.to_gradle_lockfile(),
+        "maven" => lock_file.to_maven_dependency_management(""),
+        "bom" => {
+            let config_path = current_dir.join("jx.toml");
+            let project = if config_path.exists() {
+                Some(JxConfig::load(&config_path)?.project)
+            } else {
+                None
+            };
+            let group_id = group_id
+                .or_else(|| project.as_ref().and_then(|p| p.group_id.clone()))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("生成BOM需要groupId，请使用 --group-id 指定或在jx.toml的[project]中设置group_id")
+                })?;
+            let (name, version) = match project {
+                Some(project) => (project.name, project.version),
+                None => ("jx-project".to_string(), "1.0.0".to_string()),
+            };
+            lock_file.to_bom_pom(&group_id, &format!("{}-bom", name), &version)
+        }
+        _ => return Err(anyhow::anyhow!("不支持的导出格式: {} (可选: gradle, maven, bom)", format)),
+    };
+
+    match output {
+        Some(output) => {
+            fs::write(&output, content).with_context(|| format!("写入 {} 失败", output))?;
+            println!(
+                "📤 已导出 {} 个依赖到 {}",
+                lock_file.dependencies.len(),
+                output
+            );
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
+
+/// 用gradle.lockfile中的版本作为固定版本重新解析，生成jx.lock
+pub async fn import(file: String, offline: bool) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    if !current_dir.join("jx.toml").exists() {
+        return Err(anyhow::anyhow!("jx lock import 需要jx.toml中声明直接依赖"));
+    }
+
+    let content = fs::read_to_string(&file).with_context(|| format!("读取 {} 失败", file))?;
+    let entries: Vec<GradleLockEntry> = parse_gradle_lockfile(&content)?
+        .into_iter()
+        .filter(|entry| entry.on_classpath())
+        .collect();
+    println!("📥 从 {} 读取 {} 个构件", file, entries.len());
+
+    let (lock_path, manifest) = workspace::lock_manifest(&current_dir)?;
+    let direct: HashMap<String, &Dependency> = manifest
+        .iter()
+        .map(|dep| (format!("{}:{}", dep.group_id, dep.artifact_id), dep))
+        .collect();
+
+    // 直接依赖以jx.toml为准，避免锁文件与jx.toml不一致
+    let mut pinned = HashMap::new();
+    for entry in &entries {
+        match direct.get(&entry.key()) {
+            Some(dep) if dep.version != entry.version => println!(
+                "⚠️ 直接依赖 {} 在jx.toml中为 {}，gradle.lockfile中为 {}，使用jx.toml的版本",
+                entry.key(),
+                dep.version,
+                entry.version
+            ),
+            Some(_) => {}
+            None => {
+                pinned.insert(entry.key(), entry.version.clone());
+            }
+        }
+    }
+
+    let lock_file = resolve_and_lock(&lock_path, &manifest, pinned, offline).await?;
+
+    let locked: HashSet<String> = lock_file.dependencies.values().map(|dep| dep.key()).collect();
+    let unused: Vec<String> = entries
+        .iter()
+        .filter(|entry| !locked.contains(&entry.key()))
+        .map(|entry| format!("{}:{}", entry.key(), entry.version))
+        .collect();
+    if !unused.is_empty() {
+        println!(
+            "⚠️ gradle.lockfile中有 {} 个构件没有被jx.toml中的依赖引用，已忽略:\n  {}",
+            unused.len(),
+            unused.join("\n  ")
+        );
+    }
+
+    Ok(())
+}
+
+/// 比较两个锁文件，OLD/NEW可以是文件路径或git版本，默认比较HEAD与工作区的jx.lock
+///
+/// 工作区成员中使用根目录的jx.lock，git版本中的jx.lock也按它在仓库中的路径读取
+pub fn diff(old: Option<String>, new: Option<String>, json: bool) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let lock_path = workspace::lock_path(&current_dir)?;
+    let default_new = match lock_path.strip_prefix(&current_dir) {
+        Ok(relative) => relative.display().to_string(),
+        Err(_) => lock_path.display().to_string(),
+    };
+
+    let old_spec = old.unwrap_or_else(|| "HEAD".to_string());
+    let new_spec = new.unwrap_or(default_new);
+
+    let old_lock = load_lock_source(&old_spec, &lock_path)?;
+    let new_lock = load_lock_source(&new_spec, &lock_path)?;
+    let changes = LockFile::diff(&old_lock, &new_lock);
+
+    if json {
+        let output = serde_json::json!({
+            "old": old_spec,
+            "new": new_spec,
+            "changes": changes,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("📊 jx.lock 差异: {} -> {}", old_spec, new_spec);
+
+    if changes.is_empty() {
+        println!("依赖图没有变化");
+        return Ok(());
+    }
+
+    print_changes(&changes);
+
+    Ok(())
+}
+
+fn print_changes(changes: &[LockChange]) {
+    for change in changes {
+        let line = match change.kind {
+            LockChangeKind::Added => format!(
+                "  + {} {} ({})",
+                change.artifact,
+                change.new_version.as_deref().unwrap_or(""),
+                change.new_scope.as_deref().unwrap_or("")
+            ),
+            LockChangeKind::Removed => format!(
+                "  - {} {} ({})",
+                change.artifact,
+                change.old_version.as_deref().unwrap_or(""),
+                change.old_scope.as_deref().unwrap_or("")
+            ),
+            LockChangeKind::Upgraded | LockChangeKind::Downgraded => format!(
+                "  {} {} {} -> {}",
+                if change.kind == LockChangeKind::Upgraded { "↑" } else { "↓" },
+                change.artifact,
+                change.old_version.as_deref().unwrap_or(""),
+                change.new_version.as_deref().unwrap_or("")
+            ),
+            LockChangeKind::ScopeChanged => format!(
+                "  ~ {} {} scope {} -> {}",
+                change.artifact,
+                change.new_version.as_deref().unwrap_or(""),
+                change.old_scope.as_deref().unwrap_or(""),
+                change.new_scope.as_deref().unwrap_or("")
+            ),
+        };
+
+        if change.direct {
+            println!("{}  [直接依赖]", line);
+        } else if !change.introduced_by.is_empty() {
+            println!("{}  ← {}", line, change.introduced_by.join(", "));
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    let count = |kind: LockChangeKind| changes.iter().filter(|c| c.kind == kind).count();
+    println!(
+        "\n汇总: 新增 {}，移除 {}，升级 {}，降级 {}，scope变更 {}",
+        count(LockChangeKind::Added),
+        count(LockChangeKind::Removed),
+        count(LockChangeKind::Upgraded),
+        count(LockChangeKind::Downgraded),
+        count(LockChangeKind::ScopeChanged)
+    );
+}
+
+/// 已存在的文件按路径读取，否则作为git版本读取该版本中`lock_path`对应的文件
+fn load_lock_source(spec: &str, lock_path: &Path) -> Result<LockFile> {
+    let path = Path::new(spec);
+    if path.is_file() {
+        return LockFile::load(path);
+    }
+    if spec.ends_with(".lock") {
+        return Err(anyhow::anyhow!("找不到锁文件: {}", spec));
+    }
+
+    let lock_dir = lock_path.parent().unwrap_or(Path::new("."));
+    let pathspec = repository_path(lock_path)?;
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(lock_dir)
+        .args(["show", &format!("{}:{}", spec, pathspec)])
+        .output()
+        .context("执行git命令失败")?;
+
+    if output.status.success() {
+        let content = String::from_utf8_lossy(&output.stdout);
+        return LockFile::parse(&content)
+            .map_err(|e| anyhow::anyhow!("解析 {}:{} 失败: {}", spec, pathspec, e));
+    }
+
+    // 版本存在但当时还没有jx.lock，视为空锁文件
+    let rev_exists = Command::new("git")
+        .arg("-C")
+        .arg(lock_dir)
+        .args(["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", spec)])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    if rev_exists {
+        return Ok(LockFile::new());
+    }
+
+    Err(anyhow::anyhow!(
+        "{} 既不是锁文件也不是有效的git版本: {}",
+        spec,
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+/// 锁文件相对于git仓库根目录的路径，用于`git show <rev>:<path>`
+fn repository_path(lock_path: &Path) -> Result<String> {
+    let lock_dir = lock_path.parent().unwrap_or(Path::new("."));
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(lock_dir)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .context("执行git命令失败")?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "{} 不在git仓库中: {}",
+            lock_dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let root = fs::canonicalize(String::from_utf8_lossy(&output.stdout).trim())?;
+    let lock_dir = fs::canonicalize(lock_dir)?;
+    let relative = lock_dir
+        .strip_prefix(&root)
+        .with_context(|| format!("{} 不在git仓库 {} 中", lock_dir.display(), root.display()))?
+        .join(lock_path.file_name().unwrap_or_default());
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
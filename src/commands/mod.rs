@@ -1,19 +1,26 @@
 pub mod init;
 pub mod install;
+pub mod lock;
 pub mod add;
 pub mod remove;
 pub mod update;
 pub mod build;
+pub mod package;
 pub mod run;
+pub mod run_script;
 pub mod test;
 pub mod clean;
+pub mod config;
 pub mod info;
 pub mod tree;
 pub mod search;
 pub mod venv;
+pub mod verify;
+pub mod which_class;
 
 pub use init::*;
 pub use install::*;
+pub use lock::*;
 pub use add::*;
 pub use remove::*;
 pub use update::*;
@@ -21,7 +28,10 @@ pub use build::*;
 pub use run::*;
 pub use test::*;
 pub use clean::*;
+pub use config::*;
 pub use info::*;
 pub use tree::*;
 pub use search::*;
 pub use venv::*;
+pub use verify::*;
+pub use which_class::*;
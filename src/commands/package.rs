@@ -0,0 +1,281 @@
+use crate::classpath;
+use crate::config::{JxConfig, ProjectType};
+use crate::dependency::{Dependency, DependencyScope};
+use crate::download::Downloader;
+use crate::install::ensure_offline_ready;
+use crate::jar::{Jar, Manifest};
+use crate::javac;
+use crate::profile::Profile;
+use crate::shade::{Relocator, UberJar};
+use crate::workspace::{self, Workspace};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 编译jx项目并打包为`<target_dir>/<name>-<version>.jar`，`--uber`时打包为包含所有依赖的uber jar
+///
+/// 工作区成员的路径依赖会先打包，它们的jar和运行时依赖一起复制到`<target_dir>/lib/`
+pub fn execute(profile: String, offline: bool, package: Option<String>, uber: bool) -> Result<()> {
+    let project_dir = workspace::project_dir(package.as_deref())?;
+    let config_path = project_dir.join("jx.toml");
+    if !config_path.exists() {
+        return Err(anyhow::anyhow!("找不到项目配置文件，请先运行 'jx init'"));
+    }
+    let config = JxConfig::load(&config_path)?;
+    if config.project.project_type != ProjectType::Jx {
+        return Err(anyhow::anyhow!(
+            "jx package 只用于 type = \"jx\" 的项目，{}项目请运行 'jx build --profile release'",
+            config.project.project_type.as_str()
+        ));
+    }
+
+    println!("📦 打包项目...");
+    println!("构建配置: {}", profile);
+    if offline {
+        println!("离线模式: 仅使用本地缓存");
+    }
+
+    let dirs: Vec<PathBuf> = match Workspace::discover(&project_dir)? {
+        Some(workspace) => {
+            let member = workspace
+                .member_at(&project_dir)
+                .ok_or_else(|| anyhow::anyhow!("{} 不是工作区成员", project_dir.display()))?;
+            workspace
+                .with_dependencies(&[member])?
+                .into_iter()
+                .map(|member| member.dir.clone())
+                .collect()
+        }
+        None => vec![project_dir],
+    };
+
+    for (index, dir) in dirs.iter().enumerate() {
+        let config = JxConfig::load_from_dir(dir)?;
+        if dirs.len() > 1 {
+            println!("\n📦 打包 {}", config.project.name);
+        }
+        if offline {
+            ensure_offline_ready(dir)?;
+        }
+        let mut profile = Profile::resolve(Some(&config), &profile)?;
+        profile.package = true;
+        javac::build(dir, &config, &profile)?;
+        // 被依赖的成员打成普通jar，最后一个是选中的项目
+        if uber && index == dirs.len() - 1 {
+            package_uber_jar(dir, &config, &profile)?;
+        } else {
+            package_jx_project(dir, &config, &profile)?;
+        }
+    }
+
+    println!("✅ 打包完成!");
+    Ok(())
+}
+
+/// 把已编译的输出目录打包为jar，返回jar的路径
+pub fn package_jx_project(project_dir: &Path, config: &JxConfig, profile: &Profile) -> Result<PathBuf> {
+    let target_dir = project_dir.join(config.build.target_dir.as_deref().unwrap_or("target"));
+    let classes_dir = target_dir.join("classes");
+
+    let lib_jars = copy_runtime_jars(project_dir, config, profile, &target_dir.join("lib"))?;
+
+    let mut jar = Jar::new();
+    jar.add_dir(&classes_dir)?;
+
+    let mut manifest = manifest(config, &jar, None);
+    if !lib_jars.is_empty() {
+        let entries: Vec<String> = lib_jars.iter().map(|name| format!("lib/{}", name)).collect();
+        manifest.set("Class-Path", &entries.join(" "));
+    }
+
+    let jar_path = jar_path(project_dir, config);
+    jar.write(&jar_path, &manifest)?;
+    println!(
+        "已生成 {}{}",
+        jar_path.strip_prefix(project_dir).unwrap_or(&jar_path).display(),
+        if lib_jars.is_empty() {
+            String::new()
+        } else {
+            format!("，{} 个运行时依赖在 lib/ 中", lib_jars.len())
+        }
+    );
+    Ok(jar_path)
+}
+
+/// 把项目输出和所有运行时依赖合并为`<target_dir>/<name>-<version>-uber.jar`，按`[shade]`重定位包
+pub fn package_uber_jar(project_dir: &Path, config: &JxConfig, profile: &Profile) -> Result<PathBuf> {
+    let target_dir = project_dir.join(config.build.target_dir.as_deref().unwrap_or("target"));
+    let relocator = Relocator::new(&config.shade.relocate);
+
+    let mut uber = UberJar::new(relocator);
+    uber.add_dir(&target_dir.join("classes"), &config.project.name)?;
+    let jars = runtime_jars(project_dir, config, profile)?;
+    for jar in &jars {
+        uber.add_jar(jar)?;
+    }
+    let (jar, report) = uber.finish();
+
+    let relocator = Relocator::new(&config.shade.relocate);
+    let manifest = manifest(config, &jar, Some(&relocator));
+    let jar_path = target_dir.join(format!(
+        "{}-{}-uber.jar",
+        config.project.name, config.project.version
+    ));
+    jar.write(&jar_path, &manifest)?;
+
+    println!(
+        "已生成 {}，合并了 {} 个依赖",
+        jar_path.strip_prefix(project_dir).unwrap_or(&jar_path).display(),
+        jars.len()
+    );
+    if report.relocated_classes > 0 {
+        println!("重定位了 {} 个类", report.relocated_classes);
+    }
+    if report.dropped_signatures > 0 {
+        println!("丢弃了 {} 个签名文件", report.dropped_signatures);
+    }
+    if !report.duplicate_classes.is_empty() {
+        let total: usize = report.duplicate_classes.values().map(Vec::len).sum();
+        println!("⚠️  发现 {} 个重复的类，保留先出现的版本:", total);
+        for ((first, duplicate), classes) in &report.duplicate_classes {
+            let examples: Vec<&str> = classes.iter().take(3).map(String::as_str).collect();
+            println!(
+                "  {} 与 {}: {} 个 ({}{})",
+                first,
+                duplicate,
+                classes.len(),
+                examples.join(", "),
+                if classes.len() > examples.len() { ", ..." } else { "" }
+            );
+        }
+    }
+    if report.duplicate_resources > 0 {
+        println!("重复的资源文件 {} 个，保留先出现的版本", report.duplicate_resources);
+    }
+    Ok(jar_path)
+}
+
+/// 两种jar共用的清单: Main-Class、Implementation-*，不是命名模块时加上Automatic-Module-Name
+fn manifest(config: &JxConfig, jar: &Jar, relocator: Option<&Relocator>) -> Manifest {
+    let mut manifest = Manifest::new();
+    manifest.set("Implementation-Title", &config.project.name);
+    manifest.set("Implementation-Version", &config.project.version);
+    if let Some(main_class) = &config.build.main_class {
+        let main_class = relocator
+            .and_then(|relocator| relocator.class_name(main_class))
+            .unwrap_or_else(|| main_class.clone());
+        let class_file = format!("{}.class", main_class.replace('.', "/"));
+        if !jar.contains(&class_file) {
+            println!("⚠️  找不到主类 {}，jar中仍会写入Main-Class", main_class);
+        }
+        manifest.set("Main-Class", &main_class);
+    }
+    // 有module-info.class时已经是命名模块
+    if !jar.contains("module-info.class") {
+        manifest.set("Automatic-Module-Name", &automatic_module_name(config));
+    }
+    manifest
+}
+
+/// `<target_dir>/<name>-<version>.jar`
+pub fn jar_path(project_dir: &Path, config: &JxConfig) -> PathBuf {
+    project_dir
+        .join(config.build.target_dir.as_deref().unwrap_or("target"))
+        .join(format!("{}-{}.jar", config.project.name, config.project.version))
+}
+
+/// 运行时依赖: 当前profile中compile和runtime scope的外部依赖，以及路径依赖成员的jar
+pub fn runtime_jars(project_dir: &Path, config: &JxConfig, profile: &Profile) -> Result<Vec<PathBuf>> {
+    let dependencies: Vec<Dependency> = classpath::profile_dependencies(project_dir, config, profile)?
+        .into_iter()
+        .filter(|dep| matches!(dep.scope, DependencyScope::Compile | DependencyScope::Runtime))
+        .collect();
+
+    let downloader = Downloader::new();
+    let missing = downloader.missing_from_cache(&dependencies);
+    if !missing.is_empty() {
+        return Err(anyhow::anyhow!(
+            "本地缓存缺少 {} 个运行时依赖:\n  {}\n请先运行 'jx install'",
+            missing.len(),
+            missing.join("\n  ")
+        ));
+    }
+
+    let mut jars = Vec::new();
+    collect_member_jars(project_dir, config, &mut jars)?;
+    jars.extend(dependencies.iter().map(|dep| downloader.cached_path(dep)));
+    Ok(jars)
+}
+
+/// 沿路径依赖收集成员的jar，成员需要先打包
+fn collect_member_jars(project_dir: &Path, config: &JxConfig, jars: &mut Vec<PathBuf>) -> Result<()> {
+    for dependency in config.path_dependencies() {
+        let member_dir = project_dir.join(&dependency.path);
+        let member_config = JxConfig::load_from_dir(&member_dir)?;
+        let jar = jar_path(&member_dir, &member_config);
+        if !jar.exists() {
+            return Err(anyhow::anyhow!(
+                "路径依赖 {}:{} 还没有打包: 找不到 {}",
+                dependency.group_id,
+                dependency.artifact_id,
+                jar.display()
+            ));
+        }
+        if !jars.contains(&jar) {
+            jars.push(jar);
+            collect_member_jars(&member_dir, &member_config, jars)?;
+        }
+    }
+    Ok(())
+}
+
+/// 重新生成lib/目录，返回其中的jar文件名 (按classpath顺序)
+fn copy_runtime_jars(
+    project_dir: &Path,
+    config: &JxConfig,
+    profile: &Profile,
+    lib_dir: &Path,
+) -> Result<Vec<String>> {
+    let jars = runtime_jars(project_dir, config, profile)?;
+    if lib_dir.exists() {
+        fs::remove_dir_all(lib_dir).with_context(|| format!("无法清理 {}", lib_dir.display()))?;
+    }
+    if jars.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    fs::create_dir_all(lib_dir)?;
+    let mut names = Vec::new();
+    for jar in jars {
+        let name = jar
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| anyhow::anyhow!("无效的jar路径 {}", jar.display()))?;
+        if names.contains(&name) {
+            continue;
+        }
+        fs::copy(&jar, lib_dir.join(&name))
+            .with_context(|| format!("无法复制 {}", jar.display()))?;
+        names.push(name);
+    }
+    Ok(names)
+}
+
+/// 由group_id和项目名生成合法的模块名，例如 com.example + my-app -> com.example.my.app
+fn automatic_module_name(config: &JxConfig) -> String {
+    let raw = match &config.project.group_id {
+        Some(group_id) => format!("{}.{}", group_id, config.project.name),
+        None => config.project.name.clone(),
+    };
+    raw.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            if segment.starts_with(|c: char| c.is_ascii_digit()) {
+                format!("_{}", segment)
+            } else {
+                segment.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
@@ -1,20 +1,26 @@
+use crate::gradle::{self, GradleScript};
+use crate::manifest::ManifestEditor;
+use crate::pom_editor::PomEditor;
+use crate::workspace;
 use anyhow::Result;
-use std::fs;
 use std::path::Path;
 
-pub fn execute(dependency: String) -> Result<()> {
-    let current_dir = std::env::current_dir()?;
+pub fn execute(dependency: String, managed: bool, package: Option<String>) -> Result<()> {
+    let current_dir = workspace::project_dir(package.as_deref())?;
     
     // 查找项目配置文件
     let config_file = if current_dir.join("jx.toml").exists() {
         "jx.toml"
     } else if current_dir.join("pom.xml").exists() {
         "pom.xml"
-    } else if current_dir.join("build.gradle").exists() {
-        "build.gradle"
+    } else if let Some(script) = gradle::build_script(&current_dir) {
+        script
     } else {
         return Err(anyhow::anyhow!("找不到项目配置文件，请先运行 'jx init'"));
     };
+    if managed && config_file != "pom.xml" {
+        return Err(anyhow::anyhow!("--managed 仅适用于pom.xml"));
+    }
 
     println!("🗑️ 移除依赖...");
     println!("依赖: {}", dependency);
@@ -25,8 +31,8 @@ pub fn execute(dependency: String) -> Result<()> {
     // 根据配置文件类型移除依赖
     let result = match config_file {
         "jx.toml" => remove_from_jx_config(&current_dir, &dep_info),
-        "pom.xml" => remove_from_maven(&current_dir, &dep_info),
-        "build.gradle" => remove_from_gradle(&current_dir, &dep_info),
+        "pom.xml" => remove_from_maven(&current_dir, &dep_info, managed),
+        "build.gradle" | "build.gradle.kts" => remove_from_gradle(&current_dir, &dep_info),
         _ => Err(anyhow::anyhow!("不支持的配置文件类型")),
     };
 
@@ -68,83 +74,50 @@ fn remove_from_jx_config(project_dir: &Path, dep_info: &DependencyInfo) -> Resul
         return Err(anyhow::anyhow!("找不到jx.toml配置文件"));
     }
     
-    let config_content = fs::read_to_string(&config_path)?;
-    let mut lines: Vec<String> = config_content.lines().map(|s| s.to_string()).collect();
-    
-    // 查找并移除依赖
-    let mut i = 0;
-    while i < lines.len() {
-        let line = lines[i].trim();
-        if line.starts_with(&format!("{}:{}", dep_info.group_id, dep_info.artifact_id)) {
-            lines.remove(i);
-            println!("已从jx.toml中移除");
-            break;
-        }
-        i += 1;
+    let mut editor = ManifestEditor::open(&config_path)?;
+    let key = format!("{}:{}", dep_info.group_id, dep_info.artifact_id);
+    if !editor.remove_dependency(&key) {
+        return Err(anyhow::anyhow!("jx.toml中没有声明依赖 {}", key));
     }
-    
-    // 写回文件
-    fs::write(&config_path, lines.join("\n"))?;
+    editor.save()?;
+
+    println!("已从jx.toml中移除");
     Ok(())
 }
 
-fn remove_from_maven(project_dir: &Path, dep_info: &DependencyInfo) -> Result<()> {
+fn remove_from_maven(project_dir: &Path, dep_info: &DependencyInfo, managed: bool) -> Result<()> {
     let pom_path = project_dir.join("pom.xml");
-    let pom_content = fs::read_to_string(&pom_path)?;
-    let mut lines: Vec<String> = pom_content.lines().map(|s| s.to_string()).collect();
-    
-    // 查找并移除依赖
-    let mut i = 0;
-    let mut in_dependency = false;
-    let mut dependency_start = 0;
-    
-    while i < lines.len() {
-        let line = lines[i].trim();
-        
-        if line == "<dependency>" {
-            in_dependency = true;
-            dependency_start = i;
-        } else if in_dependency && line == "</dependency>" {
-            // 检查这个依赖是否匹配
-            let dependency_lines = &lines[dependency_start..=i];
-            if dependency_lines.iter().any(|l| l.contains(&format!("<groupId>{}</groupId>", dep_info.group_id))) &&
-               dependency_lines.iter().any(|l| l.contains(&format!("<artifactId>{}</artifactId>", dep_info.artifact_id))) {
-                // 移除整个依赖块
-                for _ in dependency_start..=i {
-                    lines.remove(dependency_start);
-                }
-                println!("已从pom.xml中移除");
-                break;
-            }
-            in_dependency = false;
-        }
-        
-        i += 1;
+    let mut editor = PomEditor::open(&pom_path)?;
+
+    if !editor.remove_dependency(&dep_info.group_id, &dep_info.artifact_id, managed)? {
+        let location = if managed { "<dependencyManagement>" } else { "<dependencies>" };
+        return Err(anyhow::anyhow!(
+            "pom.xml的{}中没有声明依赖 {}:{}",
+            location,
+            dep_info.group_id,
+            dep_info.artifact_id
+        ));
     }
-    
-    // 写回文件
-    fs::write(&pom_path, lines.join("\n"))?;
+    editor.save()?;
+
+    println!("已从pom.xml中移除");
     Ok(())
 }
 
 fn remove_from_gradle(project_dir: &Path, dep_info: &DependencyInfo) -> Result<()> {
-    let build_gradle_path = project_dir.join("build.gradle");
-    let build_content = fs::read_to_string(&build_gradle_path)?;
-    let mut lines: Vec<String> = build_content.lines().map(|s| s.to_string()).collect();
-    
-    // 查找并移除依赖行
-    let mut i = 0;
-    while i < lines.len() {
-        let line = lines[i].trim();
-        if line.contains(&format!("'{}:{}", dep_info.group_id, dep_info.artifact_id)) {
-            lines.remove(i);
-            println!("已从build.gradle中移除");
-            break;
-        }
-        i += 1;
+    let mut script = GradleScript::open(project_dir)?;
+
+    if !script.remove_dependency(&dep_info.group_id, &dep_info.artifact_id)? {
+        return Err(anyhow::anyhow!(
+            "{}中没有声明依赖 {}:{}",
+            script.file_name(),
+            dep_info.group_id,
+            dep_info.artifact_id
+        ));
     }
-    
-    // 写回文件
-    fs::write(&build_gradle_path, lines.join("\n"))?;
+    script.save()?;
+
+    println!("已从{}中移除", script.file_name());
     Ok(())
 }
+
@@ -1,22 +1,34 @@
+use crate::config::{JxConfig, ProjectType};
+use crate::gradle::{self, GradleScript};
+use crate::install::ensure_offline_ready;
+use crate::profile::Profile;
+use crate::workspace;
 use anyhow::{Context, Result};
 use std::path::Path;
 use std::process::Command;
 
-pub fn execute(main_class: Option<String>, args: Vec<String>) -> Result<()> {
-    let current_dir = std::env::current_dir()?;
+pub fn execute(
+    main_class: Option<String>,
+    args: Vec<String>,
+    offline: bool,
+    package: Option<String>,
+    profile: String,
+) -> Result<()> {
+    let current_dir = workspace::project_dir(package.as_deref())?;
     
     // 查找项目配置文件
     let config_file = if current_dir.join("jx.toml").exists() {
         "jx.toml"
     } else if current_dir.join("pom.xml").exists() {
         "pom.xml"
-    } else if current_dir.join("build.gradle").exists() {
-        "build.gradle"
+    } else if let Some(script) = gradle::build_script(&current_dir) {
+        script
     } else {
         return Err(anyhow::anyhow!("找不到项目配置文件，请先运行 'jx init'"));
     };
 
     println!("🚀 运行项目...");
+    let profile = Profile::load(&current_dir, &profile)?;
     
     let class_to_run = if let Some(ref class) = main_class {
         class.clone()
@@ -26,15 +38,21 @@ pub fn execute(main_class: Option<String>, args: Vec<String>) -> Result<()> {
     };
     
     println!("主类: {}", class_to_run);
+    println!("构建配置: {}", profile.name);
     if !args.is_empty() {
         println!("参数: {}", args.join(" "));
     }
+    if offline {
+        println!("离线模式: 仅使用本地缓存");
+    }
 
     // 根据配置文件类型运行项目
     let result = match config_file {
-        "jx.toml" => run_jx_project(&current_dir, &class_to_run, &args),
-        "pom.xml" => run_maven_project(&current_dir, &class_to_run, &args),
-        "build.gradle" => run_gradle_project(&current_dir, &class_to_run, &args),
+        "jx.toml" => run_jx_project(&current_dir, &class_to_run, &args, offline, &profile),
+        "pom.xml" => run_maven_project(&current_dir, &class_to_run, &args, offline, &profile),
+        "build.gradle" | "build.gradle.kts" => {
+            run_gradle_project(&current_dir, &class_to_run, &args, offline, &profile)
+        }
         _ => Err(anyhow::anyhow!("不支持的配置文件类型")),
     };
 
@@ -53,58 +71,56 @@ pub fn execute(main_class: Option<String>, args: Vec<String>) -> Result<()> {
 fn get_main_class_from_config(project_dir: &Path, config_file: &str) -> Result<String> {
     match config_file {
         "jx.toml" => {
-            let config_path = project_dir.join("jx.toml");
-            let config_content = std::fs::read_to_string(&config_path)?;
-            
-            // 从jx.toml中提取主类
-            for line in config_content.lines() {
-                if line.trim().starts_with("main_class = \"") {
-                    let class = line.trim()
-                        .trim_start_matches("main_class = \"")
-                        .trim_end_matches("\"");
-                    return Ok(class.to_string());
-                }
-            }
-            Ok("com.example.Main".to_string()) // 默认主类
+            let config = JxConfig::load_from_dir(project_dir)?;
+            Ok(config
+                .build
+                .main_class
+                .unwrap_or_else(|| "com.example.Main".to_string())) // 默认主类
         }
         "pom.xml" => {
             // Maven项目通常使用exec插件或默认主类
             Ok("com.example.Main".to_string())
         }
-        "build.gradle" => {
+        "build.gradle" | "build.gradle.kts" => {
             // Gradle项目通常使用application插件
-            let build_gradle_path = project_dir.join("build.gradle");
-            let build_content = std::fs::read_to_string(&build_gradle_path)?;
-            
-            for line in build_content.lines() {
-                if line.trim().starts_with("mainClass = '") {
-                    let class = line.trim()
-                        .trim_start_matches("mainClass = '")
-                        .trim_end_matches("'");
-                    return Ok(class.to_string());
-                }
-            }
-            Ok("com.example.Main".to_string()) // 默认主类
+            let script = GradleScript::open(project_dir)?;
+            Ok(script
+                .property("mainClass")
+                .or_else(|| script.property("mainClassName"))
+                .unwrap_or_else(|| "com.example.Main".to_string())) // 默认主类
         }
         _ => Ok("com.example.Main".to_string()),
     }
 }
 
-fn run_jx_project(project_dir: &Path, main_class: &str, args: &[String]) -> Result<()> {
-    // 检查jx.toml中的项目类型
-    let config_path = project_dir.join("jx.toml");
-    let config_content = std::fs::read_to_string(&config_path)?;
-    
-    if config_content.contains("type = \"maven\"") {
-        run_maven_project(project_dir, main_class, args)
-    } else if config_content.contains("type = \"gradle\"") {
-        run_gradle_project(project_dir, main_class, args)
-    } else {
-        Err(anyhow::anyhow!("在jx.toml中找不到有效的项目类型"))
+fn run_jx_project(
+    project_dir: &Path,
+    main_class: &str,
+    args: &[String],
+    offline: bool,
+    profile: &Profile,
+) -> Result<()> {
+    if offline {
+        ensure_offline_ready(project_dir)?;
+    }
+
+    let config = JxConfig::load_from_dir(project_dir)?;
+    match config.project.project_type {
+        ProjectType::Maven => run_maven_project(project_dir, main_class, args, offline, profile),
+        ProjectType::Gradle => run_gradle_project(project_dir, main_class, args, offline, profile),
+        ProjectType::Jx => Err(anyhow::anyhow!(
+            "jx.toml中的项目类型为 \"jx\"，目前需要设置 [project] type = \"maven\" 或 \"gradle\" 才能运行"
+        )),
     }
 }
 
-fn run_maven_project(project_dir: &Path, main_class: &str, args: &[String]) -> Result<()> {
+fn run_maven_project(
+    project_dir: &Path,
+    main_class: &str,
+    args: &[String],
+    offline: bool,
+    profile: &Profile,
+) -> Result<()> {
     println!("使用Maven运行项目...");
     
     if !check_command_exists("mvn") {
@@ -113,8 +129,18 @@ fn run_maven_project(project_dir: &Path, main_class: &str, args: &[String]) -> R
     
     // 先编译项目
     println!("编译项目...");
+    if let Some(warning) = profile.maven_warning() {
+        println!("{}", warning);
+    }
+    let profile_args = profile.maven_arguments();
+    let mut compile_args = vec!["compile"];
+    if offline {
+        compile_args.push("-o");
+    }
+    compile_args.extend(profile_args.iter().map(String::as_str));
+
     let compile_output = Command::new("mvn")
-        .arg("compile")
+        .args(&compile_args)
         .current_dir(project_dir)
         .output()
         .context("Maven编译失败")?;
@@ -138,8 +164,26 @@ fn run_maven_project(project_dir: &Path, main_class: &str, args: &[String]) -> R
         mvn_args.push("-Dexec.args");
         mvn_args.push(&args_str);
     }
+
+    if offline {
+        mvn_args.push("-o");
+    }
+    mvn_args.extend(profile_args.iter().map(String::as_str));
+
+    // exec:java在Maven自己的JVM中运行主类，JVM参数通过MAVEN_OPTS传递
+    let mut command = Command::new("mvn");
+    if !profile.jvm_opts.is_empty() {
+        let mut maven_opts = std::env::var("MAVEN_OPTS").unwrap_or_default();
+        for opt in &profile.jvm_opts {
+            if !maven_opts.is_empty() {
+                maven_opts.push(' ');
+            }
+            maven_opts.push_str(opt);
+        }
+        command.env("MAVEN_OPTS", maven_opts);
+    }
     
-    let run_output = Command::new("mvn")
+    let run_output = command
         .args(&mvn_args)
         .current_dir(project_dir)
         .output()
@@ -157,7 +201,13 @@ fn run_maven_project(project_dir: &Path, main_class: &str, args: &[String]) -> R
     Ok(())
 }
 
-fn run_gradle_project(project_dir: &Path, _main_class: &str, args: &[String]) -> Result<()> {
+fn run_gradle_project(
+    project_dir: &Path,
+    _main_class: &str,
+    args: &[String],
+    offline: bool,
+    profile: &Profile,
+) -> Result<()> {
     println!("使用Gradle运行项目...");
     
     if !check_command_exists("gradle") {
@@ -166,8 +216,15 @@ fn run_gradle_project(project_dir: &Path, _main_class: &str, args: &[String]) ->
     
     // 先编译项目
     println!("编译项目...");
+    let profile_args = profile.gradle_arguments()?;
+    let mut compile_args = vec!["compileJava"];
+    if offline {
+        compile_args.push("--offline");
+    }
+    compile_args.extend(profile_args.iter().map(String::as_str));
+
     let compile_output = Command::new("gradle")
-        .arg("compileJava")
+        .args(&compile_args)
         .current_dir(project_dir)
         .output()
         .context("Gradle编译失败")?;
@@ -191,6 +248,11 @@ fn run_gradle_project(project_dir: &Path, _main_class: &str, args: &[String]) ->
         gradle_args.push("--args");
         gradle_args.push(&args_str);
     }
+
+    if offline {
+        gradle_args.push("--offline");
+    }
+    gradle_args.extend(profile_args.iter().map(String::as_str));
     
     let run_output = Command::new("gradle")
         .args(&gradle_args)
@@ -0,0 +1,235 @@
+use crate::classpath;
+use crate::commands::venv;
+use crate::config::{JxConfig, ScriptConfig};
+use crate::workspace;
+use anyhow::{Context, Result};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 运行jx.toml中`[scripts]`定义的脚本，依赖的脚本按顺序先运行；没有指定名称时列出所有脚本
+pub fn execute(name: Option<String>, args: Vec<String>, package: Option<String>) -> Result<()> {
+    let project_dir = workspace::project_dir(package.as_deref())?;
+    let config_path = project_dir.join("jx.toml");
+    if !config_path.exists() {
+        return Err(anyhow::anyhow!("找不到项目配置文件，请先运行 'jx init'"));
+    }
+    let config = JxConfig::load(&config_path)?;
+
+    match name {
+        Some(name) => run(&project_dir, &config, &name, &args),
+        None => {
+            list_scripts(&config);
+            Ok(())
+        }
+    }
+}
+
+/// `jx <name>`: 没有同名的内置命令时运行同名脚本
+pub fn fallback(name: &str, mut args: Vec<String>) -> Result<()> {
+    // 外部子命令的参数原样传入，与run-script一样去掉开头的 --
+    if args.first().is_some_and(|arg| arg == "--") {
+        args.remove(0);
+    }
+
+    let project_dir = std::env::current_dir()?;
+    let config_path = project_dir.join("jx.toml");
+    let config = if config_path.exists() {
+        Some(JxConfig::load(&config_path)?)
+    } else {
+        None
+    };
+
+    match config {
+        Some(config) if config.scripts.contains_key(name) => run(&project_dir, &config, name, &args),
+        _ => Err(anyhow::anyhow!(
+            "未知的命令 `{}`，当前项目的jx.toml中也没有同名脚本，运行 'jx --help' 查看可用命令",
+            name
+        )),
+    }
+}
+
+fn run(project_dir: &Path, config: &JxConfig, name: &str, args: &[String]) -> Result<()> {
+    let order = run_order(config, name)?;
+    let env = ScriptEnv::new(project_dir, config)?;
+
+    for script_name in &order {
+        let script = &config.scripts[script_name];
+        // 命令行上的额外参数只传给指定的脚本
+        let extra_args = if script_name == name { args } else { &[] };
+        run_script(&env, script_name, script, extra_args)?;
+    }
+    Ok(())
+}
+
+/// 按依赖顺序排列要运行的脚本，每个脚本只运行一次，依赖成环时报错
+pub fn run_order(config: &JxConfig, name: &str) -> Result<Vec<String>> {
+    if !config.scripts.contains_key(name) {
+        let available: Vec<&str> = config.scripts.keys().map(String::as_str).collect();
+        return Err(anyhow::anyhow!(
+            "jx.toml中没有脚本 {}，可选: {}",
+            name,
+            if available.is_empty() {
+                "(无)".to_string()
+            } else {
+                available.join(", ")
+            }
+        ));
+    }
+
+    fn visit(
+        config: &JxConfig,
+        name: &str,
+        order: &mut Vec<String>,
+        visiting: &mut Vec<String>,
+    ) -> Result<()> {
+        if order.iter().any(|done| done == name) {
+            return Ok(());
+        }
+        if visiting.iter().any(|current| current == name) {
+            visiting.push(name.to_string());
+            return Err(anyhow::anyhow!("脚本的依赖关系成环: {}", visiting.join(" -> ")));
+        }
+
+        visiting.push(name.to_string());
+        for dependency in &config.scripts[name].depends {
+            visit(config, dependency, order, visiting)?;
+        }
+        visiting.pop();
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    visit(config, name, &mut order, &mut Vec::new())?;
+    Ok(order)
+}
+
+/// 脚本的运行环境: 项目目录、classpath和虚拟环境中的JDK
+struct ScriptEnv {
+    project_dir: PathBuf,
+    classpath: OsString,
+    java_home: Option<PathBuf>,
+}
+
+impl ScriptEnv {
+    fn new(project_dir: &Path, config: &JxConfig) -> Result<Self> {
+        let classpath = classpath::join(&classpath::project_classpath(project_dir, config)?)?;
+        Ok(Self {
+            project_dir: project_dir.to_path_buf(),
+            classpath,
+            java_home: venv::active_java_home()?,
+        })
+    }
+
+    /// 设置工作目录，导出`CLASSPATH`、`JX_PROJECT_DIR`，有虚拟环境时导出`JAVA_HOME`并加入`PATH`
+    fn apply(&self, command: &mut Command) -> Result<()> {
+        command
+            .current_dir(&self.project_dir)
+            .env("CLASSPATH", &self.classpath)
+            .env("JX_PROJECT_DIR", &self.project_dir);
+
+        if let Some(java_home) = &self.java_home {
+            let mut paths = vec![java_home.join("bin")];
+            if let Some(path) = std::env::var_os("PATH") {
+                paths.extend(std::env::split_paths(&path));
+            }
+            let path = std::env::join_paths(paths).context("无法设置PATH")?;
+            command.env("JAVA_HOME", java_home).env("PATH", path);
+        }
+        Ok(())
+    }
+
+    fn java(&self) -> PathBuf {
+        match &self.java_home {
+            Some(java_home) => java_home.join("bin").join("java"),
+            None => PathBuf::from("java"),
+        }
+    }
+}
+
+fn run_script(env: &ScriptEnv, name: &str, script: &ScriptConfig, extra_args: &[String]) -> Result<()> {
+    let mut command = if let Some(cmd) = &script.cmd {
+        let mut line = cmd.clone();
+        for arg in extra_args {
+            line.push(' ');
+            line.push_str(&shell_quote(arg));
+        }
+        println!("▶ {}: {}", name, line);
+        shell_command(&line)
+    } else if let Some(main_class) = &script.main {
+        let args: Vec<&String> = script.args.iter().chain(extra_args).collect();
+        println!(
+            "▶ {}: java {} {}",
+            name,
+            main_class,
+            args.iter().map(|arg| arg.as_str()).collect::<Vec<_>>().join(" ")
+        );
+        let mut command = Command::new(env.java());
+        command.arg("-cp").arg(&env.classpath).arg(main_class).args(args);
+        command
+    } else {
+        // 只有depends的脚本
+        println!("▶ {}", name);
+        return Ok(());
+    };
+
+    env.apply(&mut command)?;
+    let status = command
+        .status()
+        .with_context(|| format!("无法启动脚本 {}", name))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!(
+            "脚本 {} 失败，退出码: {}",
+            name,
+            status
+                .code()
+                .map_or_else(|| "无 (被信号终止)".to_string(), |code| code.to_string())
+        ));
+    }
+    Ok(())
+}
+
+fn list_scripts(config: &JxConfig) {
+    if config.scripts.is_empty() {
+        println!("jx.toml中没有定义 [scripts]");
+        return;
+    }
+
+    println!("📜 可用脚本:");
+    for (name, script) in &config.scripts {
+        let summary = match (&script.description, &script.cmd, &script.main) {
+            (Some(description), _, _) => description.clone(),
+            (None, Some(cmd), _) => cmd.clone(),
+            (None, None, Some(main_class)) => format!("java {}", main_class),
+            (None, None, None) => format!("依赖: {}", script.depends.join(", ")),
+        };
+        println!("  {:<16} {}", name, summary);
+    }
+}
+
+#[cfg(unix)]
+fn shell_command(line: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(line);
+    command
+}
+
+#[cfg(windows)]
+fn shell_command(line: &str) -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/C").arg(line);
+    command
+}
+
+/// 追加到shell命令后的参数按原样传递
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | '=' | ':' | ','))
+    {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
@@ -1,12 +1,39 @@
-use anyhow::{Context, Result};
+use crate::config::JxConfig;
+use crate::gradle::{self, GradleScript};
+use crate::index::{SearchHit, SearchIndex};
+use crate::registry::{MavenRegistry, SearchPage, SearchQuery, SearchSort};
+use anyhow::Result;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
-pub fn execute(query: String, limit: usize) -> Result<()> {
+/// search命令的选项，分页、过滤和排序只作用于Maven Central
+pub struct SearchOptions {
+    pub limit: usize,
+    pub page: usize,
+    pub group: Option<String>,
+    pub packaging: Option<String>,
+    pub all_versions: bool,
+    pub sort: SearchSort,
+    pub json: bool,
+    pub offline: bool,
+    pub reindex: bool,
+}
+
+pub async fn execute(query: String, options: SearchOptions) -> Result<()> {
+    // JSON输出只包含远程结果，供脚本使用
+    if options.json {
+        if options.offline {
+            return Err(anyhow::anyhow!("离线模式下无法查询Maven Central，不能输出JSON结果"));
+        }
+        let page = search_maven_central(&query, &options).await?;
+        println!("{}", serde_json::to_string_pretty(&page)?);
+        return Ok(());
+    }
+
     println!("🔍 搜索依赖...");
     println!("搜索关键词: {}", query);
-    println!("最大结果数: {}", limit);
+    println!("最大结果数: {}", options.limit);
     
     let current_dir = std::env::current_dir()?;
     
@@ -15,7 +42,7 @@ pub fn execute(query: String, limit: usize) -> Result<()> {
     println!("项目类型: {}", project_type);
     
     // 本地搜索
-    let local_results = search_local_dependencies(&current_dir, &query, limit)?;
+    let local_results = search_local_dependencies(&current_dir, &query, options.limit)?;
     if !local_results.is_empty() {
         println!("\n📁 本地依赖搜索结果:");
         for (i, result) in local_results.iter().enumerate() {
@@ -24,7 +51,7 @@ pub fn execute(query: String, limit: usize) -> Result<()> {
     }
     
     // 配置文件搜索
-    let config_results = search_in_config_files(&current_dir, &query, limit)?;
+    let config_results = search_in_config_files(&current_dir, &query, options.limit)?;
     if !config_results.is_empty() {
         println!("\n⚙️ 配置文件搜索结果:");
         for (i, result) in config_results.iter().enumerate() {
@@ -32,19 +59,38 @@ pub fn execute(query: String, limit: usize) -> Result<()> {
         }
     }
     
-    // Maven Central搜索（模拟）
-    let central_results = search_maven_central(&query, limit)?;
-    if !central_results.is_empty() {
-        println!("\n🌐 Maven Central搜索结果:");
-        for (i, result) in central_results.iter().enumerate() {
-            println!("  {}. {}:{}:{}", i + 1, result.group_id, result.artifact_id, result.version);
-            if let Some(desc) = &result.description {
-                println!("     描述: {}", desc);
-            }
+    // 本地缓存索引搜索
+    let index = SearchIndex::load_and_refresh(options.reindex)?;
+    let cache_results = index.search(&query, options.limit);
+    if !cache_results.is_empty() {
+        println!("\n💾 本地缓存搜索结果 (索引 {} 个构件):", index.artifacts.len());
+        for (i, hit) in cache_results.iter().enumerate() {
+            display_index_hit(&index, i + 1, hit);
         }
     }
+
+    // Maven Central搜索，离线模式下跳过，失败时只提示不编造结果
+    let central_count = if options.offline {
+        println!("\n📴 离线模式: 以上结果仅来自本地缓存和项目文件，未查询Maven Central");
+        0
+    } else {
+        match search_maven_central(&query, &options).await {
+            Ok(page) => {
+                display_central_page(&page);
+                page.artifacts.len()
+            }
+            Err(e) => {
+                println!("\n⚠️ Maven Central搜索失败: {:#}", e);
+                println!("   以上结果仅来自本地缓存和项目文件");
+                0
+            }
+        }
+    };
     
-    let total_results = local_results.len() + config_results.len() + central_results.len();
+    let total_results = local_results.len()
+        + config_results.len()
+        + cache_results.len()
+        + central_count;
     if total_results == 0 {
         println!("\n❌ 未找到匹配的依赖");
         println!("💡 提示:");
@@ -58,11 +104,43 @@ pub fn execute(query: String, limit: usize) -> Result<()> {
     Ok(())
 }
 
+fn display_index_hit(index: &SearchIndex, position: usize, hit: &SearchHit) {
+    let artifact = &hit.artifact;
+    println!(
+        "  {}. {}:{}:{}",
+        position, artifact.group_id, artifact.artifact_id, artifact.version
+    );
+
+    if let Some(ref name) = artifact.name {
+        println!("     名称: {}", name);
+    }
+    if let Some(ref description) = artifact.description {
+        println!("     描述: {}", description);
+    }
+    if !artifact.licenses.is_empty() {
+        println!("     许可证: {}", artifact.licenses.join(", "));
+    }
+
+    let versions = index.versions_of(&artifact.group_id, &artifact.artifact_id);
+    if versions.len() > 1 {
+        println!("     缓存版本: {}", versions.join(", "));
+    }
+
+    if !hit.matched_classes.is_empty() {
+        let shown: Vec<&str> = hit.matched_classes.iter().take(5).map(|s| s.as_str()).collect();
+        println!("     包含类: {}", shown.join(", "));
+        if hit.matched_classes.len() > shown.len() {
+            println!("     ... 另有 {} 个匹配类", hit.matched_classes.len() - shown.len());
+        }
+    }
+}
+
 #[derive(Debug)]
 struct DependencyResult {
     group_id: String,
     artifact_id: String,
     version: String,
+    #[allow(dead_code)]
     description: Option<String>,
     #[allow(dead_code)]
     source: String,
@@ -70,14 +148,13 @@ struct DependencyResult {
 
 fn detect_project_type(project_dir: &Path) -> Result<String> {
     let has_pom = project_dir.join("pom.xml").exists();
-    let has_gradle = project_dir.join("build.gradle").exists();
-    let has_settings_gradle = project_dir.join("settings.gradle").exists();
+    let has_gradle = gradle::is_gradle_project(project_dir);
     
-    if has_pom && (has_gradle || has_settings_gradle) {
+    if has_pom && has_gradle {
         Ok("Maven + Gradle".to_string())
     } else if has_pom {
         Ok("Maven".to_string())
-    } else if has_gradle || has_settings_gradle {
+    } else if has_gradle {
         Ok("Gradle".to_string())
     } else {
         Ok("未知".to_string())
@@ -129,19 +206,15 @@ fn search_in_config_files(project_dir: &Path, query: &str, limit: usize) -> Resu
         results.extend(pom_results);
     }
     
-    // 搜索build.gradle
-    let gradle_path = project_dir.join("build.gradle");
-    if gradle_path.exists() {
-        let gradle_content = fs::read_to_string(&gradle_path)?;
-        let gradle_results = parse_gradle_dependencies(&gradle_content, query)?;
+    // 搜索build.gradle或build.gradle.kts
+    if gradle::build_script(project_dir).is_some() {
+        let gradle_results = search_gradle_dependencies(project_dir, query)?;
         results.extend(gradle_results);
     }
     
     // 搜索jx.toml
-    let jx_path = project_dir.join("jx.toml");
-    if jx_path.exists() {
-        let jx_content = fs::read_to_string(&jx_path)?;
-        let jx_results = parse_jx_dependencies(&jx_content, query)?;
+    if project_dir.join("jx.toml").exists() {
+        let jx_results = search_jx_dependencies(project_dir, query)?;
         results.extend(jx_results);
     }
     
@@ -150,152 +223,72 @@ fn search_in_config_files(project_dir: &Path, query: &str, limit: usize) -> Resu
     Ok(results)
 }
 
-fn search_maven_central(query: &str, limit: usize) -> Result<Vec<DependencyResult>> {
-    // 实现真实的Maven Central搜索
-    let mut results = Vec::new();
-    
-    // 构建搜索URL
-    let search_url = format!(
-        "https://search.maven.org/solrsearch/select?q={}&rows={}&wt=json",
-        urlencoding::encode(query),
-        limit
-    );
-    
-    println!("🔍 正在搜索Maven Central: {}", search_url);
-    
-    // 使用curl进行HTTP请求
-    let output = std::process::Command::new("curl")
-        .args(&["-s", "-H", "Accept: application/json", &search_url])
-        .output()
-        .context("执行curl命令失败")?;
-    
-    if !output.status.success() {
-        println!("⚠️ Maven Central搜索失败，使用本地模拟结果");
-        return search_maven_central_fallback(query, limit);
+async fn search_maven_central(query: &str, options: &SearchOptions) -> Result<SearchPage> {
+    let search_query = SearchQuery::new(query)
+        .group(options.group.clone())
+        .packaging(options.packaging.clone())
+        .all_versions(options.all_versions)
+        .page(options.page, options.limit);
+
+    let mut page = MavenRegistry::new().search_artifacts(&search_query).await?;
+    page.sort(options.sort);
+    Ok(page)
+}
+
+fn display_central_page(page: &SearchPage) {
+    if page.artifacts.is_empty() {
+        println!("\n🌐 Maven Central: 第 {} 页没有结果 (共 {} 个匹配)", page.page, page.total);
+        return;
     }
-    
-    let response = String::from_utf8_lossy(&output.stdout);
-    
-    // 解析JSON响应
-    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&response) {
-        if let Some(response_obj) = json.get("response") {
-            if let Some(docs) = response_obj.get("docs") {
-                if let Some(docs_array) = docs.as_array() {
-                    for doc in docs_array {
-                        if let (Some(group_id), Some(artifact_id), Some(version)) = (
-                            doc.get("g").and_then(|v| v.as_str()),
-                            doc.get("a").and_then(|v| v.as_str()),
-                            doc.get("v").and_then(|v| v.as_str())
-                        ) {
-                            let description = doc.get("description")
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string());
-                            
-                            results.push(DependencyResult {
-                                group_id: group_id.to_string(),
-                                artifact_id: artifact_id.to_string(),
-                                version: version.to_string(),
-                                description,
-                                source: "maven-central".to_string(),
-                            });
-                        }
-                    }
-                }
-            }
+
+    println!("\n🌐 Maven Central搜索结果:");
+    let offset = (page.page - 1) * page.rows;
+    for (i, artifact) in page.artifacts.iter().enumerate() {
+        println!(
+            "  {}. {}:{}:{}",
+            offset + i + 1,
+            artifact.group_id,
+            artifact.artifact_id,
+            artifact.version
+        );
+
+        let mut details = vec![format!("类型: {}", artifact.packaging)];
+        if !artifact.last_updated.is_empty() {
+            details.push(format!("更新: {}", artifact.last_updated));
         }
+        if let Some(count) = artifact.version_count {
+            details.push(format!("版本数: {}", count));
+        }
+        println!("     {}", details.join("  "));
     }
-    
-    // 如果解析失败或没有结果，使用备用方案
-    if results.is_empty() {
-        println!("⚠️ JSON解析失败，使用本地模拟结果");
-        return search_maven_central_fallback(query, limit);
-    }
-    
-    Ok(results)
-}
 
-fn search_maven_central_fallback(query: &str, limit: usize) -> Result<Vec<DependencyResult>> {
-    // 本地模拟搜索结果作为备用方案
-    let mut results = Vec::new();
-    
-    // 扩展的模拟搜索结果
-    let mock_results = [
-        ("org.springframework", "spring-core", "5.3.0", "Spring Framework核心模块"),
-        ("org.springframework", "spring-web", "5.3.0", "Spring Web模块"),
-        ("org.springframework", "spring-boot-starter", "2.7.0", "Spring Boot启动器"),
-        ("org.springframework", "spring-context", "5.3.0", "Spring上下文模块"),
-        ("org.springframework", "spring-beans", "5.3.0", "Spring Beans模块"),
-        ("com.fasterxml.jackson.core", "jackson-databind", "2.13.0", "Jackson数据绑定"),
-        ("com.fasterxml.jackson.core", "jackson-core", "2.13.0", "Jackson核心模块"),
-        ("org.apache.commons", "commons-lang3", "3.12.0", "Apache Commons Lang3"),
-        ("org.apache.commons", "commons-io", "2.11.0", "Apache Commons IO"),
-        ("junit", "junit", "4.13.2", "JUnit测试框架"),
-        ("org.mockito", "mockito-core", "4.5.1", "Mockito模拟框架"),
-        ("org.mockito", "mockito-junit-jupiter", "4.5.1", "Mockito JUnit5支持"),
-        ("org.slf4j", "slf4j-api", "1.7.36", "SLF4J日志门面"),
-        ("ch.qos.logback", "logback-classic", "1.2.11", "Logback经典模块"),
-        ("org.hibernate", "hibernate-core", "5.6.0", "Hibernate核心模块"),
-        ("mysql", "mysql-connector-java", "8.0.27", "MySQL连接器"),
-        ("org.postgresql", "postgresql", "42.3.1", "PostgreSQL连接器"),
-        ("org.mongodb", "mongodb-driver-sync", "4.4.0", "MongoDB同步驱动"),
-        ("org.elasticsearch.client", "elasticsearch-rest-high-level-client", "7.17.0", "Elasticsearch高级客户端"),
-        ("org.apache.kafka", "kafka-clients", "3.0.0", "Kafka客户端"),
-        ("org.apache.spark", "spark-core_2.12", "3.2.0", "Spark核心模块"),
-    ];
-    
-    for (group_id, artifact_id, version, description) in &mock_results {
-        if group_id.to_lowercase().contains(&query.to_lowercase()) ||
-           artifact_id.to_lowercase().contains(&query.to_lowercase()) {
-            results.push(DependencyResult {
-                group_id: group_id.to_string(),
-                artifact_id: artifact_id.to_string(),
-                version: version.to_string(),
-                description: Some(description.to_string()),
-                source: "maven-central-fallback".to_string(),
-            });
-        }
+    println!("  第 {}/{} 页，共 {} 个匹配", page.page, page.total_pages(), page.total);
+    if (page.page as u64) < page.total_pages() {
+        println!("  💡 使用 --page {} 查看下一页", page.page + 1);
     }
-    
-    // 限制结果数量
-    results.truncate(limit);
-    Ok(results)
 }
 
 #[derive(Debug)]
-struct JarDependencyInfo {
-    group_id: String,
-    artifact_id: String,
-    version: String,
+pub(crate) struct JarDependencyInfo {
+    pub(crate) group_id: String,
+    pub(crate) artifact_id: String,
+    pub(crate) version: String,
 }
 
-fn parse_jar_filename(filename: &str) -> Option<JarDependencyInfo> {
+pub(crate) fn parse_jar_filename(filename: &str) -> Option<JarDependencyInfo> {
     // 尝试从jar文件名解析依赖信息
-    // 格式通常是: artifactId-version.jar 或 groupId-artifactId-version.jar
+    // 格式通常是: artifactId-version.jar，版本从第一个 "-数字" 开始
     
-    if !filename.ends_with(".jar") {
-        return None;
-    }
+    let name_without_ext = filename.strip_suffix(".jar")?;
+    let bytes = name_without_ext.as_bytes();
     
-    let name_without_ext = &filename[..filename.len() - 4];
-    let parts: Vec<&str> = name_without_ext.split('-').collect();
+    let split_at = (1..bytes.len()).find(|&i| bytes[i - 1] == b'-' && bytes[i].is_ascii_digit())?;
     
-    if parts.len() >= 2 {
-        let artifact_id = parts[0].to_string();
-        let version = parts[1].to_string();
-        let group_id = if parts.len() > 2 {
-            parts[..parts.len() - 2].join(".")
-        } else {
-            "unknown".to_string()
-        };
-        
-        Some(JarDependencyInfo {
-            group_id,
-            artifact_id,
-            version,
-        })
-    } else {
-        None
-    }
+    Some(JarDependencyInfo {
+        group_id: "unknown".to_string(),
+        artifact_id: name_without_ext[..split_at - 1].to_string(),
+        version: name_without_ext[split_at..].to_string(),
+    })
 }
 
 fn parse_maven_dependencies(pom_content: &str, query: &str) -> Result<Vec<DependencyResult>> {
@@ -349,89 +342,44 @@ fn parse_maven_dependencies(pom_content: &str, query: &str) -> Result<Vec<Depend
     Ok(results)
 }
 
-fn parse_gradle_dependencies(gradle_content: &str, query: &str) -> Result<Vec<DependencyResult>> {
-    let mut results = Vec::new();
-    let lines: Vec<&str> = gradle_content.lines().collect();
-    
-    let mut in_dependencies = false;
-    
-    for line in lines {
-        let line = line.trim();
-        
-        if line == "dependencies {" {
-            in_dependencies = true;
-        } else if line == "}" && in_dependencies {
-            in_dependencies = false;
-            break;
-        } else if in_dependencies && line.contains("'") {
-            // 解析Gradle依赖行，格式通常是: implementation 'groupId:artifactId:version'
-            let parts: Vec<&str> = line.split('\'').collect();
-            if parts.len() >= 2 {
-                let dep_coord = parts[1];
-                let coord_parts: Vec<&str> = dep_coord.split(':').collect();
-                
-                if coord_parts.len() >= 2 {
-                    let group_id = coord_parts[0];
-                    let artifact_id = coord_parts[1];
-                    let version = coord_parts.get(2).unwrap_or(&"*");
-                    
-                    if group_id.to_lowercase().contains(&query.to_lowercase()) ||
-                       artifact_id.to_lowercase().contains(&query.to_lowercase()) {
-                        results.push(DependencyResult {
-                            group_id: group_id.to_string(),
-                            artifact_id: artifact_id.to_string(),
-                            version: version.to_string(),
-                            description: Some("Gradle依赖".to_string()),
-                            source: "build.gradle".to_string(),
-                        });
-                    }
-                }
-            }
-        }
-    }
-    
-    Ok(results)
+fn search_gradle_dependencies(project_dir: &Path, query: &str) -> Result<Vec<DependencyResult>> {
+    let script = GradleScript::open(project_dir)?;
+    let query = query.to_lowercase();
+
+    Ok(script
+        .dependencies()
+        .into_iter()
+        .filter(|dep| {
+            dep.group_id.to_lowercase().contains(&query)
+                || dep.artifact_id.to_lowercase().contains(&query)
+        })
+        .map(|dep| DependencyResult {
+            group_id: dep.group_id,
+            artifact_id: dep.artifact_id,
+            version: dep.version.unwrap_or_else(|| "*".to_string()),
+            description: Some("Gradle依赖".to_string()),
+            source: script.file_name().to_string(),
+        })
+        .collect())
 }
 
-fn parse_jx_dependencies(jx_content: &str, query: &str) -> Result<Vec<DependencyResult>> {
-    let mut results = Vec::new();
-    let lines: Vec<&str> = jx_content.lines().collect();
-    
-    let mut in_dependencies = false;
-    
-    for line in lines {
-        let line = line.trim();
-        
-        if line == "[dependencies]" {
-            in_dependencies = true;
-        } else if line.starts_with('[') && line != "[dependencies]" {
-            in_dependencies = false;
-        } else if in_dependencies && line.contains('=') {
-            // 解析jx.toml依赖行，格式通常是: groupId:artifactId = "version"
-            let parts: Vec<&str> = line.split('=').collect();
-            if parts.len() == 2 {
-                let dep_coord = parts[0].trim();
-                let version = parts[1].trim().trim_matches('"');
-                
-                let coord_parts: Vec<&str> = dep_coord.split(':').collect();
-                if coord_parts.len() == 2 {
-                    let group_id = coord_parts[0];
-                    let artifact_id = coord_parts[1];
-                    
-                    if group_id.to_lowercase().contains(&query.to_lowercase()) ||
-                       artifact_id.to_lowercase().contains(&query.to_lowercase()) {
-                        results.push(DependencyResult {
-                            group_id: group_id.to_string(),
-                            artifact_id: artifact_id.to_string(),
-                            version: version.to_string(),
-                            description: Some("jx配置依赖".to_string()),
-                            source: "jx.toml".to_string(),
-                        });
-                    }
-                }
-            }
-        }
-    }
-    
-    Ok(results)
+fn search_jx_dependencies(project_dir: &Path, query: &str) -> Result<Vec<DependencyResult>> {
+    let config = JxConfig::load_from_dir(project_dir)?;
+    let query = query.to_lowercase();
+
+    Ok(config
+        .dependencies()
+        .iter()
+        .filter(|dep| {
+            dep.group_id.to_lowercase().contains(&query)
+                || dep.artifact_id.to_lowercase().contains(&query)
+        })
+        .map(|dep| DependencyResult {
+            group_id: dep.group_id.clone(),
+            artifact_id: dep.artifact_id.clone(),
+            version: dep.version.clone(),
+            description: Some("jx配置依赖".to_string()),
+            source: "jx.toml".to_string(),
+        })
+        .collect())
 }
@@ -1,19 +1,56 @@
+use crate::config::{JxConfig, ProjectType};
+use crate::gradle::{self, GradleScript};
+use crate::install::ensure_offline_ready;
+use crate::modules::{self, Module};
+use crate::profile::Profile;
+use crate::workspace::Workspace;
 use anyhow::{Context, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-pub fn execute(test_class: Option<String>, method: Option<String>) -> Result<()> {
+pub fn execute(
+    test_class: Option<String>,
+    method: Option<String>,
+    offline: bool,
+    packages: Vec<String>,
+    profile: String,
+) -> Result<()> {
     println!("🧪 运行测试...");
     
     let current_dir = std::env::current_dir()?;
+    let workspace = Workspace::discover(&current_dir)?;
+    if let Some(workspace) = &workspace {
+        let result = test_workspace(
+            workspace,
+            &current_dir,
+            &packages,
+            &test_class,
+            &method,
+            offline,
+            &profile,
+        );
+        return match result {
+            Ok(_) => {
+                println!("✅ 测试执行完成!");
+                Ok(())
+            }
+            Err(e) => {
+                eprintln!("❌ 测试执行失败: {}", e);
+                Err(e)
+            }
+        };
+    }
     
     // 检测项目类型
     let project_type = detect_project_type(&current_dir)?;
     println!("项目类型: {}", project_type);
     
     // 获取测试配置
-    let test_config = get_test_config(&current_dir, &project_type)?;
+    let mut test_config = get_test_config(&current_dir, &project_type)?;
+    test_config.offline = offline;
+    test_config.modules = modules::select(&current_dir, &packages)?;
+    test_config.profile = Profile::load(&current_dir, &profile)?;
     
     // 显示测试信息
     display_test_info(&test_config, &test_class, &method);
@@ -38,6 +75,29 @@ pub fn execute(test_class: Option<String>, method: Option<String>) -> Result<()>
     }
 }
 
+/// 依次测试选中的工作区成员，遇到失败的成员时停止
+fn test_workspace(
+    workspace: &Workspace,
+    current_dir: &Path,
+    packages: &[String],
+    test_class: &Option<String>,
+    method: &Option<String>,
+    offline: bool,
+    profile: &str,
+) -> Result<()> {
+    for member in workspace.select(packages, current_dir)? {
+        println!("\n📦 测试 {} ({})", member.name(), member.path);
+
+        let mut test_config = get_jx_test_config(&member.dir)?;
+        test_config.offline = offline;
+        test_config.profile = Profile::load(&member.dir, profile)?;
+        display_test_info(&test_config, test_class, method);
+        run_jx_tests(&member.dir, &test_config, test_class, method)
+            .map_err(|e| anyhow::anyhow!("成员 {} 的测试失败: {}", member.name(), e))?;
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 struct TestConfig {
     test_framework: String,
@@ -48,21 +108,24 @@ struct TestConfig {
     java_version: Option<String>,
     #[allow(dead_code)]
     dependencies: Vec<String>,
+    offline: bool,
+    /// `-p`选择的Maven/Gradle子模块，为空时测试整个项目
+    modules: Vec<Module>,
+    profile: Profile,
 }
 
 fn detect_project_type(project_dir: &Path) -> Result<String> {
     let has_pom = project_dir.join("pom.xml").exists();
-    let has_gradle = project_dir.join("build.gradle").exists();
-    let has_settings_gradle = project_dir.join("settings.gradle").exists();
+    let has_gradle = gradle::is_gradle_project(project_dir);
     let has_jx = project_dir.join("jx.toml").exists();
     
     if has_jx {
         Ok("jx".to_string())
-    } else if has_pom && (has_gradle || has_settings_gradle) {
+    } else if has_pom && has_gradle {
         Ok("Maven + Gradle".to_string())
     } else if has_pom {
         Ok("Maven".to_string())
-    } else if has_gradle || has_settings_gradle {
+    } else if has_gradle {
         Ok("Gradle".to_string())
     } else {
         Ok("未知".to_string())
@@ -90,6 +153,9 @@ fn get_maven_test_config(project_dir: &Path) -> Result<TestConfig> {
         test_class: None,
         java_version: Some("11".to_string()),
         dependencies: Vec::new(),
+        offline: false,
+        modules: Vec::new(),
+        profile: Profile::default(),
     };
     
     let lines: Vec<&str> = pom_content.lines().collect();
@@ -123,39 +189,28 @@ fn get_maven_test_config(project_dir: &Path) -> Result<TestConfig> {
 }
 
 fn get_gradle_test_config(project_dir: &Path) -> Result<TestConfig> {
-    let build_gradle_path = project_dir.join("build.gradle");
-    let build_content = fs::read_to_string(&build_gradle_path)?;
+    // 多项目构建的根目录可以只有设置脚本
+    if gradle::build_script(project_dir).is_none() {
+        let mut config = get_generic_test_config(project_dir)?;
+        config.test_class_dir = project_dir.join("build/classes/java/test");
+        return Ok(config);
+    }
+    let script = GradleScript::open(project_dir)?;
+    let build_content = script.content();
     
     let mut config = TestConfig {
         test_framework: "JUnit".to_string(),
         test_source_dir: project_dir.join("src/test/java"),
         test_class_dir: project_dir.join("build/classes/java/test"),
-        main_class: None,
+        main_class: script.property("mainClass"),
         test_class: None,
-        java_version: Some("11".to_string()),
+        java_version: script.java_version().or_else(|| Some("11".to_string())),
         dependencies: Vec::new(),
+        offline: false,
+        modules: Vec::new(),
+        profile: Profile::default(),
     };
     
-    let lines: Vec<&str> = build_content.lines().collect();
-    
-    for line in lines {
-        let line = line.trim();
-        
-        if line.starts_with("sourceCompatibility") {
-            if let Some(quote_start) = line.find('\'') {
-                if let Some(quote_end) = line.rfind('\'') {
-                    config.java_version = Some(line[quote_start+1..quote_end].to_string());
-                }
-            }
-        } else if line.starts_with("mainClass") {
-            if let Some(quote_start) = line.find('\'') {
-                if let Some(quote_end) = line.rfind('\'') {
-                    config.main_class = Some(line[quote_start+1..quote_end].to_string());
-                }
-            }
-        }
-    }
-    
     // 检测测试框架
     if build_content.contains("junit") {
         config.test_framework = "JUnit".to_string();
@@ -167,46 +222,20 @@ fn get_gradle_test_config(project_dir: &Path) -> Result<TestConfig> {
 }
 
 fn get_jx_test_config(project_dir: &Path) -> Result<TestConfig> {
-    let jx_path = project_dir.join("jx.toml");
-    let jx_content = fs::read_to_string(&jx_path)?;
-    
-    let mut config = TestConfig {
+    let jx_config = JxConfig::load_from_dir(project_dir)?;
+
+    Ok(TestConfig {
         test_framework: "JUnit".to_string(),
         test_source_dir: project_dir.join("src/test/java"),
         test_class_dir: project_dir.join("target/test-classes"),
-        main_class: None,
-        test_class: None,
-        java_version: Some("11".to_string()),
+        java_version: Some(jx_config.java_version().unwrap_or("11").to_string()),
+        main_class: jx_config.build.main_class,
+        test_class: jx_config.build.test_class,
         dependencies: Vec::new(),
-    };
-    
-    let lines: Vec<&str> = jx_content.lines().collect();
-    
-    for line in lines {
-        let line = line.trim();
-        
-        if line.starts_with("main_class = \"") {
-            let start = "main_class = \"".len();
-            let end = line.len() - 1;
-            if start < end {
-                config.main_class = Some(line[start..end].to_string());
-            }
-        } else if line.starts_with("test_class = \"") {
-            let start = "test_class = \"".len();
-            let end = line.len() - 1;
-            if start < end {
-                config.test_class = Some(line[start..end].to_string());
-            }
-        } else if line.starts_with("java_version = \"") {
-            let start = "java_version = \"".len();
-            let end = line.len() - 1;
-            if start < end {
-                config.java_version = Some(line[start..end].to_string());
-            }
-        }
-    }
-    
-    Ok(config)
+        offline: false,
+        modules: Vec::new(),
+        profile: Profile::default(),
+    })
 }
 
 fn get_generic_test_config(project_dir: &Path) -> Result<TestConfig> {
@@ -218,6 +247,9 @@ fn get_generic_test_config(project_dir: &Path) -> Result<TestConfig> {
         test_class: None,
         java_version: Some("11".to_string()),
         dependencies: Vec::new(),
+        offline: false,
+        modules: Vec::new(),
+        profile: Profile::default(),
     })
 }
 
@@ -247,11 +279,22 @@ fn display_test_info(config: &TestConfig, test_class: &Option<String>, method: &
     if let Some(ref m) = method {
         println!("指定测试方法: {}", m);
     }
+
+    if !config.modules.is_empty() {
+        let names: Vec<&str> = config.modules.iter().map(|module| module.name.as_str()).collect();
+        println!("指定模块: {}", names.join(", "));
+    }
+
+    println!("构建配置: {}", config.profile.name);
+
+    if config.offline {
+        println!("离线模式: 仅使用本地缓存");
+    }
 }
 
 fn run_maven_tests(
     project_dir: &Path, 
-    _config: &TestConfig, 
+    config: &TestConfig, 
     test_class: &Option<String>, 
     method: &Option<String>
 ) -> Result<()> {
@@ -261,11 +304,23 @@ fn run_maven_tests(
         return Err(anyhow::anyhow!("Maven未安装，请先安装Maven"));
     }
     
+    if let Some(warning) = config.profile.maven_warning() {
+        println!("{}", warning);
+    }
+
     // 先编译项目
     println!("编译项目...");
+    let module_args = modules::maven_arguments(&config.modules);
+    let profile_args = config.profile.maven_arguments();
+    let mut compile_args = vec!["compile", "test-compile"];
+    if config.offline {
+        compile_args.push("-o");
+    }
+    compile_args.extend(module_args.iter().map(String::as_str));
+    compile_args.extend(profile_args.iter().map(String::as_str));
+
     let compile_output = Command::new("mvn")
-        .arg("compile")
-        .arg("test-compile")
+        .args(&compile_args)
         .current_dir(project_dir)
         .output()
         .context("Maven编译失败")?;
@@ -286,6 +341,23 @@ fn run_maven_tests(
     if let Some(ref m) = method {
         mvn_args.push(format!("-Dmethods={}", m));
     }
+
+    if config.offline {
+        mvn_args.push("-o".to_string());
+    }
+
+    mvn_args.extend(profile_args);
+    if let Some(arg_line) = config.profile.maven_arg_line() {
+        mvn_args.push(arg_line);
+    }
+
+    if !config.modules.is_empty() {
+        mvn_args.extend(module_args);
+        // -am 同时构建的上游模块中没有指定的测试类
+        if test_class.is_some() {
+            mvn_args.push("-Dsurefire.failIfNoSpecifiedTests=false".to_string());
+        }
+    }
     
     println!("执行Maven测试命令: mvn {}", mvn_args.join(" "));
     
@@ -319,7 +391,7 @@ fn run_maven_tests(
 
 fn run_gradle_tests(
     project_dir: &Path, 
-    _config: &TestConfig, 
+    config: &TestConfig, 
     test_class: &Option<String>, 
     method: &Option<String>
 ) -> Result<()> {
@@ -331,9 +403,15 @@ fn run_gradle_tests(
     
     // 先编译项目
     println!("编译项目...");
+    let mut compile_args = modules::gradle_tasks(&config.modules, &["compileJava", "compileTestJava"]);
+    if config.offline {
+        compile_args.push("--offline".to_string());
+    }
+    let profile_args = config.profile.gradle_arguments()?;
+    compile_args.extend(profile_args.iter().cloned());
+
     let compile_output = Command::new("gradle")
-        .arg("compileJava")
-        .arg("compileTestJava")
+        .args(&compile_args)
         .current_dir(project_dir)
         .output()
         .context("Gradle编译失败")?;
@@ -344,7 +422,8 @@ fn run_gradle_tests(
     }
     
     // 构建测试命令
-    let mut gradle_args = vec!["test"];
+    let test_tasks = modules::gradle_tasks(&config.modules, &["test"]);
+    let mut gradle_args: Vec<&str> = test_tasks.iter().map(String::as_str).collect();
     let mut test_method = None;
     
     if let Some(ref m) = method {
@@ -362,6 +441,11 @@ fn run_gradle_tests(
         gradle_args.push("--tests");
         gradle_args.push(method_name);
     }
+
+    if config.offline {
+        gradle_args.push("--offline");
+    }
+    gradle_args.extend(profile_args.iter().map(String::as_str));
     
     println!("执行Gradle测试命令: gradle {}", gradle_args.join(" "));
     
@@ -392,17 +476,15 @@ fn run_jx_tests(
     method: &Option<String>
 ) -> Result<()> {
     println!("\n🔨 使用jx运行测试...");
+
+    if config.offline {
+        ensure_offline_ready(project_dir)?;
+    }
     
-    // 检查jx.toml中的项目类型
-    let jx_path = project_dir.join("jx.toml");
-    let jx_content = fs::read_to_string(&jx_path)?;
-    
-    if jx_content.contains("type = \"maven\"") {
-        run_maven_tests(project_dir, config, test_class, method)
-    } else if jx_content.contains("type = \"gradle\"") {
-        run_gradle_tests(project_dir, config, test_class, method)
-    } else {
-        run_generic_tests(project_dir, config, test_class, method)
+    match JxConfig::load_from_dir(project_dir)?.project.project_type {
+        ProjectType::Maven => run_maven_tests(project_dir, config, test_class, method),
+        ProjectType::Gradle => run_gradle_tests(project_dir, config, test_class, method),
+        ProjectType::Jx => run_generic_tests(project_dir, config, test_class, method),
     }
 }
 
@@ -436,7 +518,7 @@ fn run_generic_tests(
     
     // 运行测试
     for test_class in test_classes {
-        run_single_test_class(&test_class, method)?;
+        run_single_test_class(&test_class, method, &config.profile.jvm_opts)?;
     }
     
     Ok(())
@@ -468,6 +550,8 @@ fn compile_test_classes(project_dir: &Path, config: &TestConfig) -> Result<()> {
         javac_args.push(classpath);
     }
     
+    javac_args.extend(config.profile.javac_flags.iter().cloned());
+
     // 添加测试源码目录
     javac_args.push("-sourcepath".to_string());
     javac_args.push(config.test_source_dir.to_str().unwrap().to_string());
@@ -536,7 +620,7 @@ fn find_java_files(source_dir: &Path) -> Result<Vec<PathBuf>> {
     Ok(java_files)
 }
 
-fn run_single_test_class(test_class_path: &Path, method: &Option<String>) -> Result<()> {
+fn run_single_test_class(test_class_path: &Path, method: &Option<String>, jvm_opts: &[String]) -> Result<()> {
     let class_name = test_class_path
         .strip_prefix(test_class_path.parent().unwrap())
         .unwrap()
@@ -552,7 +636,8 @@ fn run_single_test_class(test_class_path: &Path, method: &Option<String>) -> Res
         return Err(anyhow::anyhow!("Java运行时未安装"));
     }
     
-    let mut java_args = vec!["-cp", ".", &class_name];
+    let mut java_args: Vec<&str> = jvm_opts.iter().map(String::as_str).collect();
+    java_args.extend(["-cp", ".", &class_name]);
     
     if let Some(ref m) = method {
         java_args.push(m);
@@ -1,9 +1,14 @@
+use crate::config::JxConfig;
+use crate::gradle::{self, GradleScript};
+use crate::lock::{DependencyTreeNode, LockFile};
+use crate::modules;
+use crate::pom::load_local_pom;
+use crate::workspace::{self, Workspace};
 use anyhow::Result;
 use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-pub fn execute(transitive: bool) -> Result<()> {
+pub fn execute(transitive: bool, offline: bool, packages: Vec<String>) -> Result<()> {
     println!("🌳 依赖树...");
     
     let current_dir = std::env::current_dir()?;
@@ -15,20 +20,79 @@ pub fn execute(transitive: bool) -> Result<()> {
     if transitive {
         println!("显示传递依赖");
     }
-    
-    // 构建依赖树
-    let dependency_tree = build_dependency_tree(&current_dir, transitive)?;
-    
-    if dependency_tree.is_empty() {
-        println!("❌ 未找到依赖信息");
-        println!("💡 提示:");
-        println!("  - 确保项目已正确配置");
-        println!("  - 运行 'jx install' 安装依赖");
-        println!("  - 检查 pom.xml 或 build.gradle 文件");
+
+    // 多模块项目按模块分别显示
+    let sections = module_sections(&current_dir, &packages)?;
+    if sections.is_empty() {
+        let dependency_tree = build_tree(&current_dir, transitive, offline)?;
+        if dependency_tree.is_empty() {
+            println!("❌ 未找到依赖信息");
+            println!("💡 提示:");
+            println!("  - 确保项目已正确配置");
+            println!("  - 运行 'jx install' 安装依赖");
+            println!("  - 检查 pom.xml 或 build.gradle 文件");
+            return Ok(());
+        }
+        print_tree(&dependency_tree, transitive);
         return Ok(());
     }
+
+    // 聚合工程的根项目自己声明了依赖时也显示
+    if packages.is_empty() && !current_dir.join("jx.toml").exists() {
+        let dependency_tree = build_tree(&current_dir, transitive, offline)?;
+        if !dependency_tree.is_empty() {
+            println!("\n📦 根项目");
+            print_tree(&dependency_tree, transitive);
+        }
+    }
+
+    for (label, dir) in sections {
+        println!("\n📦 模块 {}", label);
+        let dependency_tree = build_tree(&dir, transitive, offline)?;
+        if dependency_tree.is_empty() {
+            println!("没有声明依赖");
+            continue;
+        }
+        print_tree(&dependency_tree, transitive);
+    }
     
-    // 显示依赖树
+    Ok(())
+}
+
+/// 需要分别显示的模块: jx工作区的成员，或Maven/Gradle的子模块；单个项目时为空
+fn module_sections(project_dir: &Path, packages: &[String]) -> Result<Vec<(String, PathBuf)>> {
+    if let Some(workspace) = Workspace::discover(project_dir)? {
+        if workspace.root != project_dir && packages.is_empty() {
+            return Ok(Vec::new());
+        }
+        return Ok(workspace
+            .select(packages, project_dir)?
+            .into_iter()
+            .map(|member| (format!("{} ({})", member.name(), member.path), member.dir.clone()))
+            .collect());
+    }
+
+    let modules = if packages.is_empty() {
+        modules::detect(project_dir)?
+    } else {
+        modules::select(project_dir, packages)?
+    };
+    Ok(modules
+        .into_iter()
+        .map(|module| (format!("{} ({})", module.name, module.path), module.dir))
+        .collect())
+}
+
+fn build_tree(project_dir: &Path, transitive: bool, offline: bool) -> Result<Vec<DependencyNode>> {
+    let use_lock = offline || workspace::lock_path(project_dir)?.exists();
+    if transitive && use_lock {
+        build_lock_dependency_tree(project_dir)
+    } else {
+        build_dependency_tree(project_dir, transitive)
+    }
+}
+
+fn print_tree(dependency_tree: &[DependencyNode], transitive: bool) {
     println!("\n📋 依赖树结构:");
     println!("{}", "─".repeat(50));
     
@@ -40,7 +104,7 @@ pub fn execute(transitive: bool) -> Result<()> {
     }
     
     // 统计信息
-    let total_deps = count_total_dependencies(&dependency_tree);
+    let total_deps = count_total_dependencies(dependency_tree);
     let direct_deps = dependency_tree.len();
     let transitive_deps = total_deps - direct_deps;
     
@@ -51,8 +115,6 @@ pub fn execute(transitive: bool) -> Result<()> {
         println!("  传递依赖: {}", transitive_deps);
     }
     println!("  总依赖数: {}", total_deps);
-    
-    Ok(())
 }
 
 #[derive(Debug)]
@@ -67,14 +129,13 @@ struct DependencyNode {
 
 fn detect_project_type(project_dir: &Path) -> Result<String> {
     let has_pom = project_dir.join("pom.xml").exists();
-    let has_gradle = project_dir.join("build.gradle").exists();
-    let has_settings_gradle = project_dir.join("settings.gradle").exists();
+    let has_gradle = gradle::is_gradle_project(project_dir);
     
-    if has_pom && (has_gradle || has_settings_gradle) {
+    if has_pom && has_gradle {
         Ok("Maven + Gradle".to_string())
     } else if has_pom {
         Ok("Maven".to_string())
-    } else if has_gradle || has_settings_gradle {
+    } else if has_gradle {
         Ok("Gradle".to_string())
     } else {
         Ok("未知".to_string())
@@ -108,6 +169,52 @@ fn build_dependency_tree(project_dir: &Path, transitive: bool) -> Result<Vec<Dep
     Ok(root_dependencies)
 }
 
+/// 传递依赖来自jx.lock，离线模式下没有锁文件时只显示直接依赖
+fn build_lock_dependency_tree(project_dir: &Path) -> Result<Vec<DependencyNode>> {
+    let lock_path = workspace::lock_path(project_dir)?;
+    if !lock_path.exists() {
+        println!("⚠️ 离线模式下没有jx.lock，只显示直接依赖");
+        return build_dependency_tree(project_dir, false);
+    }
+
+    println!("依赖树来自 jx.lock");
+    let lock_file = LockFile::load(&lock_path)?;
+
+    // 工作区共用的锁文件包含所有成员的依赖，成员目录中只显示该成员的直接依赖
+    let member_roots: Option<Vec<String>> = Workspace::discover(project_dir)?.and_then(|ws| {
+        ws.member_at(project_dir).map(|member| {
+            member
+                .config
+                .dependencies()
+                .iter()
+                .map(|dep| format!("{}:{}", dep.group_id, dep.artifact_id))
+                .collect()
+        })
+    });
+
+    Ok(lock_file
+        .get_dependency_tree()
+        .iter()
+        .filter(|node| {
+            member_roots.as_ref().is_none_or(|roots| {
+                roots.contains(&format!("{}:{}", node.dependency.group_id, node.dependency.artifact_id))
+            })
+        })
+        .map(convert_lock_node)
+        .collect())
+}
+
+fn convert_lock_node(node: &DependencyTreeNode) -> DependencyNode {
+    DependencyNode {
+        group_id: node.dependency.group_id.clone(),
+        artifact_id: node.dependency.artifact_id.clone(),
+        version: node.dependency.version.clone(),
+        scope: node.dependency.scope.clone(),
+        children: node.children.iter().map(convert_lock_node).collect(),
+        depth: node.depth,
+    }
+}
+
 #[derive(Debug)]
 struct ConfigDependency {
     group_id: String,
@@ -120,167 +227,68 @@ fn read_dependencies_from_config(project_dir: &Path) -> Result<Vec<ConfigDepende
     let mut dependencies = Vec::new();
     
     // 读取pom.xml
-    let pom_path = project_dir.join("pom.xml");
-    if pom_path.exists() {
-        let pom_content = fs::read_to_string(&pom_path)?;
-        let pom_deps = parse_maven_dependencies(&pom_content)?;
-        dependencies.extend(pom_deps);
-    }
-    
-    // 读取build.gradle
-    let gradle_path = project_dir.join("build.gradle");
-    if gradle_path.exists() {
-        let gradle_content = fs::read_to_string(&gradle_path)?;
-        let gradle_deps = parse_gradle_dependencies(&gradle_content)?;
+    if project_dir.join("pom.xml").exists() {
+        dependencies.extend(read_maven_dependencies(project_dir)?);
+    }
+    
+    // 读取build.gradle或build.gradle.kts
+    if gradle::build_script(project_dir).is_some() {
+        let gradle_deps = read_gradle_dependencies(project_dir)?;
         dependencies.extend(gradle_deps);
     }
     
     // 读取jx.toml
-    let jx_path = project_dir.join("jx.toml");
-    if jx_path.exists() {
-        let jx_content = fs::read_to_string(&jx_path)?;
-        let jx_deps = parse_jx_dependencies(&jx_content)?;
+    if project_dir.join("jx.toml").exists() {
+        let jx_deps = read_jx_dependencies(project_dir)?;
         dependencies.extend(jx_deps);
     }
     
     Ok(dependencies)
 }
 
-fn parse_maven_dependencies(pom_content: &str) -> Result<Vec<ConfigDependency>> {
-    let mut dependencies = Vec::new();
-    let lines: Vec<&str> = pom_content.lines().collect();
-    
-    let mut in_dependencies = false;
-    let mut current_dep: Option<HashMap<String, String>> = None;
-    
-    for line in lines {
-        let line = line.trim();
-        
-        if line == "<dependencies>" {
-            in_dependencies = true;
-        } else if line == "</dependencies>" {
-            in_dependencies = false;
-            break;
-        } else if in_dependencies {
-            if line == "<dependency>" {
-                current_dep = Some(HashMap::new());
-            } else if line == "</dependency>" {
-                if let Some(dep) = current_dep.take() {
-                    if let (Some(group_id), Some(artifact_id), Some(version)) = (
-                        dep.get("groupId"), dep.get("artifactId"), dep.get("version")
-                    ) {
-                        let scope = dep.get("scope").unwrap_or(&"compile".to_string()).clone();
-                        dependencies.push(ConfigDependency {
-                            group_id: group_id.clone(),
-                            artifact_id: artifact_id.clone(),
-                            version: version.clone(),
-                            scope,
-                        });
-                    }
-                }
-            } else if line.starts_with("<") && line.ends_with(">") && !line.starts_with("</") {
-                if let Some(dep) = &mut current_dep {
-                    let content = line.trim_start_matches('<').trim_end_matches('>');
-                    if let Some(colon_pos) = content.find('>') {
-                        let tag_name = &content[..colon_pos];
-                        let value = &content[colon_pos + 1..];
-                        
-                        if !tag_name.is_empty() && !value.is_empty() {
-                            dep.insert(tag_name.to_string(), value.to_string());
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    Ok(dependencies)
+fn read_maven_dependencies(project_dir: &Path) -> Result<Vec<ConfigDependency>> {
+    let pom = load_local_pom(project_dir)?;
+
+    Ok(pom
+        .effective_dependencies()
+        .into_iter()
+        .map(|dep| ConfigDependency {
+            group_id: dep.group_id,
+            artifact_id: dep.artifact_id,
+            version: dep.version.unwrap_or_else(|| "*".to_string()),
+            scope: dep.scope.unwrap_or_else(|| "compile".to_string()),
+        })
+        .collect())
 }
 
-fn parse_gradle_dependencies(gradle_content: &str) -> Result<Vec<ConfigDependency>> {
-    let mut dependencies = Vec::new();
-    let lines: Vec<&str> = gradle_content.lines().collect();
-    
-    let mut in_dependencies = false;
-    
-    for line in lines {
-        let line = line.trim();
-        
-        if line == "dependencies {" {
-            in_dependencies = true;
-        } else if line == "}" && in_dependencies {
-            in_dependencies = false;
-            break;
-        } else if in_dependencies && line.contains("'") {
-            // 解析Gradle依赖行，格式通常是: implementation 'groupId:artifactId:version'
-            let parts: Vec<&str> = line.split('\'').collect();
-            if parts.len() >= 2 {
-                let dep_coord = parts[1];
-                let coord_parts: Vec<&str> = dep_coord.split(':').collect();
-                
-                if coord_parts.len() >= 2 {
-                    let group_id = coord_parts[0];
-                    let artifact_id = coord_parts[1];
-                    let version = coord_parts.get(2).unwrap_or(&"*");
-                    
-                    // 从行内容推断scope
-                    let scope = if line.contains("implementation") { "implementation" }
-                               else if line.contains("compileOnly") { "compileOnly" }
-                               else if line.contains("runtimeOnly") { "runtimeOnly" }
-                               else if line.contains("testImplementation") { "testImplementation" }
-                               else { "implementation" };
-                    
-                    dependencies.push(ConfigDependency {
-                        group_id: group_id.to_string(),
-                        artifact_id: artifact_id.to_string(),
-                        version: version.to_string(),
-                        scope: scope.to_string(),
-                    });
-                }
-            }
-        }
-    }
-    
-    Ok(dependencies)
+fn read_gradle_dependencies(project_dir: &Path) -> Result<Vec<ConfigDependency>> {
+    let script = GradleScript::open(project_dir)?;
+
+    Ok(script
+        .dependencies()
+        .into_iter()
+        .map(|dep| ConfigDependency {
+            group_id: dep.group_id,
+            artifact_id: dep.artifact_id,
+            version: dep.version.unwrap_or_else(|| "*".to_string()),
+            scope: dep.configuration,
+        })
+        .collect())
 }
 
-fn parse_jx_dependencies(jx_content: &str) -> Result<Vec<ConfigDependency>> {
-    let mut dependencies = Vec::new();
-    let lines: Vec<&str> = jx_content.lines().collect();
-    
-    let mut in_dependencies = false;
-    
-    for line in lines {
-        let line = line.trim();
-        
-        if line == "[dependencies]" {
-            in_dependencies = true;
-        } else if line.starts_with('[') && line != "[dependencies]" {
-            in_dependencies = false;
-        } else if in_dependencies && line.contains('=') {
-            // 解析jx.toml依赖行，格式通常是: groupId:artifactId = "version"
-            let parts: Vec<&str> = line.split('=').collect();
-            if parts.len() == 2 {
-                let dep_coord = parts[0].trim();
-                let version = parts[1].trim().trim_matches('"');
-                
-                let coord_parts: Vec<&str> = dep_coord.split(':').collect();
-                if coord_parts.len() == 2 {
-                    let group_id = coord_parts[0];
-                    let artifact_id = coord_parts[1];
-                    
-                    dependencies.push(ConfigDependency {
-                        group_id: group_id.to_string(),
-                        artifact_id: artifact_id.to_string(),
-                        version: version.to_string(),
-                        scope: "compile".to_string(),
-                    });
-                }
-            }
-        }
-    }
-    
-    Ok(dependencies)
+fn read_jx_dependencies(project_dir: &Path) -> Result<Vec<ConfigDependency>> {
+    let config = JxConfig::load_from_dir(project_dir)?;
+
+    Ok(config
+        .dependencies()
+        .iter()
+        .map(|dep| ConfigDependency {
+            group_id: dep.group_id.clone(),
+            artifact_id: dep.artifact_id.clone(),
+            version: dep.version.clone(),
+            scope: dep.scope.as_str().to_string(),
+        })
+        .collect())
 }
 
 fn add_transitive_dependencies(node: &mut DependencyNode, _all_deps: &[ConfigDependency]) {
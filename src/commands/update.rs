@@ -1,21 +1,35 @@
+use crate::config::JxConfig;
+use crate::gradle::{self, GradleScript};
+use crate::manifest::ManifestEditor;
+use crate::pom_editor::PomEditor;
+use crate::registry::MavenRegistry;
+use crate::workspace;
 use anyhow::{Context, Result};
-use std::fs;
 use std::path::Path;
 use std::process::Command;
 
-pub fn execute(dependency: Option<String>, latest: bool) -> Result<()> {
-    let current_dir = std::env::current_dir()?;
+pub async fn execute(
+    dependency: Option<String>,
+    latest: bool,
+    offline: bool,
+    managed: bool,
+    package: Option<String>,
+) -> Result<()> {
+    let current_dir = workspace::project_dir(package.as_deref())?;
     
     // 查找项目配置文件
     let config_file = if current_dir.join("jx.toml").exists() {
         "jx.toml"
     } else if current_dir.join("pom.xml").exists() {
         "pom.xml"
-    } else if current_dir.join("build.gradle").exists() {
-        "build.gradle"
+    } else if let Some(script) = gradle::build_script(&current_dir) {
+        script
     } else {
         return Err(anyhow::anyhow!("找不到项目配置文件，请先运行 'jx init'"));
     };
+    if managed && config_file != "pom.xml" {
+        return Err(anyhow::anyhow!("--managed 仅适用于pom.xml"));
+    }
 
     println!("🔄 更新依赖...");
     
@@ -31,9 +45,11 @@ pub fn execute(dependency: Option<String>, latest: bool) -> Result<()> {
 
     // 根据配置文件类型更新依赖
     let result = match config_file {
-        "jx.toml" => update_jx_config(&current_dir, &dependency, latest),
-        "pom.xml" => update_maven(&current_dir, &dependency, latest),
-        "build.gradle" => update_gradle(&current_dir, &dependency, latest),
+        "jx.toml" => update_jx_config(&current_dir, &dependency, latest, offline).await,
+        "pom.xml" => update_maven(&current_dir, &dependency, latest, offline, managed).await,
+        "build.gradle" | "build.gradle.kts" => {
+            update_gradle(&current_dir, &dependency, latest, offline).await
+        }
         _ => Err(anyhow::anyhow!("不支持的配置文件类型")),
     };
 
@@ -50,54 +66,133 @@ pub fn execute(dependency: Option<String>, latest: bool) -> Result<()> {
     }
 }
 
-fn update_jx_config(project_dir: &Path, dependency: &Option<String>, latest: bool) -> Result<()> {
+async fn update_jx_config(
+    project_dir: &Path,
+    dependency: &Option<String>,
+    latest: bool,
+    offline: bool,
+) -> Result<()> {
     let config_path = project_dir.join("jx.toml");
     
     if !config_path.exists() {
         return Err(anyhow::anyhow!("找不到jx.toml配置文件"));
     }
-    
-    if latest {
-        // 更新所有依赖到最新版本
-        let config_content = fs::read_to_string(&config_path)?;
-        let mut lines: Vec<String> = config_content.lines().map(|s| s.to_string()).collect();
-        
-        for i in 0..lines.len() {
-            let line = lines[i].trim();
-            if line.contains(" = \"") && !line.contains(" = \"*\"") {
-                // 将版本号改为 *
-                let new_line = line.replace(" = \"", " = \"*\"");
-                lines[i] = new_line;
+
+    let config = JxConfig::load(&config_path)?;
+
+    // 要更新的依赖和目标版本，没有指定版本时查询最新发布版本
+    let targets: Vec<(DependencyInfo, Option<String>)> = match dependency {
+        Some(dep) => {
+            let mut dep_info = parse_dependency_coordinate(dep)?;
+            let declared = config.dependencies().iter().any(|declared| {
+                declared.group_id == dep_info.group_id && declared.artifact_id == dep_info.artifact_id
+            });
+            if !declared {
+                return Err(anyhow::anyhow!(
+                    "jx.toml中没有声明依赖 {}:{}，请使用 'jx add' 添加",
+                    dep_info.group_id,
+                    dep_info.artifact_id
+                ));
             }
+            let version = dep_info.version.take();
+            vec![(dep_info, version)]
         }
-        
-        fs::write(&config_path, lines.join("\n"))?;
-        println!("已更新jx.toml中的所有依赖到最新版本");
-    } else if let Some(dep) = dependency {
-        // 更新特定依赖
-        let dep_info = parse_dependency_coordinate(dep)?;
-        let config_content = fs::read_to_string(&config_path)?;
-        let mut lines: Vec<String> = config_content.lines().map(|s| s.to_string()).collect();
-        
-        for i in 0..lines.len() {
-            let line = lines[i].trim();
-            if line.starts_with(&format!("{}:{}", dep_info.group_id, dep_info.artifact_id)) {
-                // 将版本号改为 *
-                let new_line = line.replace(" = \"", " = \"*\"");
-                lines[i] = new_line;
-                println!("已更新依赖 {} 到最新版本", dep);
-                break;
+        None if latest => config
+            .dependencies()
+            .iter()
+            .map(|dep| {
+                let dep_info = DependencyInfo {
+                    group_id: dep.group_id.clone(),
+                    artifact_id: dep.artifact_id.clone(),
+                    version: None,
+                };
+                (dep_info, None)
+            })
+            .collect(),
+        None => {
+            return Err(anyhow::anyhow!(
+                "请指定要更新的依赖，或使用 --latest 更新所有依赖"
+            ))
+        }
+    };
+
+    let single = targets.len() == 1;
+    let mut editor = ManifestEditor::open(&config_path)?;
+    let mut registry = MavenRegistry::new();
+    let mut updated = 0;
+
+    for (dep_info, version) in targets {
+        let key = format!("{}:{}", dep_info.group_id, dep_info.artifact_id);
+        let version = match target_version(&mut registry, &dep_info, version, offline).await {
+            Ok(version) => version,
+            Err(e) if single => return Err(e),
+            Err(e) => {
+                println!("⚠️ 无法获取 {} 的最新版本: {}", key, e);
+                continue;
             }
+        };
+
+        match editor.set_version(&key, &version) {
+            Some(old_version) if old_version != version => {
+                println!("⬆️  {} {} -> {}", key, old_version, version);
+                updated += 1;
+            }
+            _ => println!("  {} 已是 {}", key, version),
         }
-        
-        fs::write(&config_path, lines.join("\n"))?;
     }
-    
+
+    editor.save()?;
+    if updated == 0 {
+        println!("jx.toml中的依赖已是最新版本");
+    } else {
+        println!("已更新jx.toml中的 {} 个依赖", updated);
+    }
     Ok(())
 }
 
-fn update_maven(project_dir: &Path, dependency: &Option<String>, latest: bool) -> Result<()> {
-    if latest {
+async fn update_maven(
+    project_dir: &Path,
+    dependency: &Option<String>,
+    latest: bool,
+    offline: bool,
+    managed: bool,
+) -> Result<()> {
+    if let Some(dep) = dependency {
+        // 更新特定依赖，${property}形式的版本更新对应的属性
+        let mut dep_info = parse_dependency_coordinate(dep)?;
+        let pom_path = project_dir.join("pom.xml");
+        let mut editor = PomEditor::open(&pom_path)?;
+        if !editor.has_dependency(&dep_info.group_id, &dep_info.artifact_id, managed) {
+            return Err(anyhow::anyhow!(
+                "pom.xml中没有声明依赖 {}:{}，请使用 'jx add' 添加",
+                dep_info.group_id,
+                dep_info.artifact_id
+            ));
+        }
+
+        let version = dep_info.version.take();
+        let version = target_version(&mut MavenRegistry::new(), &dep_info, version, offline).await?;
+        let change = editor.set_version(&dep_info.group_id, &dep_info.artifact_id, &version, managed)?;
+        editor.save()?;
+
+        let location = if change.managed { " (<dependencyManagement>)" } else { "" };
+        match &change.property {
+            Some(property) => {
+                println!(
+                    "⬆️  属性 {}: {} -> {}{}",
+                    property, change.old_version, version, location
+                );
+                let usages = editor.property_usages(property);
+                if usages > 1 {
+                    println!("注意: 属性 ${{{}}} 在pom.xml中被引用了 {} 次", property, usages);
+                }
+            }
+            None => println!(
+                "⬆️  {}:{} {} -> {}{}",
+                dep_info.group_id, dep_info.artifact_id, change.old_version, version, location
+            ),
+        }
+    } else if latest {
         // 使用Maven命令更新所有依赖
         println!("使用Maven更新所有依赖...");
         
@@ -117,58 +212,78 @@ fn update_maven(project_dir: &Path, dependency: &Option<String>, latest: bool) -
         }
         
         println!("Maven依赖更新完成");
-    } else if let Some(dep) = dependency {
-        // 更新特定依赖
-        let dep_info = parse_dependency_coordinate(dep)?;
-        let pom_path = project_dir.join("pom.xml");
-        let pom_content = fs::read_to_string(&pom_path)?;
-        let mut lines: Vec<String> = pom_content.lines().map(|s| s.to_string()).collect();
-        
-        // 查找并更新版本号
-        let mut i = 0;
-        while i < lines.len() {
-            let line = lines[i].trim();
-            if line == "<dependency>" {
-                let mut in_dependency = false;
-                let mut dependency_start = i;
-                
-                for j in i..lines.len() {
-                    let dep_line = lines[j].trim();
-                    if dep_line == "<dependency>" {
-                        in_dependency = true;
-                        dependency_start = j;
-                    } else if in_dependency && dep_line == "</dependency>" {
-                        // 检查这个依赖是否匹配
-                        let dependency_lines = &lines[dependency_start..=j];
-                        if dependency_lines.iter().any(|l| l.contains(&format!("<groupId>{}</groupId>", dep_info.group_id))) &&
-                           dependency_lines.iter().any(|l| l.contains(&format!("<artifactId>{}</artifactId>", dep_info.artifact_id))) {
-                            // 将版本号改为 *
-                            for k in dependency_start..=j {
-                                if lines[k].trim().starts_with("<version>") {
-                                    lines[k] = "            <version>*</version>".to_string();
-                                    println!("已更新依赖 {} 到最新版本", dep);
-                                    break;
-                                }
-                            }
-                            break;
-                        }
-                        in_dependency = false;
-                    }
-                }
-                break;
-            }
-            i += 1;
-        }
-        
-        fs::write(&pom_path, lines.join("\n"))?;
     }
     
     Ok(())
 }
 
-fn update_gradle(project_dir: &Path, dependency: &Option<String>, latest: bool) -> Result<()> {
-    if latest {
-        // 使用Gradle命令更新所有依赖
+/// 指定了版本时直接使用，否则查询Maven Central上的最新发布版本
+async fn target_version(
+    registry: &mut MavenRegistry,
+    dep_info: &DependencyInfo,
+    version: Option<String>,
+    offline: bool,
+) -> Result<String> {
+    match version {
+        Some(version) => Ok(version),
+        None if offline => Err(anyhow::anyhow!(
+            "离线模式下无法查询最新版本，请指定版本: {}:{}:<version>",
+            dep_info.group_id,
+            dep_info.artifact_id
+        )),
+        None => registry
+            .latest_release(&dep_info.group_id, &dep_info.artifact_id)
+            .await,
+    }
+}
+
+async fn update_gradle(
+    project_dir: &Path,
+    dependency: &Option<String>,
+    latest: bool,
+    offline: bool,
+) -> Result<()> {
+    if let Some(dep) = dependency {
+        // 更新特定依赖
+        let mut dep_info = parse_dependency_coordinate(dep)?;
+        let mut script = GradleScript::open(project_dir)?;
+        if !script.has_dependency(&dep_info.group_id, &dep_info.artifact_id) {
+            return Err(anyhow::anyhow!(
+                "{}中没有声明依赖 {}:{}，请使用 'jx add' 添加",
+                script.file_name(),
+                dep_info.group_id,
+                dep_info.artifact_id
+            ));
+        }
+
+        let version = dep_info.version.take();
+        let version = target_version(&mut MavenRegistry::new(), &dep_info, version, offline).await?;
+        let change = script.set_version(&dep_info.group_id, &dep_info.artifact_id, &version)?;
+        script.save()?;
+
+        let old_version = change.old_version.as_deref().unwrap_or("未指定");
+        match change.catalog.as_ref().and_then(|c| c.version_ref.as_deref()) {
+            Some(reference) => {
+                println!(
+                    "⬆️  版本目录 [versions] {}: {} -> {}",
+                    reference, old_version, version
+                );
+                let usages = script.catalog().map_or(0, |c| c.version_ref_usages(reference));
+                if usages > 1 {
+                    println!("注意: 版本 {} 被版本目录中的 {} 个库引用", reference, usages);
+                }
+            }
+            None => println!(
+                "⬆️  {}:{} {} -> {}{}",
+                dep_info.group_id,
+                dep_info.artifact_id,
+                old_version,
+                version,
+                if change.catalog.is_some() { " (gradle/libs.versions.toml)" } else { "" }
+            ),
+        }
+    } else if latest {
+        // 使用Gradle命令检查所有依赖的更新
         println!("使用Gradle更新所有依赖...");
         
         if !check_command_exists("gradle") {
@@ -187,26 +302,6 @@ fn update_gradle(project_dir: &Path, dependency: &Option<String>, latest: bool)
         }
         
         println!("Gradle依赖更新完成");
-    } else if let Some(dep) = dependency {
-        // 更新特定依赖
-        let dep_info = parse_dependency_coordinate(dep)?;
-        let build_gradle_path = project_dir.join("build.gradle");
-        let build_content = fs::read_to_string(&build_gradle_path)?;
-        let mut lines: Vec<String> = build_content.lines().map(|s| s.to_string()).collect();
-        
-        // 查找并更新版本号
-        for i in 0..lines.len() {
-            let line = lines[i].trim();
-            if line.contains(&format!("'{}:{}", dep_info.group_id, dep_info.artifact_id)) {
-                // 将版本号改为 +
-                let new_line = line.replace("'", "'").replace(":", ":+");
-                lines[i] = new_line;
-                println!("已更新依赖 {} 到最新版本", dep);
-                break;
-            }
-        }
-        
-        fs::write(&build_gradle_path, lines.join("\n"))?;
     }
     
     Ok(())
@@ -216,6 +311,7 @@ fn update_gradle(project_dir: &Path, dependency: &Option<String>, latest: bool)
 struct DependencyInfo {
     group_id: String,
     artifact_id: String,
+    version: Option<String>,
 }
 
 fn parse_dependency_coordinate(coordinate: &str) -> Result<DependencyInfo> {
@@ -225,8 +321,14 @@ fn parse_dependency_coordinate(coordinate: &str) -> Result<DependencyInfo> {
         2 => Ok(DependencyInfo {
             group_id: parts[0].to_string(),
             artifact_id: parts[1].to_string(),
+            version: None,
         }),
-        _ => Err(anyhow::anyhow!("无效的依赖坐标格式，应为 groupId:artifactId")),
+        3 => Ok(DependencyInfo {
+            group_id: parts[0].to_string(),
+            artifact_id: parts[1].to_string(),
+            version: Some(parts[2].to_string()),
+        }),
+        _ => Err(anyhow::anyhow!("无效的依赖坐标格式，应为 groupId:artifactId 或 groupId:artifactId:version")),
     }
 }
 
@@ -237,3 +339,4 @@ fn check_command_exists(command: &str) -> bool {
         .map(|output| output.status.success())
         .unwrap_or(false)
 }
+
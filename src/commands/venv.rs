@@ -2,7 +2,6 @@ use crate::utils::{calculate_directory_size, format_file_size};
 use anyhow::{Context, Result};
 use futures_util::StreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
-use reqwest;
 use serde::Deserialize;
 use std::env;
 use std::fs;
@@ -525,6 +524,25 @@ fn get_venv_directory(name: &str) -> Result<PathBuf> {
     Ok(venv_base.join(name))
 }
 
+/// 当前虚拟环境中JDK的JAVA_HOME: 优先使用激活脚本设置的`JX_VENV_PATH`，其次是`jx venv activate`记录的环境
+pub fn active_java_home() -> Result<Option<PathBuf>> {
+    let venv_dir = match env::var_os("JX_VENV_PATH") {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => match get_active_venv()? {
+            Some(name) => get_venv_directory(&name)?,
+            None => return Ok(None),
+        },
+    };
+
+    let jdk = venv_dir.join("lib").join("java").join("jdk");
+    let java_home = if jdk.join("Contents").join("Home").exists() {
+        jdk.join("Contents").join("Home")
+    } else {
+        jdk
+    };
+    Ok(java_home.exists().then_some(java_home))
+}
+
 fn get_active_venv() -> Result<Option<String>> {
     let activation_file = get_jx_home()?.join(".active_venv");
     if activation_file.exists() {
@@ -821,7 +839,7 @@ async fn download_file(url: &str, path: &Path) -> Result<()> {
     println!("下载: {}", url);
 
     // 创建HTTP客户端
-    let client = reqwest::Client::new();
+    let client = crate::settings::http_client()?;
 
     // 发送GET请求
     let response = client.get(url).send().await.context("发送HTTP请求失败")?;
@@ -0,0 +1,192 @@
+use crate::download::Downloader;
+use crate::install::{is_production, lib_dependencies, Installer};
+use crate::lock::{LockFile, LockedDependency};
+use crate::utils::sha256_file;
+use crate::workspace;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// `production`与 `jx install --production` 对应，test和provided依赖不应出现在lib/中
+pub async fn execute(fix: bool, production: bool, offline: bool) -> Result<()> {
+    println!("🔍 校验依赖...");
+
+    let current_dir = std::env::current_dir()?;
+    let lock_path = workspace::lock_path(&current_dir)?;
+    if !lock_path.exists() {
+        return Err(anyhow::anyhow!("找不到jx.lock，请先运行 'jx install'"));
+    }
+
+    let lock_file = LockFile::load(&lock_path)?;
+    let installer = Installer::new();
+    let downloader = Downloader::new().offline(offline);
+    let lib_dir = current_dir.join("lib");
+
+    // 注解处理器只在缓存中，不安装到lib/
+    let lib_dependencies: Vec<LockedDependency> = lib_dependencies(&current_dir, &lock_file)?
+        .into_iter()
+        .filter(|dep| installed_in_lib(dep) && (!production || is_production(dep)))
+        .collect();
+    let expected: HashMap<String, &LockedDependency> = lib_dependencies
+        .iter()
+        .map(|dep| (dep.filename(), dep))
+        .collect();
+    let installed = installer.get_installed_dependencies()?;
+
+    let mut report = VerifyReport::default();
+
+    for filename in &installed {
+        match expected.get(filename) {
+            None => report.extra.push(filename.clone()),
+            Some(dep) => {
+                if !checksum_matches(dep, &lib_dir.join(filename))? {
+                    report.tampered_lib.push((*dep).clone());
+                }
+            }
+        }
+    }
+
+    for (filename, dep) in &expected {
+        if !installed.contains(filename) {
+            report.missing_lib.push((*dep).clone());
+        }
+    }
+
+    for dep in lock_file.dependencies.values() {
+        let cache_path = downloader.cached_path(&dep.to_dependency());
+        if !cache_path.exists() {
+            report.missing_cache.push((*dep).clone());
+        } else if !checksum_matches(dep, &cache_path)? {
+            report.tampered_cache.push((*dep).clone());
+        }
+    }
+
+    report.sort();
+    report.print(lock_file.dependencies.len());
+
+    if report.is_clean() {
+        println!("✅ lib/ 和本地缓存与 jx.lock 一致");
+        return Ok(());
+    }
+
+    if !fix {
+        return Err(anyhow::anyhow!(
+            "依赖与 jx.lock 不一致，运行 'jx verify --fix' 恢复锁定的依赖"
+        ));
+    }
+
+    // 离线时删除了被篡改的缓存也无法重新下载，只报告需要联网做的修复，不改动任何文件
+    if offline && !(report.missing_cache.is_empty() && report.tampered_cache.is_empty()) {
+        println!("\n📴 离线模式下无法重新下载，'jx verify --fix' 需要联网执行:");
+        for dep in &report.tampered_cache {
+            println!("  删除并重新下载 {} ({})", dep.coordinate(), dep.filename());
+        }
+        for dep in &report.missing_cache {
+            println!("  下载 {} ({})", dep.coordinate(), dep.filename());
+        }
+        return Err(anyhow::anyhow!("离线模式下无法修复本地缓存，请去掉 --offline 后重试"));
+    }
+
+    println!("\n🔧 修复依赖...");
+
+    for filename in &report.extra {
+        installer.uninstall_dependency(filename)?;
+    }
+
+    // 被篡改的缓存删除后重新下载
+    for dep in &report.tampered_cache {
+        fs::remove_file(downloader.cached_path(&dep.to_dependency()))?;
+    }
+
+    fs::create_dir_all(&lib_dir)?;
+    let to_restore = report
+        .missing_cache
+        .iter()
+        .chain(&report.tampered_cache)
+        .chain(&report.missing_lib)
+        .chain(&report.tampered_lib);
+    for dep in to_restore {
+        let cache_path = downloader
+            .download_dependency(&dep.group_id, &dep.artifact_id, &dep.version, dep.classifier.as_deref())
+            .await?;
+        lock_file.check_checksum(dep, Path::new(&cache_path))?;
+        if expected.contains_key(&dep.filename()) {
+            fs::copy(&cache_path, lib_dir.join(dep.filename()))?;
+        }
+    }
+
+    println!("✅ 已恢复 jx.lock 中锁定的 {} 个依赖", lock_file.dependencies.len());
+    Ok(())
+}
+
+#[derive(Default)]
+struct VerifyReport {
+    extra: Vec<String>,
+    missing_lib: Vec<LockedDependency>,
+    tampered_lib: Vec<LockedDependency>,
+    missing_cache: Vec<LockedDependency>,
+    tampered_cache: Vec<LockedDependency>,
+}
+
+impl VerifyReport {
+    fn is_clean(&self) -> bool {
+        self.extra.is_empty()
+            && self.missing_lib.is_empty()
+            && self.tampered_lib.is_empty()
+            && self.missing_cache.is_empty()
+            && self.tampered_cache.is_empty()
+    }
+
+    fn sort(&mut self) {
+        self.extra.sort();
+        for deps in [
+            &mut self.missing_lib,
+            &mut self.tampered_lib,
+            &mut self.missing_cache,
+            &mut self.tampered_cache,
+        ] {
+            deps.sort_by_key(|dep| dep.coordinate());
+        }
+    }
+
+    fn print(&self, total: usize) {
+        println!("锁定依赖: {}", total);
+
+        if !self.extra.is_empty() {
+            println!("\n➕ lib/ 中多余的jar:");
+            for filename in &self.extra {
+                println!("  {}", filename);
+            }
+        }
+        print_dependencies("➖ lib/ 中缺失的依赖:", &self.missing_lib);
+        print_dependencies("⚠️ lib/ 中校验和不匹配的jar:", &self.tampered_lib);
+        // 缓存缺失只是需要重新下载，不代表文件被篡改
+        print_dependencies("💾 本地缓存中缺失的依赖:", &self.missing_cache);
+        print_dependencies("⚠️ 本地缓存中校验和不匹配的jar:", &self.tampered_cache);
+    }
+}
+
+fn print_dependencies(title: &str, deps: &[LockedDependency]) {
+    if deps.is_empty() {
+        return;
+    }
+
+    println!("\n{}", title);
+    for dep in deps {
+        println!("  {} ({})", dep.coordinate(), dep.filename());
+    }
+}
+
+/// 与 `jx install` 一致: 注解处理器不放进lib/，以免出现在运行classpath上
+fn installed_in_lib(dep: &LockedDependency) -> bool {
+    dep.scope != "annotation-processor"
+}
+
+/// 锁文件中没有校验和时视为一致
+fn checksum_matches(dep: &LockedDependency, path: &Path) -> Result<bool> {
+    if dep.checksum.is_empty() {
+        return Ok(true);
+    }
+    Ok(sha256_file(path)? == dep.checksum)
+}
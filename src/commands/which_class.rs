@@ -0,0 +1,257 @@
+use crate::commands::search::parse_jar_filename;
+use crate::index::{class_matches, list_jar_classes, read_jar_coordinates, SearchIndex};
+use crate::lock::LockFile;
+use crate::registry::MavenRegistry;
+use crate::workspace;
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+
+pub async fn execute(class_name: String, remote: bool, offline: bool, add: bool) -> Result<()> {
+    println!("🔎 查找类...");
+
+    let class_name = normalize_class_name(&class_name);
+    println!("类名: {}", class_name);
+
+    let current_dir = std::env::current_dir()?;
+
+    // 项目类路径
+    let classpath_matches = search_project_classpath(&current_dir, &class_name)?;
+    if !classpath_matches.is_empty() {
+        println!("\n📁 项目类路径:");
+        display_matches(&classpath_matches);
+    }
+
+    // 本地缓存
+    let index = SearchIndex::load_and_refresh(false)?;
+    let cache_matches: Vec<ClassMatch> = index
+        .find_class(&class_name)
+        .into_iter()
+        .map(|(artifact, class)| ClassMatch {
+            group_id: artifact.group_id,
+            artifact_id: artifact.artifact_id,
+            version: artifact.version,
+            class_name: class,
+            source: artifact.jar_path,
+        })
+        .collect();
+    if !cache_matches.is_empty() {
+        println!("\n💾 本地缓存:");
+        display_matches(&cache_matches);
+    }
+
+    // Maven Central类名搜索
+    let central_matches = if !remote {
+        Vec::new()
+    } else if offline {
+        println!("\n📴 离线模式: 跳过Maven Central类名搜索");
+        Vec::new()
+    } else {
+        // 网络不可用或被限流时仍然使用本地的结果
+        match search_central_class(&class_name).await {
+            Ok(matches) => {
+                if !matches.is_empty() {
+                    println!("\n🌐 Maven Central:");
+                    display_matches(&matches);
+                }
+                matches
+            }
+            Err(e) => {
+                println!("\n⚠️ Maven Central类名搜索失败: {}", e);
+                Vec::new()
+            }
+        }
+    };
+
+    if !classpath_matches.is_empty() {
+        println!("\n✅ 该类已在项目类路径中");
+        return Ok(());
+    }
+
+    // 每个groupId:artifactId只保留一个候选(缓存中为最新版本)
+    let mut candidates: Vec<&ClassMatch> = Vec::new();
+    for m in cache_matches.iter().chain(&central_matches) {
+        if !candidates
+            .iter()
+            .any(|c| c.group_id == m.group_id && c.artifact_id == m.artifact_id)
+        {
+            candidates.push(m);
+        }
+    }
+
+    // 全限定名精确匹配优先，其次是jx.lock中已有的、缓存中版本多的构件
+    let locked = locked_artifacts(&current_dir);
+    let rank = |m: &ClassMatch| {
+        (
+            m.class_name == class_name,
+            locked.contains(&(m.group_id.clone(), m.artifact_id.clone())),
+            index.versions_of(&m.group_id, &m.artifact_id).len(),
+        )
+    };
+    candidates.sort_by_cached_key(|m| std::cmp::Reverse(rank(m)));
+
+    if candidates.len() > 1 && rank(candidates[0]) == rank(candidates[1]) {
+        println!("\n⚠️ 有多个构件包含 {}，无法确定最佳匹配:", class_name);
+        for candidate in candidates.iter().take_while(|c| rank(c) == rank(candidates[0])) {
+            println!("  jx add {}", candidate.coordinate());
+        }
+        if add {
+            anyhow::bail!("有多个候选构件，请从上面选择一个并运行 jx add");
+        }
+        return Ok(());
+    }
+
+    let best = match candidates.first() {
+        Some(best) => best,
+        None => {
+            println!("\n❌ 未找到包含 {} 的构件", class_name);
+            println!("💡 提示:");
+            if !remote {
+                println!("  - 使用 --remote 查询Maven Central");
+            }
+            println!("  - 检查类名拼写和包名是否正确");
+            return Ok(());
+        }
+    };
+
+    let coordinate = best.coordinate();
+    println!("\n⭐ 最佳匹配: {}", coordinate);
+
+    if add || confirm(&format!("是否将 {} 添加到项目依赖? [y/N] ", coordinate))? {
+        crate::commands::add::execute(coordinate, "compile".to_string(), offline, false, None, None).await
+    } else {
+        println!("可以运行以下命令添加依赖:");
+        println!("  jx add {}", coordinate);
+        Ok(())
+    }
+}
+
+/// 锁文件中已有的groupId:artifactId，读取失败时视为空
+fn locked_artifacts(project_dir: &Path) -> HashSet<(String, String)> {
+    workspace::lock_path(project_dir)
+        .and_then(|path| LockFile::load(&path))
+        .map(|lock_file| {
+            lock_file
+                .dependencies
+                .values()
+                .map(|dep| (dep.group_id.clone(), dep.artifact_id.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[derive(Debug)]
+struct ClassMatch {
+    group_id: String,
+    artifact_id: String,
+    version: String,
+    class_name: String,
+    source: String,
+}
+
+impl ClassMatch {
+    fn coordinate(&self) -> String {
+        format!("{}:{}:{}", self.group_id, self.artifact_id, self.version)
+    }
+}
+
+/// 支持 ClassNotFoundException 的点号形式和 NoClassDefFoundError 的斜杠形式
+fn normalize_class_name(class_name: &str) -> String {
+    let class_name = class_name.trim().trim_end_matches(".class");
+
+    // JVM描述符形式: Lorg/slf4j/LoggerFactory;
+    let class_name = match class_name.strip_prefix('L').and_then(|c| c.strip_suffix(';')) {
+        Some(inner) => inner,
+        None => class_name,
+    };
+
+    class_name.replace('/', ".")
+}
+
+fn search_project_classpath(project_dir: &Path, class_name: &str) -> Result<Vec<ClassMatch>> {
+    let mut matches = Vec::new();
+    let lib_dir = project_dir.join("lib");
+
+    if !lib_dir.exists() {
+        return Ok(matches);
+    }
+
+    for entry in fs::read_dir(&lib_dir)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("jar") {
+            continue;
+        }
+
+        let classes = match list_jar_classes(&path) {
+            Ok(classes) => classes,
+            Err(e) => {
+                eprintln!("⚠️ {}", e);
+                continue;
+            }
+        };
+
+        let filename = path.file_name().unwrap().to_string_lossy().to_string();
+        for class in classes.into_iter().filter(|c| class_matches(c, class_name)) {
+            // jar内的pom.properties最准确，其次从文件名推断
+            let (group_id, artifact_id, version) = read_jar_coordinates(&path)
+                .or_else(|| {
+                    parse_jar_filename(&filename)
+                        .map(|info| (info.group_id, info.artifact_id, info.version))
+                })
+                .unwrap_or_else(|| ("unknown".to_string(), filename.clone(), "unknown".to_string()));
+
+            matches.push(ClassMatch {
+                group_id,
+                artifact_id,
+                version,
+                class_name: class,
+                source: path.display().to_string(),
+            });
+        }
+    }
+
+    Ok(matches)
+}
+
+async fn search_central_class(class_name: &str) -> Result<Vec<ClassMatch>> {
+    println!("\n🔍 正在搜索Maven Central...");
+
+    let page = MavenRegistry::new().search_class(class_name, 20).await?;
+    let matches = page
+        .artifacts
+        .into_iter()
+        .map(|artifact| ClassMatch {
+            group_id: artifact.group_id,
+            artifact_id: artifact.artifact_id,
+            version: artifact.version,
+            class_name: class_name.to_string(),
+            source: "Maven Central".to_string(),
+        })
+        .collect();
+
+    Ok(matches)
+}
+
+fn display_matches(matches: &[ClassMatch]) {
+    for (i, m) in matches.iter().enumerate() {
+        println!("  {}. {}", i + 1, m.coordinate());
+        println!("     类: {}", m.class_name);
+        println!("     来源: {}", m.source);
+    }
+}
+
+/// 仅在交互式终端中询问
+fn confirm(prompt: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+
+    print!("{}", prompt);
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
@@ -1,61 +1,126 @@
+use crate::dependency::{Dependency, DependencyScope, Exclusion};
 use anyhow::Result;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// jx.toml的类型化模型，所有命令都通过它读取项目配置
+///
+/// 依赖写在`[dependencies]`中 (compile)，其他scope写在`[dependencies.<scope>]`子表中:
+///
+/// ```toml
+/// [dependencies]
+/// "org.slf4j:slf4j-api" = "2.0.9"
+/// "com.google.guava:guava" = { version = "32.1.3-jre", exclusions = ["com.google.code.findbugs:jsr305"] }
+///
+/// [dependencies.test]
+/// "junit:junit" = "4.13.2"
+/// ```
+///
+/// 工作区成员之间用路径依赖引用: `"com.example:core" = { path = "../core" }`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct JxConfig {
     pub project: ProjectConfig,
+    pub workspace: Option<WorkspaceConfig>,
+    #[serde(default)]
     pub build: BuildConfig,
+    #[serde(default)]
     pub dependencies: DependenciesConfig,
+    #[serde(default)]
     pub repositories: RepositoriesConfig,
+    /// 只用于校验，生效的值由`settings`按优先级合并后读取
+    #[serde(default)]
+    #[allow(dead_code)]
+    pub net: NetConfig,
+    #[serde(default)]
+    pub profile: BTreeMap<String, ProfileConfig>,
+    #[serde(default, deserialize_with = "scripts")]
+    pub scripts: BTreeMap<String, ScriptConfig>,
+    #[serde(default)]
+    pub shade: ShadeConfig,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ProjectConfig {
+    #[serde(deserialize_with = "non_empty")]
     pub name: String,
+    #[serde(default = "default_version", deserialize_with = "non_empty")]
     pub version: String,
+    pub group_id: Option<String>,
     pub description: Option<String>,
     pub authors: Option<Vec<String>>,
     pub license: Option<String>,
     pub homepage: Option<String>,
     pub repository: Option<String>,
+    #[serde(rename = "type", default)]
+    pub project_type: ProjectType,
+    #[serde(default, deserialize_with = "java_version")]
+    pub java_version: Option<String>,
+}
+
+/// 项目由谁构建: jx自身、Maven或Gradle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ProjectType {
+    #[default]
+    Jx,
+    Maven,
+    Gradle,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct BuildConfig {
     pub source_dir: Option<String>,
     pub target_dir: Option<String>,
+    #[serde(default, deserialize_with = "java_class_name")]
     pub main_class: Option<String>,
+    #[serde(default, deserialize_with = "java_class_name")]
     pub test_class: Option<String>,
+    #[serde(default, deserialize_with = "java_version")]
     pub java_version: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// `[workspace]`: 成员目录相对于工作区根目录，可以使用通配符 (例如 `"libs/*"`)
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    pub members: Vec<String>,
+}
+
+/// 按声明顺序保存的直接依赖，scope取自所在的表
+#[derive(Debug, Default)]
 pub struct DependenciesConfig {
-    pub compile: Option<Vec<Dependency>>,
-    pub runtime: Option<Vec<Dependency>>,
-    pub test: Option<Vec<Dependency>>,
-    pub provided: Option<Vec<Dependency>>,
+    declared: Vec<Dependency>,
+    paths: Vec<PathDependency>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Dependency {
+/// 指向工作区中另一个成员的依赖
+#[derive(Debug, Clone)]
+pub struct PathDependency {
     pub group_id: String,
     pub artifact_id: String,
-    pub version: String,
-    pub scope: Option<String>,
-    pub classifier: Option<String>,
+    /// 相对于声明它的jx.toml所在目录
+    pub path: String,
+    pub scope: DependencyScope,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct RepositoriesConfig {
     pub maven_central: Option<String>,
     pub jcenter: Option<String>,
     pub custom: Option<Vec<CustomRepository>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CustomRepository {
     pub name: String,
     pub url: String,
@@ -63,54 +128,889 @@ pub struct CustomRepository {
     pub password: Option<String>,
 }
 
+/// `[net]`: 离线模式等机器相关配置，见`settings::Settings::offline`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetConfig {
+    #[serde(default)]
+    #[allow(dead_code)]
+    pub offline: bool,
+}
+
+/// `[profile.<name>]`: build、test和run用`--profile`选择的构建配置
+///
+/// ```toml
+/// [profile.ci]
+/// inherits = "release"
+/// javac_flags = ["-Xlint:all", "-Werror"]
+/// jvm_opts = ["-Xmx1g"]
+/// maven_profiles = ["ci"]
+/// gradle_properties = { "org.gradle.caching" = true }
+///
+/// [profile.ci.dependencies.test]
+/// "org.jacoco:org.jacoco.agent" = "0.8.11"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// 继承的profile，默认为同名的内置profile或debug
+    #[serde(default, deserialize_with = "non_empty_option")]
+    pub inherits: Option<String>,
+    /// 是否打包 (Maven package / Gradle build)，而不只是编译
+    pub package: Option<bool>,
+    #[serde(default)]
+    pub javac_flags: Vec<String>,
+    #[serde(default)]
+    pub jvm_opts: Vec<String>,
+    #[serde(default)]
+    pub maven_profiles: Vec<String>,
+    #[serde(default, deserialize_with = "property_values")]
+    pub gradle_properties: BTreeMap<String, String>,
+    #[serde(default)]
+    pub dependencies: DependenciesConfig,
+}
+
+/// `[scripts]`中的任务: shell命令字符串，或者表
+///
+/// ```toml
+/// [scripts]
+/// fmt = "google-java-format -i $(find src -name '*.java')"
+/// gen = { main = "com.example.Generate", args = ["target/generated"] }
+/// ci = { depends = ["fmt", "gen"], cmd = "jx test" }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptConfig {
+    /// shell命令
+    #[serde(default, deserialize_with = "non_empty_option")]
+    pub cmd: Option<String>,
+    /// 用项目classpath运行的Java主类
+    #[serde(default, deserialize_with = "java_class_name")]
+    pub main: Option<String>,
+    /// 主类的参数
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// 先运行的其他脚本
+    #[serde(default)]
+    pub depends: Vec<String>,
+    pub description: Option<String>,
+}
+
+/// `[shade]`: `jx package --uber`合并依赖时重定位的包，子包一起重定位
+///
+/// ```toml
+/// [shade]
+/// relocate = { "com.google" = "myapp.shaded.google" }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShadeConfig {
+    #[serde(default, deserialize_with = "package_mapping")]
+    pub relocate: BTreeMap<String, String>,
+}
+
 impl Default for JxConfig {
     fn default() -> Self {
         Self {
             project: ProjectConfig {
                 name: "my-java-project".to_string(),
-                version: "1.0.0".to_string(),
+                version: default_version(),
+                group_id: None,
                 description: Some("A Java project created with jx".to_string()),
                 authors: Some(vec!["jx team".to_string()]),
                 license: Some("MIT".to_string()),
                 homepage: None,
                 repository: None,
+                project_type: ProjectType::Jx,
+                java_version: Some("11".to_string()),
             },
+            workspace: None,
             build: BuildConfig {
                 source_dir: Some("src/main/java".to_string()),
                 target_dir: Some("target".to_string()),
                 main_class: Some("com.example.Main".to_string()),
                 test_class: Some("com.example.MainTest".to_string()),
-                java_version: Some("11".to_string()),
-            },
-            dependencies: DependenciesConfig {
-                compile: Some(vec![]),
-                runtime: Some(vec![]),
-                test: Some(vec![]),
-                provided: Some(vec![]),
-            },
-            repositories: RepositoriesConfig {
-                maven_central: Some("https://repo1.maven.org/maven2/".to_string()),
-                jcenter: Some("https://jcenter.bintray.com/".to_string()),
-                custom: Some(vec![]),
+                java_version: None,
             },
+            dependencies: DependenciesConfig::default(),
+            repositories: RepositoriesConfig::default(),
+            net: NetConfig::default(),
+            profile: BTreeMap::new(),
+            scripts: BTreeMap::new(),
+            shade: ShadeConfig::default(),
+        }
+    }
+}
+
+impl Default for RepositoriesConfig {
+    fn default() -> Self {
+        Self {
+            maven_central: Some("https://repo1.maven.org/maven2/".to_string()),
+            jcenter: Some("https://jcenter.bintray.com/".to_string()),
+            custom: Some(vec![]),
         }
     }
 }
 
-pub fn load_config(config_path: &PathBuf) -> Result<JxConfig> {
-    if config_path.exists() {
-        let content = fs::read_to_string(config_path)?;
-        let config: JxConfig = toml::from_str(&content)?;
+impl JxConfig {
+    /// 读取并校验jx.toml，错误信息指向出错的行和列
+    pub fn load(config_path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(config_path)
+            .map_err(|e| anyhow::anyhow!("无法读取 {}: {}", config_path.display(), e))?;
+        Self::parse(&content, config_path)
+    }
+
+    /// 读取项目目录下的jx.toml
+    pub fn load_from_dir(project_dir: &Path) -> Result<Self> {
+        Self::load(&project_dir.join("jx.toml"))
+    }
+
+    pub fn parse(original: &str, config_path: &Path) -> Result<Self> {
+        let content = quote_legacy_keys(original);
+        if let Ok(WorkspaceManifest { workspace }) = toml::from_str(&content) {
+            return Err(anyhow::anyhow!(
+                "{} 是工作区的根配置，只包含 [workspace] (成员: {})，请在成员目录中运行或使用 -p 选择成员",
+                config_path.display(),
+                workspace.members.join(", ")
+            ));
+        }
+        let config: Self =
+            toml::from_str(&content).map_err(|e| describe_error(config_path, original, &content, &e))?;
+        config
+            .check_profiles()
+            .and_then(|_| config.check_scripts())
+            .map_err(|e| anyhow::anyhow!("{}: {}", config_path.display(), e))?;
         Ok(config)
-    } else {
-        Ok(JxConfig::default())
+    }
+
+    /// jx.toml中声明的所有直接依赖，不包括路径依赖
+    pub fn dependencies(&self) -> &[Dependency] {
+        &self.dependencies.declared
+    }
+
+    /// 直接依赖加上所有profile中的依赖，一起写入jx.lock，切换profile时不需要重新锁定
+    ///
+    /// 同时在classpath上的注解处理器 (例如Lombok) 只锁定classpath上的条目
+    pub fn lock_dependencies(&self) -> Vec<Dependency> {
+        let mut dependencies: Vec<Dependency> = self
+            .dependencies()
+            .iter()
+            .filter(|dep| {
+                !matches!(dep.scope, DependencyScope::AnnotationProcessor)
+                    || self.dependencies.scope_of(&dep.group_id, &dep.artifact_id, false).is_none()
+            })
+            .cloned()
+            .collect();
+        for profile in self.profile.values() {
+            for dependency in &profile.dependencies.declared {
+                let declared = dependencies.iter().any(|dep| {
+                    dep.group_id == dependency.group_id && dep.artifact_id == dependency.artifact_id
+                });
+                if !declared {
+                    dependencies.push(dependency.clone());
+                }
+            }
+        }
+        dependencies
+    }
+
+    /// 每个脚本必须是shell命令、Java主类或只有depends，依赖的脚本必须存在
+    fn check_scripts(&self) -> Result<()> {
+        for (name, script) in &self.scripts {
+            if name.is_empty()
+                || name.starts_with('-')
+                || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+            {
+                return Err(anyhow::anyhow!(
+                    "无效的脚本名称 `{}`，只能包含字母、数字、-、_ 和 :",
+                    name
+                ));
+            }
+            match (&script.cmd, &script.main) {
+                (Some(_), Some(_)) => {
+                    return Err(anyhow::anyhow!("[scripts] {} 的cmd和main只能指定一个", name))
+                }
+                (None, None) if script.depends.is_empty() => {
+                    return Err(anyhow::anyhow!("[scripts] {} 需要cmd、main或depends", name))
+                }
+                (_, None) if !script.args.is_empty() => {
+                    return Err(anyhow::anyhow!(
+                        "[scripts] {} 的args只用于main，shell命令的参数直接写在cmd中",
+                        name
+                    ))
+                }
+                _ => {}
+            }
+            if let Some(missing) = script.depends.iter().find(|dep| !self.scripts.contains_key(*dep)) {
+                return Err(anyhow::anyhow!("[scripts] {} 依赖的脚本 `{}` 不存在", name, missing));
+            }
+        }
+        Ok(())
+    }
+
+    /// profile之间共用jx.lock，所以profile中的依赖不能覆盖直接依赖，同一构件在各profile中的版本必须一致
+    fn check_profiles(&self) -> Result<()> {
+        let mut seen: Vec<(&str, &Dependency)> = Vec::new();
+        for (name, profile) in &self.profile {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')) {
+                return Err(anyhow::anyhow!(
+                    "无效的profile名称 `{}`，只能包含字母、数字、- 和 _",
+                    name
+                ));
+            }
+            if let Some(parent) = &profile.inherits {
+                if !self.profile.contains_key(parent) && !matches!(parent.as_str(), "debug" | "release") {
+                    return Err(anyhow::anyhow!(
+                        "[profile.{}] 继承的profile `{}` 不存在",
+                        name,
+                        parent
+                    ));
+                }
+            }
+            if let Some(path) = profile.dependencies.paths.first() {
+                return Err(anyhow::anyhow!(
+                    "[profile.{}] 中不支持路径依赖 {}:{}",
+                    name,
+                    path.group_id,
+                    path.artifact_id
+                ));
+            }
+
+            for dependency in &profile.dependencies.declared {
+                let processor = matches!(dependency.scope, DependencyScope::AnnotationProcessor);
+                if self
+                    .dependencies
+                    .scope_of(&dependency.group_id, &dependency.artifact_id, processor)
+                    .is_some()
+                {
+                    return Err(anyhow::anyhow!(
+                        "[profile.{}] 中的 {}:{} 已在 [dependencies] 中声明",
+                        name,
+                        dependency.group_id,
+                        dependency.artifact_id
+                    ));
+                }
+                let other = seen.iter().find(|(_, dep)| {
+                    dep.group_id == dependency.group_id && dep.artifact_id == dependency.artifact_id
+                });
+                match other {
+                    Some((other, dep)) if dep.version != dependency.version => {
+                        return Err(anyhow::anyhow!(
+                            "{}:{} 在 [profile.{}] 中为 {}，在 [profile.{}] 中为 {}，共享的jx.lock要求版本一致",
+                            dependency.group_id,
+                            dependency.artifact_id,
+                            other,
+                            dep.version,
+                            name,
+                            dependency.version
+                        ));
+                    }
+                    Some(_) => {}
+                    None => seen.push((name, dependency)),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 指向工作区成员的路径依赖
+    pub fn path_dependencies(&self) -> &[PathDependency] {
+        &self.dependencies.paths
+    }
+
+    /// `[project] java_version`，兼容写在`[build]`中的旧配置
+    pub fn java_version(&self) -> Option<&str> {
+        self.project
+            .java_version
+            .as_deref()
+            .or(self.build.java_version.as_deref())
+    }
+}
+
+impl ProfileConfig {
+    pub fn dependencies(&self) -> &[Dependency] {
+        &self.dependencies.declared
+    }
+}
+
+/// 只有`[workspace]`的根配置 (虚拟清单)
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceManifest {
+    pub workspace: WorkspaceConfig,
+}
+
+impl WorkspaceManifest {
+    /// 读取工作区根目录的jx.toml，既可以是虚拟清单，也可以是带`[workspace]`的普通项目
+    pub fn load(config_path: &Path) -> Result<Option<WorkspaceConfig>> {
+        let content = fs::read_to_string(config_path)
+            .map_err(|e| anyhow::anyhow!("无法读取 {}: {}", config_path.display(), e))?;
+        let content = quote_legacy_keys(&content);
+        if let Ok(manifest) = toml::from_str::<WorkspaceManifest>(&content) {
+            return Ok(Some(manifest.workspace));
+        }
+        Ok(JxConfig::parse(&content, config_path)?.workspace)
+    }
+}
+
+impl ProjectType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectType::Jx => "jx",
+            ProjectType::Maven => "maven",
+            ProjectType::Gradle => "gradle",
+        }
+    }
+}
+
+impl TryFrom<String> for ProjectType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "jx" => Ok(ProjectType::Jx),
+            "maven" => Ok(ProjectType::Maven),
+            "gradle" => Ok(ProjectType::Gradle),
+            _ => Err(format!("未知的项目类型 `{}`，可选值: jx, maven, gradle", value)),
+        }
+    }
+}
+
+fn default_version() -> String {
+    "1.0.0".to_string()
+}
+
+fn non_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    if value.trim().is_empty() {
+        return Err(de::Error::custom("不能为空"));
+    }
+    Ok(value)
+}
+
+fn non_empty_option<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    non_empty(deserializer).map(Some)
+}
+
+/// 脚本可以直接写成shell命令字符串，也可以写成表
+fn scripts<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, ScriptConfig>, D::Error> {
+    struct ScriptValue(ScriptConfig);
+
+    impl<'de> Deserialize<'de> for ScriptValue {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(ScriptValueVisitor).map(ScriptValue)
+        }
+    }
+
+    Ok(BTreeMap::<String, ScriptValue>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, ScriptValue(script))| (name, script))
+        .collect())
+}
+
+struct ScriptValueVisitor;
+
+impl<'de> Visitor<'de> for ScriptValueVisitor {
+    type Value = ScriptConfig;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("shell命令字符串或 { cmd = \"..\" } / { main = \"..\" } 内联表")
+    }
+
+    fn visit_str<E: de::Error>(self, cmd: &str) -> Result<Self::Value, E> {
+        if cmd.trim().is_empty() {
+            return Err(E::custom("命令不能为空"));
+        }
+        Ok(ScriptConfig {
+            cmd: Some(cmd.to_string()),
+            ..ScriptConfig::default()
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        ScriptConfig::deserialize(de::value::MapAccessDeserializer::new(map))
+    }
+}
+
+/// Gradle属性的值可以写成字符串、布尔值或数字，统一按`-Pkey=value`传递
+fn property_values<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawValue {
+        Bool(bool),
+        Integer(i64),
+        Float(f64),
+        Text(String),
+    }
+
+    Ok(BTreeMap::<String, RawValue>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                RawValue::Bool(b) => b.to_string(),
+                RawValue::Integer(i) => i.to_string(),
+                RawValue::Float(f) => f.to_string(),
+                RawValue::Text(text) => text,
+            };
+            (key, value)
+        })
+        .collect())
+}
+
+/// 接受 `"17"`、`"1.8"` 或整数 `17`
+fn java_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawVersion {
+        Number(u32),
+        Text(String),
+    }
+
+    let version = match RawVersion::deserialize(deserializer)? {
+        RawVersion::Number(number) => number.to_string(),
+        RawVersion::Text(text) => text,
+    };
+    let feature = version.strip_prefix("1.").unwrap_or(&version);
+    if feature.is_empty() || !feature.chars().all(|c| c.is_ascii_digit()) {
+        return Err(de::Error::custom(format!(
+            "无效的Java版本 `{}`，应为 \"8\"、\"1.8\"、\"17\" 这样的版本号",
+            version
+        )));
+    }
+    Ok(Some(version))
+}
+
+fn java_class_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    let valid = name.split('.').all(|part| {
+        let mut chars = part.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+            && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+    });
+    if !valid {
+        return Err(de::Error::custom(format!(
+            "无效的类名 `{}`，应为全限定类名，例如 com.example.Main",
+            name
+        )));
+    }
+    Ok(Some(name))
+}
+
+/// 重定位规则的两边都必须是包名，例如 com.google
+fn package_mapping<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, String>, D::Error> {
+    let mapping = BTreeMap::<String, String>::deserialize(deserializer)?;
+    let valid = |name: &str| {
+        name.split('.').all(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+                && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        })
+    };
+    for (from, to) in &mapping {
+        if let Some(invalid) = [from, to].into_iter().find(|name| !valid(name)) {
+            return Err(de::Error::custom(format!(
+                "无效的包名 `{}`，重定位规则应写成 \"com.google\" = \"myapp.shaded.google\"",
+                invalid
+            )));
+        }
+        if from == to {
+            return Err(de::Error::custom(format!("包 `{}` 重定位到了自身", from)));
+        }
+    }
+    Ok(mapping)
+}
+
+/// `[dependencies]`中的键: 依赖坐标，或者`[dependencies.<scope>]`子表的scope名
+enum DependencyKey {
+    Coordinate(String, String),
+    Scope(DependencyScope),
+}
+
+impl<'de> Deserialize<'de> for DependencyKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        if key.contains(':') {
+            let (group_id, artifact_id) = parse_coordinate_key(&key).map_err(de::Error::custom)?;
+            return Ok(DependencyKey::Coordinate(group_id, artifact_id));
+        }
+        match key.as_str() {
+            "compile" => Ok(DependencyKey::Scope(DependencyScope::Compile)),
+            "runtime" => Ok(DependencyKey::Scope(DependencyScope::Runtime)),
+            "test" => Ok(DependencyKey::Scope(DependencyScope::Test)),
+            "provided" => Ok(DependencyKey::Scope(DependencyScope::Provided)),
+            "annotation-processor" => Ok(DependencyKey::Scope(DependencyScope::AnnotationProcessor)),
+            _ => Err(de::Error::custom(format!(
+                "`{}` 既不是依赖坐标也不是scope，依赖应写成 \"groupId:artifactId\" = \"version\"，scope子表可选: compile, runtime, test, provided, annotation-processor",
+                key
+            ))),
+        }
+    }
+}
+
+/// scope子表中只允许依赖坐标
+struct CoordinateKey(String, String);
+
+impl<'de> Deserialize<'de> for CoordinateKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        let (group_id, artifact_id) = parse_coordinate_key(&key).map_err(de::Error::custom)?;
+        Ok(CoordinateKey(group_id, artifact_id))
+    }
+}
+
+fn parse_coordinate_key(key: &str) -> Result<(String, String), String> {
+    let parts: Vec<&str> = key.split(':').collect();
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    };
+    if parts.len() != 2 || !parts.iter().all(|part| valid_part(part)) {
+        return Err(format!(
+            "无效的依赖坐标 `{}`，应为 \"groupId:artifactId\"，版本写在等号右边",
+            key
+        ));
+    }
+    Ok((parts[0].to_string(), parts[1].to_string()))
+}
+
+/// 依赖的值: `"1.0"`、`{ version = "1.0", classifier = "..", optional = true, exclusions = [..] }`
+/// 或者路径依赖 `{ path = "../core" }`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DependencyDetail {
+    #[serde(default, deserialize_with = "non_empty_option")]
+    version: Option<String>,
+    #[serde(default, deserialize_with = "non_empty_option")]
+    path: Option<String>,
+    classifier: Option<String>,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default, deserialize_with = "exclusions")]
+    exclusions: Vec<Exclusion>,
+}
+
+fn exclusions<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Exclusion>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|exclusion| match exclusion.split_once(':') {
+            Some((group_id, artifact_id)) if !group_id.is_empty() && !artifact_id.is_empty() => {
+                Ok(Exclusion {
+                    group_id: group_id.to_string(),
+                    artifact_id: artifact_id.to_string(),
+                })
+            }
+            _ => Err(de::Error::custom(format!(
+                "无效的exclusion `{}`，应为 \"groupId:artifactId\" (可以使用 *)",
+                exclusion
+            ))),
+        })
+        .collect()
+}
+
+struct DependencyValueVisitor;
+
+impl<'de> Visitor<'de> for DependencyValueVisitor {
+    type Value = DependencyDetail;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("版本字符串或 { version = \"..\" } 内联表")
+    }
+
+    fn visit_str<E: de::Error>(self, version: &str) -> Result<Self::Value, E> {
+        if version.trim().is_empty() {
+            return Err(E::custom("版本不能为空"));
+        }
+        Ok(DependencyDetail {
+            version: Some(version.to_string()),
+            path: None,
+            classifier: None,
+            optional: false,
+            exclusions: Vec::new(),
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        DependencyDetail::deserialize(de::value::MapAccessDeserializer::new(map))
     }
 }
 
-pub fn save_config(config: &JxConfig, config_path: &PathBuf) -> Result<()> {
-    let content = toml::to_string_pretty(config)?;
-    fs::write(config_path, content)?;
-    Ok(())
+/// 解析一个依赖的值并追加到`dependencies`
+///
+/// 同一构件只能声明一次，唯一的例外是同时作为注解处理器和classpath依赖 (例如Lombok)，此时版本必须一致
+struct DependencySeed<'a> {
+    group_id: String,
+    artifact_id: String,
+    scope: DependencyScope,
+    dependencies: &'a mut DependenciesConfig,
+}
+
+impl<'de> DeserializeSeed<'de> for DependencySeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        let processor = matches!(self.scope, DependencyScope::AnnotationProcessor);
+        if let Some(existing) = self.dependencies.scope_of(&self.group_id, &self.artifact_id, processor) {
+            return Err(de::Error::custom(format!(
+                "依赖 {}:{} 重复声明 (已在 {} scope中声明)",
+                self.group_id,
+                self.artifact_id,
+                existing.as_str()
+            )));
+        }
+
+        let detail = deserializer.deserialize_any(DependencyValueVisitor)?;
+        let version = match (detail.version, detail.path) {
+            (None, Some(_)) if processor => {
+                return Err(de::Error::custom("注解处理器不支持路径依赖，请使用发布的构件"))
+            }
+            (None, Some(path)) => {
+                if detail.classifier.is_some() || !detail.exclusions.is_empty() {
+                    return Err(de::Error::custom("路径依赖不支持classifier和exclusions"));
+                }
+                self.dependencies.paths.push(PathDependency {
+                    group_id: self.group_id,
+                    artifact_id: self.artifact_id,
+                    path,
+                    scope: self.scope,
+                });
+                return Ok(());
+            }
+            (Some(version), None) => version,
+            (Some(_), Some(_)) => {
+                return Err(de::Error::custom("version和path只能指定一个，路径依赖的版本取自成员的jx.toml"))
+            }
+            (None, None) => return Err(de::Error::custom("缺少version (或工作区成员的path)")),
+        };
+
+        let counterpart = self.dependencies.declared.iter().find(|dep| {
+            dep.group_id == self.group_id && dep.artifact_id == self.artifact_id
+        });
+        if let Some(counterpart) = counterpart.filter(|dep| dep.version != version) {
+            return Err(de::Error::custom(format!(
+                "{}:{} 同时声明为注解处理器和 {} 依赖时版本必须一致: {} 和 {}",
+                self.group_id,
+                self.artifact_id,
+                if processor { counterpart.scope.as_str() } else { self.scope.as_str() },
+                counterpart.version,
+                version
+            )));
+        }
+
+        let mut dependency = Dependency::new(&self.group_id, &self.artifact_id, &version)
+            .with_scope(self.scope)
+            .with_exclusions(detail.exclusions)
+            .optional(detail.optional);
+        if let Some(classifier) = detail.classifier {
+            dependency = dependency.with_classifier(&classifier);
+        }
+        self.dependencies.declared.push(dependency);
+        Ok(())
+    }
+}
+
+/// `[dependencies.<scope>]` 子表
+struct ScopeTableSeed<'a> {
+    scope: DependencyScope,
+    dependencies: &'a mut DependenciesConfig,
+}
+
+impl<'de> DeserializeSeed<'de> for ScopeTableSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ScopeTableSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "[dependencies.{}] 依赖表", self.scope.as_str())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(CoordinateKey(group_id, artifact_id)) = map.next_key()? {
+            map.next_value_seed(DependencySeed {
+                group_id,
+                artifact_id,
+                scope: self.scope.clone(),
+                dependencies: self.dependencies,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de> Deserialize<'de> for DependenciesConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(DependenciesVisitor)
+    }
+}
+
+struct DependenciesVisitor;
+
+impl<'de> Visitor<'de> for DependenciesVisitor {
+    type Value = DependenciesConfig;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("[dependencies] 依赖表")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut dependencies = DependenciesConfig::default();
+        while let Some(key) = map.next_key()? {
+            match key {
+                DependencyKey::Coordinate(group_id, artifact_id) => {
+                    map.next_value_seed(DependencySeed {
+                        group_id,
+                        artifact_id,
+                        scope: DependencyScope::Compile,
+                        dependencies: &mut dependencies,
+                    })?;
+                }
+                DependencyKey::Scope(scope) => {
+                    map.next_value_seed(ScopeTableSeed {
+                        scope,
+                        dependencies: &mut dependencies,
+                    })?;
+                }
+            }
+        }
+        Ok(dependencies)
+    }
+}
+
+impl DependenciesConfig {
+    /// 已声明的依赖 (包括路径依赖) 所在的scope，`processor`选择在注解处理器中还是在classpath上查找
+    fn scope_of(&self, group_id: &str, artifact_id: &str, processor: bool) -> Option<&DependencyScope> {
+        let declared = self
+            .declared
+            .iter()
+            .find(|dep| {
+                dep.group_id == group_id
+                    && dep.artifact_id == artifact_id
+                    && matches!(dep.scope, DependencyScope::AnnotationProcessor) == processor
+            })
+            .map(|dep| &dep.scope);
+        declared.or_else(|| {
+            self.paths
+                .iter()
+                .find(|dep| dep.group_id == group_id && dep.artifact_id == artifact_id)
+                .map(|dep| &dep.scope)
+        })
+    }
+}
+
+/// 早期的jx.toml把依赖写成 `group:artifact = "version"`，冒号在TOML裸键中不合法。
+/// 返回这些行的行号 (从0开始)
+pub fn legacy_key_lines(content: &str) -> Vec<usize> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| legacy_key(line).is_some())
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn legacy_key(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with(['"', '\'', '[', '#']) {
+        return None;
+    }
+    let (key, _) = trimmed.split_once('=')?;
+    let key = key.trim_end();
+    (key.contains(':') && !key.contains(char::is_whitespace)).then_some(key)
+}
+
+/// 读取前给旧格式的依赖键加上引号，其余内容保持不变
+pub(crate) fn quote_legacy_keys(content: &str) -> Cow<'_, str> {
+    if legacy_key_lines(content).is_empty() {
+        return Cow::Borrowed(content);
+    }
+
+    // 逐行保留原来的换行符 (包括CRLF和文件末尾的换行)
+    let quoted: String = content
+        .split_inclusive('\n')
+        .map(|line| match legacy_key(line) {
+            Some(key) => line.replacen(key, &format!("\"{}\"", key), 1),
+            None => line.to_string(),
+        })
+        .collect();
+    Cow::Owned(quoted)
+}
+
+/// 把TOML错误转换成指向具体行列的提示
+///
+/// `content`是给旧格式依赖键加上引号后实际解析的文本，行号不变；
+/// 显示的行和列换算回用户写的`original`
+fn describe_error(config_path: &Path, original: &str, content: &str, error: &toml::de::Error) -> anyhow::Error {
+    let Some(span) = error.span() else {
+        return anyhow::anyhow!("{}: {}", config_path.display(), error.message());
+    };
+
+    let before = &content[..span.start];
+    let line_number = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let quoted_line = content[line_start..].lines().next().unwrap_or("");
+    let line = original.lines().nth(line_number - 1).unwrap_or("");
+
+    let start = original_offset(line, span.start - line_start);
+    let end = original_offset(line, span.end.min(line_start + quoted_line.len()).max(span.start) - line_start);
+    let column = line[..start].chars().count() + 1;
+    let width = line[start..end.max(start)].chars().count().max(1);
+
+    let gutter = " ".repeat(line_number.to_string().len());
+    anyhow::anyhow!(
+        "{} 第{}行第{}列: {}\n{} |\n{} | {}\n{} | {}{}",
+        config_path.display(),
+        line_number,
+        column,
+        error.message(),
+        gutter,
+        line_number,
+        line,
+        gutter,
+        " ".repeat(column - 1),
+        "^".repeat(width)
+    )
+}
+
+/// `quote_legacy_keys`在旧格式的依赖键两边各插入一个引号，把加引号后行内的字节位置换算回原来的行
+fn original_offset(line: &str, offset: usize) -> usize {
+    let Some(key) = legacy_key(line) else {
+        return offset.min(line.len());
+    };
+    let key_start = line.find(key).unwrap_or(0);
+    let key_end = key_start + key.len();
+
+    let offset = if offset <= key_start {
+        offset
+    } else if offset <= key_end + 1 {
+        (offset - 1).min(key_end)
+    } else {
+        offset - 2
+    };
+    offset.min(line.len())
+}
+
+/// 判断是否启用离线模式
+///
+/// 命令行的`--offline`优先，其次是分层配置中的`net.offline`
+pub fn is_offline(cli_flag: bool) -> bool {
+    cli_flag || crate::settings::current().offline()
 }
 
 pub fn get_config_path() -> PathBuf {
@@ -118,3 +1018,30 @@ pub fn get_config_path() -> PathBuf {
     path.push("jx.toml");
     path
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_columns_refer_to_the_unquoted_legacy_line() {
+        let content = "[project]\nname = \"demo\"\nversion = \"1.0\"\n\n[dependencies]\ncom.google:g = 1.0.0\n";
+        let error = JxConfig::parse(content, Path::new("jx.toml")).unwrap_err().to_string();
+
+        assert!(error.contains("第6行第19列"), "{}", error);
+        assert!(error.contains("6 | com.google:g = 1.0.0"), "{}", error);
+    }
+
+    #[test]
+    fn original_offset_skips_inserted_quotes() {
+        let line = "  a.b:c = 1";
+        // 加引号后: `  "a.b:c" = 1`
+        assert_eq!(original_offset(line, 1), 1);
+        assert_eq!(original_offset(line, 2), 2);
+        assert_eq!(original_offset(line, 3), 2);
+        assert_eq!(original_offset(line, 8), 7);
+        assert_eq!(original_offset(line, 9), 7);
+        assert_eq!(original_offset(line, 12), 10);
+        assert_eq!(original_offset("name = 1", 5), 5);
+    }
+}
@@ -20,6 +20,8 @@ pub enum DependencyScope {
     Test,
     Provided,
     System,
+    /// 注解处理器，与编译classpath分开解析，只出现在javac的`-processorpath`中
+    AnnotationProcessor,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,6 +83,30 @@ impl Dependency {
     }
 }
 
+impl DependencyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DependencyScope::Compile => "compile",
+            DependencyScope::Runtime => "runtime",
+            DependencyScope::Test => "test",
+            DependencyScope::Provided => "provided",
+            DependencyScope::System => "system",
+            DependencyScope::AnnotationProcessor => "annotation-processor",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "runtime" | "runtimeOnly" => DependencyScope::Runtime,
+            "test" | "testImplementation" | "testRuntimeOnly" => DependencyScope::Test,
+            "provided" | "compileOnly" => DependencyScope::Provided,
+            "system" => DependencyScope::System,
+            "annotation-processor" | "annotationProcessor" => DependencyScope::AnnotationProcessor,
+            _ => DependencyScope::Compile,
+        }
+    }
+}
+
 impl DependencyNode {
     pub fn new(dependency: Dependency, depth: usize) -> Self {
         Self {
@@ -1,19 +1,85 @@
 use anyhow::{Context, Result};
-use futures_util::StreamExt;
+use futures_util::{StreamExt, TryStreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
-use reqwest;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::io::AsyncWriteExt;
 
 pub struct Downloader {
-    cache_dir: String,
+    cache_dir: PathBuf,
+    repository: String,
+    offline: bool,
 }
 
 impl Downloader {
+    /// 缓存目录和仓库地址来自配置项 `cache.dir` 与 `repositories.*`
     pub fn new() -> Self {
-        let cache_dir = format!("{}/.jx/cache", dirs::home_dir().unwrap().display());
-        Self { cache_dir }
+        let settings = crate::settings::current();
+        Self {
+            cache_dir: settings.cache_dir(),
+            repository: settings.repository_url(),
+            offline: false,
+        }
+    }
+
+    /// 离线模式下只从缓存读取，缓存缺失时直接报错
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    pub fn cached_path(&self, dep: &crate::dependency::Dependency) -> PathBuf {
+        self.cache_dir()
+            .join(&dep.group_id)
+            .join(&dep.artifact_id)
+            .join(dep.filename())
+    }
+
+    /// 返回缓存中缺失的依赖坐标
+    pub fn missing_from_cache(&self, dependencies: &[crate::dependency::Dependency]) -> Vec<String> {
+        dependencies
+            .iter()
+            .filter(|dep| !self.cached_path(dep).exists())
+            .map(|dep| dep.coordinate())
+            .collect()
+    }
+
+    /// 离线模式下确保所有依赖都已缓存，否则列出全部缺失坐标
+    pub fn ensure_cached(&self, dependencies: &[crate::dependency::Dependency]) -> Result<()> {
+        let missing = self.missing_from_cache(dependencies);
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        Err(anyhow::anyhow!(
+            "离线模式下本地缓存缺少 {} 个依赖:\n  {}\n请联网后运行 'jx install' 填充缓存",
+            missing.len(),
+            missing.join("\n  ")
+        ))
+    }
+
+    /// 按配置项 `net.concurrency` 并发下载，结果与输入顺序一致
+    pub async fn download_all(
+        &self,
+        dependencies: &[crate::dependency::Dependency],
+    ) -> Result<Vec<String>> {
+        let concurrency = crate::settings::current().concurrency();
+        futures_util::stream::iter(dependencies)
+            .map(|dep| {
+                self.download_dependency(
+                    &dep.group_id,
+                    &dep.artifact_id,
+                    &dep.version,
+                    dep.classifier.as_deref(),
+                )
+            })
+            .buffered(concurrency)
+            .try_collect()
+            .await
     }
 
     pub async fn download_dependency(
@@ -33,10 +99,13 @@ impl Downloader {
             format!("{}-{}.jar", artifact_id, version)
         };
 
-        let cache_path = format!(
-            "{}/{}/{}/{}",
-            self.cache_dir, group_id, artifact_id, filename
-        );
+        let cache_path = self
+            .cache_dir
+            .join(group_id)
+            .join(artifact_id)
+            .join(&filename)
+            .to_string_lossy()
+            .to_string();
         let cache_file = Path::new(&cache_path);
 
         // 检查缓存
@@ -45,6 +114,15 @@ impl Downloader {
             return Ok(cache_path);
         }
 
+        if self.offline {
+            return Err(anyhow::anyhow!(
+                "离线模式下本地缓存缺少: {}:{}:{}",
+                group_id,
+                artifact_id,
+                version
+            ));
+        }
+
         // 创建目录
         if let Some(parent) = cache_file.parent() {
             fs::create_dir_all(parent)?;
@@ -55,7 +133,7 @@ impl Downloader {
         println!("下载: {}", url);
 
         // 创建HTTP客户端
-        let client = reqwest::Client::new();
+        let client = crate::settings::http_client()?;
 
         // 发送GET请求
         let response = client.get(&url).send().await.context("发送HTTP请求失败")?;
@@ -110,7 +188,66 @@ impl Downloader {
         Ok(cache_path)
     }
 
-    fn build_maven_central_url(
+    /// POM与jar放在同一缓存目录，搜索索引也从这里读取元数据
+    pub fn cached_pom_path(&self, group_id: &str, artifact_id: &str, version: &str) -> PathBuf {
+        self.cache_dir()
+            .join(group_id)
+            .join(artifact_id)
+            .join(format!("{}-{}.pom", artifact_id, version))
+    }
+
+    pub async fn download_pom(&self, group_id: &str, artifact_id: &str, version: &str) -> Result<String> {
+        let cache_path = self.cached_pom_path(group_id, artifact_id, version);
+        if cache_path.exists() {
+            return fs::read_to_string(&cache_path)
+                .with_context(|| format!("读取POM失败: {}", cache_path.display()));
+        }
+
+        if self.offline {
+            return Err(anyhow::anyhow!(
+                "离线模式下本地缓存缺少POM: {}:{}:{}",
+                group_id,
+                artifact_id,
+                version
+            ));
+        }
+
+        let url = format!(
+            "{}/{}/{}/{}/{}-{}.pom",
+            self.repository,
+            group_id.replace('.', "/"),
+            artifact_id,
+            version,
+            artifact_id,
+            version
+        );
+        log::debug!("下载POM: {}", url);
+
+        let response = crate::settings::http_client()?
+            .get(&url)
+            .send()
+            .await
+            .context("发送HTTP请求失败")?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "下载POM失败 {}:{}:{}，状态码: {}",
+                group_id,
+                artifact_id,
+                version,
+                response.status()
+            ));
+        }
+
+        let content = response.text().await.context("读取POM失败")?;
+        if let Some(parent) = cache_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&cache_path, &content)?;
+
+        Ok(content)
+    }
+
+    pub fn build_maven_central_url(
         &self,
         group_id: &str,
         artifact_id: &str,
@@ -119,8 +256,8 @@ impl Downloader {
     ) -> String {
         let group_path = group_id.replace('.', "/");
         let mut url = format!(
-            "https://repo1.maven.org/maven2/{}/{}/{}/{}-{}",
-            group_path, artifact_id, version, artifact_id, version
+            "{}/{}/{}/{}/{}-{}",
+            self.repository, group_path, artifact_id, version, artifact_id, version
         );
 
         if let Some(c) = classifier {
@@ -132,7 +269,7 @@ impl Downloader {
     }
 
     pub fn clear_cache(&self) -> Result<()> {
-        if Path::new(&self.cache_dir).exists() {
+        if self.cache_dir.exists() {
             fs::remove_dir_all(&self.cache_dir)?;
             println!("缓存已清理");
         }
@@ -140,7 +277,7 @@ impl Downloader {
     }
 
     pub fn get_cache_size(&self) -> Result<u64> {
-        if !Path::new(&self.cache_dir).exists() {
+        if !self.cache_dir.exists() {
             return Ok(0);
         }
 
@@ -149,7 +286,7 @@ impl Downloader {
         Ok(total_size)
     }
 
-    fn calculate_dir_size(&self, dir_path: &str, total_size: &mut u64) -> Result<()> {
+    fn calculate_dir_size(&self, dir_path: &Path, total_size: &mut u64) -> Result<()> {
         let entries = fs::read_dir(dir_path)?;
 
         for entry in entries {
@@ -161,7 +298,7 @@ impl Downloader {
                     *total_size += metadata.len();
                 }
             } else if path.is_dir() {
-                self.calculate_dir_size(path.to_str().unwrap(), total_size)?;
+                self.calculate_dir_size(&path, total_size)?;
             }
         }
 
@@ -0,0 +1,789 @@
+use crate::catalog::{to_accessor, CatalogLibrary, CatalogVersionChange, VersionCatalog};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Groovy和Kotlin DSL的构建脚本，两者同时存在时使用Groovy脚本
+const BUILD_SCRIPTS: [&str; 2] = ["build.gradle", "build.gradle.kts"];
+const SETTINGS_SCRIPTS: [&str; 2] = ["settings.gradle", "settings.gradle.kts"];
+
+/// 目录中的Gradle构建脚本文件名
+pub fn build_script(dir: &Path) -> Option<&'static str> {
+    BUILD_SCRIPTS.into_iter().find(|name| dir.join(name).exists())
+}
+
+/// 目录中的Gradle设置脚本文件名
+pub fn settings_script(dir: &Path) -> Option<&'static str> {
+    SETTINGS_SCRIPTS.into_iter().find(|name| dir.join(name).exists())
+}
+
+pub fn is_gradle_project(dir: &Path) -> bool {
+    build_script(dir).is_some() || settings_script(dir).is_some()
+}
+
+/// jx的scope对应的Gradle依赖配置
+pub fn configuration(scope: &str) -> &'static str {
+    match scope {
+        "runtime" => "runtimeOnly",
+        "test" => "testImplementation",
+        "provided" => "compileOnly",
+        "annotation-processor" => "annotationProcessor",
+        _ => "implementation",
+    }
+}
+
+/// 设置脚本中的`rootProject.name`
+pub fn root_project_name(dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(dir.join(settings_script(dir)?)).ok()?;
+    content
+        .lines()
+        .find_map(|line| property_value(line, "rootProject.name"))
+}
+
+/// 设置脚本中`include`的子项目，返回项目路径 (例如`:services:api`) 和项目目录
+///
+/// 支持`project(":x").projectDir = file("...")`改写的目录
+pub fn included_projects(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Some(content) = settings_script(dir).and_then(|script| fs::read_to_string(dir.join(script)).ok())
+    else {
+        return Vec::new();
+    };
+
+    let mut projects: Vec<String> = Vec::new();
+    let mut project_dirs: Vec<(String, String)> = Vec::new();
+    // include的参数可以跨多行: 括号没有闭合或行尾是逗号时下一行继续
+    let mut continued = false;
+
+    for line in content.lines() {
+        let code = code_part(line).trim();
+        let arguments = if continued {
+            Some(code)
+        } else {
+            code.strip_prefix("include")
+                .filter(|rest| rest.starts_with(['(', ' ', '\t']))
+        };
+
+        if let Some(arguments) = arguments {
+            projects.extend(string_literals(arguments).into_iter().map(project_path));
+            let open = continued || arguments.trim_start().starts_with('(');
+            continued = (open && !arguments.contains(')')) || arguments.ends_with(',');
+            continue;
+        }
+
+        if let Some(rest) = code.strip_prefix("project(") {
+            let Some((path, _)) = quoted(rest.trim_start()) else {
+                continue;
+            };
+            let file_argument = code
+                .split_once(".projectDir")
+                .and_then(|(_, value)| value.split_once("file("))
+                .and_then(|(_, argument)| quoted(argument.trim_start()));
+            if let Some((project_dir, _)) = file_argument {
+                project_dirs.push((project_path(path), project_dir.to_string()));
+            }
+        }
+    }
+
+    projects
+        .into_iter()
+        .map(|path| {
+            let project_dir = match project_dirs.iter().find(|(p, _)| *p == path) {
+                Some((_, custom)) => dir.join(custom),
+                None => dir.join(path.trim_start_matches(':').replace(':', "/")),
+            };
+            (path, project_dir)
+        })
+        .collect()
+}
+
+/// 构建脚本中声明的依赖
+#[derive(Debug, Clone)]
+pub struct GradleDependency {
+    /// 依赖配置，例如implementation、testImplementation
+    pub configuration: String,
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: Option<String>,
+    /// 通过版本目录引用时的库别名，例如`libs.commons.lang3`对应的`commons-lang3`
+    pub alias: Option<String>,
+}
+
+/// 构建脚本plugins块中的插件
+#[derive(Debug, Clone)]
+pub struct GradlePlugin {
+    pub id: String,
+    pub version: Option<String>,
+    /// 通过`alias(libs.plugins.xxx)`引用时的插件别名
+    pub alias: Option<String>,
+}
+
+/// 版本修改的结果
+pub struct GradleVersionChange {
+    pub old_version: Option<String>,
+    /// 版本在版本目录中定义时修改的是版本目录
+    pub catalog: Option<CatalogVersionChange>,
+}
+
+/// 依赖的写法
+#[derive(Debug, Clone)]
+enum Notation {
+    /// `'g:a:v'`或`"g:a:v"`
+    Coordinate { literal: String, quote: char },
+    /// `group: 'g', name: 'a', version: 'v'`或`group = "g", name = "a", version = "v"`
+    Named,
+    /// `libs.xxx`
+    Catalog,
+    /// `libs.bundles.xxx`
+    Bundle(String),
+}
+
+#[derive(Debug)]
+struct Entry {
+    line: usize,
+    dependency: GradleDependency,
+    notation: Notation,
+}
+
+/// 按行编辑的Gradle构建脚本 (build.gradle或build.gradle.kts)
+///
+/// 只解析和修改顶层`dependencies { }`块中的依赖语句，
+/// `buildscript`、`constraints`等嵌套块中的依赖不受影响
+pub struct GradleScript {
+    path: PathBuf,
+    lines: Vec<String>,
+    trailing_newline: bool,
+    kotlin: bool,
+    catalog: Option<VersionCatalog>,
+    catalog_changed: bool,
+}
+
+impl GradleScript {
+    pub fn open(project_dir: &Path) -> Result<Self> {
+        let name = build_script(project_dir).ok_or_else(|| {
+            anyhow::anyhow!("在 {} 中找不到build.gradle或build.gradle.kts", project_dir.display())
+        })?;
+        let path = project_dir.join(name);
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("无法读取 {}", path.display()))?;
+
+        Ok(Self {
+            kotlin: name.ends_with(".kts"),
+            trailing_newline: content.ends_with('\n'),
+            lines: content.lines().map(str::to_string).collect(),
+            catalog: VersionCatalog::find(project_dir)?,
+            catalog_changed: false,
+            path,
+        })
+    }
+
+    pub fn file_name(&self) -> &str {
+        if self.kotlin {
+            "build.gradle.kts"
+        } else {
+            "build.gradle"
+        }
+    }
+
+    pub fn catalog(&self) -> Option<&VersionCatalog> {
+        self.catalog.as_ref()
+    }
+
+    pub fn content(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// 顶层dependencies块中的依赖，`libs.xxx`引用通过版本目录解析
+    pub fn dependencies(&self) -> Vec<GradleDependency> {
+        self.entries().into_iter().map(|entry| entry.dependency).collect()
+    }
+
+    /// plugins块中的插件，`alias(libs.plugins.xxx)`引用通过版本目录解析
+    pub fn plugins(&self) -> Vec<GradlePlugin> {
+        let Some((start, end)) = self.block("plugins") else {
+            return Vec::new();
+        };
+
+        self.lines[start + 1..end]
+            .iter()
+            .filter_map(|line| {
+                let code = code_part(line).trim();
+                if let Some(rest) = code.strip_prefix("alias(libs.plugins.") {
+                    let accessor = leading_token(rest, |c| c.is_ascii_alphanumeric() || c == '.' || c == '_');
+                    let plugin = self.catalog.as_ref()?.plugin(accessor)?;
+                    return Some(GradlePlugin {
+                        id: plugin.id,
+                        version: plugin.version,
+                        alias: Some(plugin.alias),
+                    });
+                }
+
+                // id("x") version "1.0" 或 id 'x' version '1.0'
+                let rest = code.strip_prefix("id")?.trim_start();
+                let rest = rest.strip_prefix('(').unwrap_or(rest).trim_start();
+                let (id, _) = quoted(rest)?;
+                let version = code
+                    .find(" version ")
+                    .and_then(|index| quoted(code[index + " version ".len()..].trim_start()))
+                    .map(|(version, _)| version.to_string());
+                Some(GradlePlugin {
+                    id: id.to_string(),
+                    version,
+                    alias: None,
+                })
+            })
+            .collect()
+    }
+
+    pub fn has_dependency(&self, group_id: &str, artifact_id: &str) -> bool {
+        self.find(group_id, artifact_id).is_some()
+    }
+
+    /// 构建脚本中`name = "value"`、`name 'value'`或`name.set("value")`形式的属性
+    pub fn property(&self, name: &str) -> Option<String> {
+        self.lines.iter().find_map(|line| property_value(line, name))
+    }
+
+    /// sourceCompatibility或toolchain中声明的Java版本
+    pub fn java_version(&self) -> Option<String> {
+        if let Some(version) = self.property("sourceCompatibility") {
+            return Some(version);
+        }
+
+        self.lines.iter().find_map(|line| {
+            let code = code_part(line);
+            if let Some(index) = code.find("JavaLanguageVersion.of(") {
+                let digits = leading_token(&code[index + "JavaLanguageVersion.of(".len()..], |c| {
+                    c.is_ascii_digit()
+                });
+                return (!digits.is_empty()).then(|| digits.to_string());
+            }
+            if let Some(index) = code.find("JavaVersion.VERSION_") {
+                let version = leading_token(&code[index + "JavaVersion.VERSION_".len()..], |c| {
+                    c.is_ascii_digit() || c == '_'
+                });
+                return (!version.is_empty()).then(|| version.replace('_', "."));
+            }
+            let rest = code.trim().strip_prefix("sourceCompatibility")?;
+            let version = leading_token(rest.trim_start().strip_prefix('=')?.trim_start(), |c| {
+                c.is_ascii_digit() || c == '.'
+            });
+            (!version.is_empty()).then(|| version.to_string())
+        })
+    }
+
+    /// 在顶层dependencies块中添加依赖，没有该块时在文件末尾创建
+    ///
+    /// 新语句放在同一配置的最后一个依赖之后，沿用已有依赖的缩进和引号
+    pub fn add_dependency(
+        &mut self,
+        configuration: &str,
+        group_id: &str,
+        artifact_id: &str,
+        version: Option<&str>,
+    ) {
+        let coordinate = match version {
+            Some(version) => format!("{}:{}:{}", group_id, artifact_id, version),
+            None => format!("{}:{}", group_id, artifact_id),
+        };
+        let quote = if self.kotlin {
+            '"'
+        } else {
+            self.entries()
+                .iter()
+                .find_map(|entry| match entry.notation {
+                    Notation::Coordinate { quote, .. } => Some(quote),
+                    _ => None,
+                })
+                .unwrap_or('\'')
+        };
+        self.insert_statement(configuration, &format!("{}{}{}", quote, coordinate, quote));
+    }
+
+    /// 在版本目录中添加库 (已有同一坐标的库时沿用其别名)，构建脚本中以`libs.xxx`引用
+    ///
+    /// 返回构建脚本中使用的访问器
+    pub fn add_catalog_dependency(
+        &mut self,
+        configuration: &str,
+        alias: Option<&str>,
+        group_id: &str,
+        artifact_id: &str,
+        version: &str,
+    ) -> Result<String> {
+        let catalog = self
+            .catalog
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("找不到版本目录 gradle/libs.versions.toml"))?;
+
+        let alias = match catalog.find_library(group_id, artifact_id) {
+            Some(library) => library.alias,
+            None => {
+                let alias = alias
+                    .map(str::to_string)
+                    .unwrap_or_else(|| default_alias(catalog, group_id, artifact_id));
+                catalog.add_library(&alias, group_id, artifact_id, version)?;
+                self.catalog_changed = true;
+                alias
+            }
+        };
+
+        let accessor = format!("libs.{}", to_accessor(&alias));
+        self.insert_statement(configuration, &accessor);
+        Ok(accessor)
+    }
+
+    /// 移除依赖语句，返回是否找到
+    pub fn remove_dependency(&mut self, group_id: &str, artifact_id: &str) -> Result<bool> {
+        let entries: Vec<Entry> = self
+            .entries()
+            .into_iter()
+            .filter(|entry| matches(&entry.dependency, group_id, artifact_id))
+            .collect();
+
+        if let Some(Notation::Bundle(bundle)) = entries.iter().map(|entry| &entry.notation).find(|n| matches!(n, Notation::Bundle(_))) {
+            return Err(anyhow::anyhow!(
+                "{}:{} 通过 libs.bundles.{} 引用，请在gradle/libs.versions.toml中修改该bundle",
+                group_id,
+                artifact_id,
+                bundle
+            ));
+        }
+
+        for entry in entries.iter().rev() {
+            self.lines.remove(entry.line);
+        }
+        Ok(!entries.is_empty())
+    }
+
+    /// 修改依赖的版本，通过`libs.xxx`引用时修改版本目录
+    pub fn set_version(
+        &mut self,
+        group_id: &str,
+        artifact_id: &str,
+        version: &str,
+    ) -> Result<GradleVersionChange> {
+        let entry = self.find(group_id, artifact_id).ok_or_else(|| {
+            anyhow::anyhow!(
+                "{}中没有声明依赖 {}:{}",
+                self.file_name(),
+                group_id,
+                artifact_id
+            )
+        })?;
+        let old_version = entry.dependency.version.clone();
+
+        if let Some(alias) = &entry.dependency.alias {
+            let catalog = self.catalog.as_mut().expect("通过版本目录解析的依赖");
+            let change = catalog.set_library_version(alias, version)?;
+            self.catalog_changed = true;
+            return Ok(GradleVersionChange {
+                old_version,
+                catalog: Some(change),
+            });
+        }
+
+        if old_version.as_deref().is_some_and(|old| old.contains('$')) {
+            return Err(anyhow::anyhow!(
+                "{}:{} 的版本由变量定义 ({})，请直接修改该变量",
+                group_id,
+                artifact_id,
+                old_version.unwrap_or_default()
+            ));
+        }
+
+        let line = &self.lines[entry.line];
+        let updated = match &entry.notation {
+            Notation::Coordinate { literal, quote } => {
+                let mut parts: Vec<&str> = literal.split(':').collect();
+                if parts.len() > 2 {
+                    parts[2] = version;
+                } else {
+                    parts.push(version);
+                }
+                line.replacen(
+                    &format!("{}{}{}", quote, literal, quote),
+                    &format!("{}{}{}", quote, parts.join(":"), quote),
+                    1,
+                )
+            }
+            Notation::Named => replace_named_version(line, version).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "{}:{} 没有写version参数，请在{}中手动添加",
+                    group_id,
+                    artifact_id,
+                    self.file_name()
+                )
+            })?,
+            Notation::Catalog | Notation::Bundle(_) => unreachable!("版本目录中的依赖已在上面处理"),
+        };
+
+        self.lines[entry.line] = updated;
+        Ok(GradleVersionChange {
+            old_version,
+            catalog: None,
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let mut content = self.content();
+        if self.trailing_newline {
+            content.push('\n');
+        }
+        fs::write(&self.path, content)
+            .with_context(|| format!("无法写入 {}", self.path.display()))?;
+
+        if let Some(catalog) = self.catalog.as_ref().filter(|_| self.catalog_changed) {
+            catalog.save()?;
+        }
+        Ok(())
+    }
+
+    fn find(&self, group_id: &str, artifact_id: &str) -> Option<Entry> {
+        self.entries()
+            .into_iter()
+            .find(|entry| matches(&entry.dependency, group_id, artifact_id))
+    }
+
+    /// 把`configuration(argument)`或`configuration argument`插入顶层dependencies块
+    fn insert_statement(&mut self, configuration: &str, argument: &str) {
+        let statement = if self.kotlin {
+            format!("{}({})", configuration, argument)
+        } else {
+            format!("{} {}", configuration, argument)
+        };
+        if let Some((start, end)) = self.block("dependencies") {
+            if start == end {
+                self.split_one_line_block(start);
+            }
+        }
+        let entries = self.entries();
+
+        match self.block("dependencies") {
+            Some((start, end)) => {
+                let indent = entries
+                    .first()
+                    .map(|entry| line_indent(&self.lines[entry.line]).to_string())
+                    .unwrap_or_else(|| format!("{}    ", line_indent(&self.lines[start])));
+                let index = entries
+                    .iter()
+                    .rev()
+                    .find(|entry| entry.dependency.configuration == configuration)
+                    .or_else(|| entries.last())
+                    .map(|entry| entry.line + 1)
+                    .unwrap_or(end);
+                self.lines.insert(index, format!("{}{}", indent, statement));
+            }
+            _ => {
+                if self.lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                    self.lines.push(String::new());
+                }
+                self.lines.push("dependencies {".to_string());
+                self.lines.push(format!("    {}", statement));
+                self.lines.push("}".to_string());
+            }
+        }
+    }
+
+    /// 把单行的`name { ... }`拆成多行，便于在其中插入语句
+    fn split_one_line_block(&mut self, index: usize) {
+        let line = self.lines[index].clone();
+        let code = code_part(&line);
+        let (Some(open), Some(close)) = (code.find('{'), code.rfind('}')) else {
+            return;
+        };
+        let indent = line_indent(&line);
+        let inner = code[open + 1..close].trim();
+
+        let mut replacement = vec![line[..=open].to_string()];
+        if !inner.is_empty() {
+            replacement.push(format!("{}    {}", indent, inner));
+        }
+        replacement.push(format!("{}{}", indent, &line[close..]));
+        self.lines.splice(index..=index, replacement);
+    }
+
+    /// 顶层`name { }`块的起止行，起始行是`{`所在的行，允许`{`写在块名的下一行
+    fn block(&self, name: &str) -> Option<(usize, usize)> {
+        let mut depth = 0;
+        let mut start = None;
+        let mut header = false;
+
+        for (index, line) in self.lines.iter().enumerate() {
+            let code = code_part(line);
+            if depth == 0 && start.is_none() {
+                let trimmed = code.trim();
+                if header && !trimmed.is_empty() {
+                    header = false;
+                    if trimmed.starts_with('{') {
+                        start = Some(index);
+                    }
+                }
+                match trimmed.strip_prefix(name).map(str::trim_start) {
+                    Some(rest) if rest.starts_with('{') => start = Some(index),
+                    Some("") => header = true,
+                    _ => {}
+                }
+            }
+            depth += brace_delta(code);
+            if let Some(start) = start {
+                if depth <= 0 {
+                    return Some((start, index));
+                }
+            }
+        }
+        None
+    }
+
+    fn entries(&self) -> Vec<Entry> {
+        let Some((start, end)) = self.block("dependencies") else {
+            return Vec::new();
+        };
+
+        let mut entries = Vec::new();
+        if start == end {
+            // 单行的 dependencies { ... }
+            let code = code_part(&self.lines[start]);
+            if let (Some(open), Some(close)) = (code.find('{'), code.rfind('}')) {
+                for (dependency, notation) in parse_statement(&code[open + 1..close], self.catalog.as_ref()) {
+                    entries.push(Entry {
+                        line: start,
+                        dependency,
+                        notation,
+                    });
+                }
+            }
+            return entries;
+        }
+
+        let mut depth = 1;
+        for index in start + 1..end {
+            let code = code_part(&self.lines[index]);
+            if depth == 1 {
+                for (dependency, notation) in parse_statement(code, self.catalog.as_ref()) {
+                    entries.push(Entry {
+                        line: index,
+                        dependency,
+                        notation,
+                    });
+                }
+            }
+            depth += brace_delta(code);
+        }
+        entries
+    }
+}
+
+fn matches(dependency: &GradleDependency, group_id: &str, artifact_id: &str) -> bool {
+    dependency.group_id == group_id && dependency.artifact_id == artifact_id
+}
+
+/// 默认使用artifactId作为别名，已被占用时加上groupId的最后一段
+fn default_alias(catalog: &VersionCatalog, group_id: &str, artifact_id: &str) -> String {
+    let alias = artifact_id.to_lowercase().replace('.', "-");
+    if catalog.library(&to_accessor(&alias)).is_none() {
+        return alias;
+    }
+    let prefix = group_id.rsplit('.').next().unwrap_or(group_id);
+    format!("{}-{}", prefix.to_lowercase(), alias)
+}
+
+/// 解析一行依赖语句，例如`implementation("g:a:v")`、`api 'g:a:v'`、`testImplementation(libs.junit)`
+///
+/// `libs.bundles.xxx`展开为bundle中的每个库
+fn parse_statement(code: &str, catalog: Option<&VersionCatalog>) -> Vec<(GradleDependency, Notation)> {
+    let code = code.trim();
+    let configuration = leading_token(code, |c| c.is_ascii_alphanumeric() || c == '_');
+    if configuration.is_empty() {
+        return Vec::new();
+    }
+    let Some(argument) = statement_argument(&code[configuration.len()..]) else {
+        return Vec::new();
+    };
+
+    let dependency = |group_id: &str, artifact_id: &str, version: Option<&str>| GradleDependency {
+        configuration: configuration.to_string(),
+        group_id: group_id.to_string(),
+        artifact_id: artifact_id.to_string(),
+        version: version.filter(|v| !v.is_empty()).map(str::to_string),
+        alias: None,
+    };
+    let from_catalog = |library: CatalogLibrary, notation: Notation| {
+        let mut dependency = dependency(&library.group_id, &library.artifact_id, library.version.as_deref());
+        dependency.alias = Some(library.alias);
+        (dependency, notation)
+    };
+
+    if let Some((literal, quote)) = quoted(argument) {
+        let parts: Vec<&str> = literal.split(':').collect();
+        if parts.len() < 2 {
+            return Vec::new();
+        }
+        let notation = Notation::Coordinate {
+            literal: literal.to_string(),
+            quote,
+        };
+        return vec![(dependency(parts[0], parts[1], parts.get(2).copied()), notation)];
+    }
+
+    if let Some(accessor) = argument.strip_prefix("libs.") {
+        let accessor = leading_token(accessor, |c| c.is_ascii_alphanumeric() || c == '.' || c == '_');
+        let Some(catalog) = catalog else {
+            return Vec::new();
+        };
+        if let Some(bundle) = accessor.strip_prefix("bundles.") {
+            return catalog
+                .bundle(bundle)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|library| from_catalog(library, Notation::Bundle(bundle.to_string())))
+                .collect();
+        }
+        return catalog
+            .library(accessor)
+            .map(|library| from_catalog(library, Notation::Catalog))
+            .into_iter()
+            .collect();
+    }
+
+    let (Some(group_id), Some(artifact_id)) = (
+        named_argument(argument, "group"),
+        named_argument(argument, "name"),
+    ) else {
+        return Vec::new();
+    };
+    let version = named_argument(argument, "version");
+    vec![(dependency(&group_id, &artifact_id, version.as_deref()), Notation::Named)]
+}
+
+/// 依赖语句的参数，去掉括号以及platform()等包装
+fn statement_argument(rest: &str) -> Option<&str> {
+    let rest = rest.trim_start();
+    let mut argument = match rest.strip_prefix('(') {
+        Some(inner) => &inner[..closing_paren(inner)?],
+        None => rest,
+    }
+    .trim();
+    for wrapper in ["platform(", "enforcedPlatform(", "testFixtures("] {
+        if let Some(inner) = argument.strip_prefix(wrapper) {
+            argument = inner[..closing_paren(inner)?].trim();
+        }
+    }
+    Some(argument)
+}
+
+/// `key: 'value'`或`key = "value"`形式的命名参数
+fn named_argument(argument: &str, key: &str) -> Option<String> {
+    argument.split(',').find_map(|part| {
+        let rest = part.trim().strip_prefix(key)?.trim_start();
+        let rest = rest.strip_prefix(':').or_else(|| rest.strip_prefix('='))?;
+        quoted(rest.trim()).map(|(value, _)| value.to_string())
+    })
+}
+
+fn replace_named_version(line: &str, version: &str) -> Option<String> {
+    let key = line.find("version")?;
+    let open = key + line[key..].find(['\'', '"'])?;
+    let quote = line[open..].chars().next()?;
+    let close = open + 1 + line[open + 1..].find(quote)?;
+    Some(format!("{}{}{}", &line[..open + 1], version, &line[close..]))
+}
+
+/// `name = "value"`、`name 'value'`、`name.set("value")`或`name("value")`中的value
+fn property_value(line: &str, name: &str) -> Option<String> {
+    let rest = code_part(line).trim().strip_prefix(name)?;
+    let rest = rest.strip_prefix(".set").unwrap_or(rest).trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim_start();
+    let rest = rest.strip_prefix('(').unwrap_or(rest).trim_start();
+    quoted(rest).map(|(value, _)| value.to_string())
+}
+
+/// 以引号开头的字符串字面量及其引号
+fn quoted(text: &str) -> Option<(&str, char)> {
+    let quote = text.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let end = text[1..].find(quote)?;
+    Some((&text[1..1 + end], quote))
+}
+
+/// 文本中所有的字符串字面量
+fn string_literals(text: &str) -> Vec<&str> {
+    let mut literals = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(['\'', '"']) {
+        let Some((literal, _)) = quoted(&rest[start..]) else {
+            break;
+        };
+        literals.push(literal);
+        rest = &rest[start + literal.len() + 2..];
+    }
+    literals
+}
+
+/// 统一写成以`:`开头的项目路径
+fn project_path(path: &str) -> String {
+    if path.starts_with(':') {
+        path.to_string()
+    } else {
+        format!(":{}", path)
+    }
+}
+
+fn leading_token(text: &str, accept: impl Fn(char) -> bool) -> &str {
+    let end = text.find(|c: char| !accept(c)).unwrap_or(text.len());
+    &text[..end]
+}
+
+/// 去掉行尾的`//`注释，字符串中的`//`不算
+fn code_part(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (index, c) in line.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c == '/' && previous == '/' => return &line[..index - 1],
+            None => {}
+        }
+        previous = c;
+    }
+    line
+}
+
+/// 字符串之外的`{`与`}`数量之差
+fn brace_delta(code: &str) -> i32 {
+    let mut quote = None;
+    let mut delta = 0;
+    for c in code.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c == '{' => delta += 1,
+            None if c == '}' => delta -= 1,
+            None => {}
+        }
+    }
+    delta
+}
+
+/// 与已经读过的`(`配对的`)`的位置
+fn closing_paren(text: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut depth = 0;
+    for (index, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c == '(' => depth += 1,
+            None if c == ')' && depth == 0 => return Some(index),
+            None if c == ')' => depth -= 1,
+            None => {}
+        }
+    }
+    None
+}
+
+fn line_indent(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
@@ -0,0 +1,516 @@
+use crate::download::Downloader;
+use crate::pom::{parse_pom_info, PomInfo};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const INDEX_VERSION: u32 = 2;
+
+/// 本地缓存构件索引，保存在缓存目录 (配置项`cache.dir`，默认~/.jx/cache) 下的index.json
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchIndex {
+    pub version: u32,
+    pub artifacts: Vec<IndexedArtifact>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedArtifact {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classifier: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub licenses: Vec<String>,
+    pub classes: Vec<String>,
+    pub jar_path: String,
+    pub jar_size: u64,
+    pub jar_modified: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub artifact: IndexedArtifact,
+    pub score: u32,
+    pub matched_classes: Vec<String>,
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            artifacts: Vec::new(),
+        }
+    }
+
+    /// 索引与它描述的缓存放在一起，清理缓存时一并删除
+    pub fn index_path(cache_dir: &Path) -> PathBuf {
+        cache_dir.join("index.json")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+
+        let content = fs::read_to_string(path)?;
+        match serde_json::from_str::<SearchIndex>(&content) {
+            Ok(index) if index.version == INDEX_VERSION => Ok(index),
+            // 旧版本或损坏的索引直接重建
+            _ => Ok(Self::new()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// 加载索引并与缓存目录同步，只重新读取新增或变化的jar
+    pub fn load_and_refresh(force: bool) -> Result<Self> {
+        let downloader = Downloader::new();
+        let path = Self::index_path(downloader.cache_dir());
+        let mut index = if force { Self::new() } else { Self::load(&path)? };
+
+        if index.refresh(downloader.cache_dir())? {
+            index.save(&path)?;
+        }
+
+        Ok(index)
+    }
+
+    /// 返回索引是否发生变化
+    pub fn refresh(&mut self, cache_dir: &Path) -> Result<bool> {
+        let mut existing: HashMap<String, IndexedArtifact> = self
+            .artifacts
+            .drain(..)
+            .map(|artifact| (artifact.jar_path.clone(), artifact))
+            .collect();
+        let mut changed = false;
+
+        if cache_dir.exists() {
+            for jar_path in find_cached_jars(cache_dir)? {
+                let metadata = fs::metadata(&jar_path)?;
+                let size = metadata.len();
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                let key = jar_path.to_string_lossy().to_string();
+
+                match existing.remove(&key) {
+                    Some(artifact) if artifact.jar_size == size && artifact.jar_modified == modified => {
+                        self.artifacts.push(artifact);
+                    }
+                    _ => {
+                        changed = true;
+                        match index_jar(&jar_path, size, modified) {
+                            Ok(Some(artifact)) => self.artifacts.push(artifact),
+                            Ok(None) => {}
+                            Err(e) => eprintln!("⚠️ 索引 {} 失败: {}", jar_path.display(), e),
+                        }
+                    }
+                }
+            }
+        }
+
+        // 剩余的条目对应的jar已被删除
+        changed |= !existing.is_empty();
+
+        self.artifacts.sort_by(|a, b| {
+            (&a.group_id, &a.artifact_id, &a.version).cmp(&(&b.group_id, &b.artifact_id, &b.version))
+        });
+
+        Ok(changed)
+    }
+
+    /// 按相关度排序的搜索，支持坐标、名称、描述和全限定类名
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let terms: Vec<String> = query.split_whitespace().map(|t| t.to_lowercase()).collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut best: HashMap<(String, String), SearchHit> = HashMap::new();
+
+        for artifact in &self.artifacts {
+            let mut score = 0;
+            let mut matched_classes = Vec::new();
+
+            for term in &terms {
+                let (term_score, classes) = score_term(artifact, term);
+                if term_score == 0 {
+                    // 所有关键词都必须命中
+                    score = 0;
+                    break;
+                }
+                score += term_score;
+                matched_classes.extend(classes);
+            }
+
+            if score == 0 {
+                continue;
+            }
+
+            matched_classes.sort();
+            matched_classes.dedup();
+
+            let key = (artifact.group_id.clone(), artifact.artifact_id.clone());
+            let replace = match best.get(&key) {
+                Some(hit) => {
+                    score > hit.score
+                        || (score == hit.score
+                            && compare_versions(&artifact.version, &hit.artifact.version)
+                                == std::cmp::Ordering::Greater)
+                }
+                None => true,
+            };
+
+            if replace {
+                best.insert(
+                    key,
+                    SearchHit {
+                        artifact: artifact.clone(),
+                        score,
+                        matched_classes,
+                    },
+                );
+            }
+        }
+
+        let mut hits: Vec<SearchHit> = best.into_values().collect();
+        hits.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.artifact.group_id.cmp(&b.artifact.group_id))
+                .then_with(|| a.artifact.artifact_id.cmp(&b.artifact.artifact_id))
+        });
+        hits.truncate(limit);
+        hits
+    }
+
+    /// 查找包含指定类的构件，类名不含包名时按简单类名匹配
+    pub fn find_class(&self, class_name: &str) -> Vec<(IndexedArtifact, String)> {
+        let mut found = Vec::new();
+
+        for artifact in &self.artifacts {
+            for class in &artifact.classes {
+                if class_matches(class, class_name) {
+                    found.push((artifact.clone(), class.clone()));
+                }
+            }
+        }
+
+        // 同一构件的新版本排在前面
+        found.sort_by(|(a, _), (b, _)| {
+            (&a.group_id, &a.artifact_id)
+                .cmp(&(&b.group_id, &b.artifact_id))
+                .then_with(|| compare_versions(&b.version, &a.version))
+        });
+        found
+    }
+
+    /// 同一groupId:artifactId在缓存中的全部版本
+    pub fn versions_of(&self, group_id: &str, artifact_id: &str) -> Vec<String> {
+        let mut versions: Vec<String> = self
+            .artifacts
+            .iter()
+            .filter(|a| a.group_id == group_id && a.artifact_id == artifact_id)
+            .map(|a| a.version.clone())
+            .collect();
+        versions.sort_by(|a, b| compare_versions(b, a));
+        versions.dedup();
+        versions
+    }
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn score_term(artifact: &IndexedArtifact, term: &str) -> (u32, Vec<String>) {
+    let group_id = artifact.group_id.to_lowercase();
+    let artifact_id = artifact.artifact_id.to_lowercase();
+    let coordinate = format!("{}:{}", group_id, artifact_id);
+    let mut score = 0;
+
+    if artifact_id == term || coordinate == term {
+        score += 100;
+    } else if artifact_id.starts_with(term) {
+        score += 60;
+    } else if artifact_id.contains(term) {
+        score += 40;
+    }
+
+    if group_id == term {
+        score += 30;
+    } else if group_id.contains(term) {
+        score += 20;
+    }
+
+    if let Some(ref name) = artifact.name {
+        if name.to_lowercase().contains(term) {
+            score += 10;
+        }
+    }
+
+    if let Some(ref description) = artifact.description {
+        if description.to_lowercase().contains(term) {
+            score += 5;
+        }
+    }
+
+    // 类名匹配: 全限定名完全一致的权重最高
+    let mut matched_classes = Vec::new();
+    let mut class_score = 0;
+    for class in &artifact.classes {
+        let class_lower = class.to_lowercase();
+        let simple_name = class_lower.rsplit('.').next().unwrap_or(&class_lower);
+
+        if class_lower == term {
+            class_score = class_score.max(120);
+            matched_classes.push(class.clone());
+        } else if simple_name == term {
+            class_score = class_score.max(50);
+            matched_classes.push(class.clone());
+        } else if term.contains('.') && class_lower.starts_with(term) {
+            class_score = class_score.max(25);
+            matched_classes.push(class.clone());
+        }
+    }
+    score += class_score;
+
+    (score, matched_classes)
+}
+
+pub fn class_matches(class: &str, query: &str) -> bool {
+    if query.contains('.') {
+        class == query
+    } else {
+        class.rsplit('.').next() == Some(query)
+    }
+}
+
+/// 缓存目录结构为 groupId/artifactId/artifactId-version[-classifier].jar
+fn find_cached_jars(cache_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut jars = Vec::new();
+
+    for entry in walkdir::WalkDir::new(cache_dir).min_depth(3).max_depth(3) {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("jar") {
+            jars.push(path.to_path_buf());
+        }
+    }
+
+    jars.sort();
+    Ok(jars)
+}
+
+fn index_jar(jar_path: &Path, size: u64, modified: u64) -> Result<Option<IndexedArtifact>> {
+    let artifact_dir = jar_path.parent().context("无效的缓存路径")?;
+    let group_dir = artifact_dir.parent().context("无效的缓存路径")?;
+    let group_id = group_dir.file_name().unwrap().to_string_lossy().to_string();
+    let artifact_id = artifact_dir.file_name().unwrap().to_string_lossy().to_string();
+    let filename = jar_path.file_name().unwrap().to_string_lossy().to_string();
+
+    let prefix = format!("{}-", artifact_id);
+    if !filename.starts_with(&prefix)
+        || filename.ends_with("-sources.jar")
+        || filename.ends_with("-javadoc.jar")
+    {
+        return Ok(None);
+    }
+    let stem = &filename[prefix.len()..filename.len() - ".jar".len()];
+
+    let file = fs::File::open(jar_path)?;
+    let mut archive = zip::ZipArchive::new(file).context("无法读取jar文件")?;
+    let classes = list_archive_classes(&mut archive);
+
+    let (version, classifier) = split_version_classifier(artifact_dir, &artifact_id, stem, &mut archive);
+
+    // 优先使用缓存中的.pom文件，其次是jar内嵌的pom.xml
+    let pom_path = artifact_dir.join(format!("{}-{}.pom", artifact_id, version));
+    let embedded_pom = format!("META-INF/maven/{}/{}/pom.xml", group_id, artifact_id);
+    let pom_content = if pom_path.exists() {
+        fs::read_to_string(&pom_path).ok()
+    } else {
+        archive.by_name(&embedded_pom).ok().and_then(|mut entry| {
+            let mut content = String::new();
+            entry.read_to_string(&mut content).ok().map(|_| content)
+        })
+    };
+    let pom_info = pom_content
+        .and_then(|content| parse_pom_info(&content).ok())
+        .unwrap_or_else(PomInfo::default);
+
+    Ok(Some(IndexedArtifact {
+        group_id,
+        artifact_id,
+        version,
+        classifier,
+        name: pom_info.name,
+        description: pom_info.description,
+        licenses: pom_info.licenses,
+        classes,
+        jar_path: jar_path.to_string_lossy().to_string(),
+        jar_size: size,
+        jar_modified: modified,
+    }))
+}
+
+/// 把文件名中的`version[-classifier]`拆开。版本号本身可能含`-`，
+/// 所以依次参考同目录的`artifactId-<version>.pom`和jar内的pom.properties
+fn split_version_classifier<R: Read + std::io::Seek>(
+    artifact_dir: &Path,
+    artifact_id: &str,
+    stem: &str,
+    archive: &mut zip::ZipArchive<R>,
+) -> (String, Option<String>) {
+    let split = |version: &str| -> Option<(String, Option<String>)> {
+        if stem == version {
+            Some((version.to_string(), None))
+        } else {
+            let classifier = stem.strip_prefix(version)?.strip_prefix('-')?;
+            Some((version.to_string(), Some(classifier.to_string())))
+        }
+    };
+
+    let prefix = format!("{}-", artifact_id);
+    let mut pom_versions: Vec<String> = fs::read_dir(artifact_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_prefix(&prefix)?.strip_suffix(".pom").map(str::to_string)
+        })
+        .collect();
+    // 1.0 和 1.0-beta 同时存在时取最长的匹配
+    pom_versions.sort_by_key(|version| std::cmp::Reverse(version.len()));
+    if let Some(found) = pom_versions.iter().find_map(|version| split(version)) {
+        return found;
+    }
+
+    let properties_name = archive
+        .file_names()
+        .find(|name| name.starts_with("META-INF/maven/") && name.ends_with("/pom.properties"))
+        .map(str::to_string);
+    let mut content = String::new();
+    if let Some(mut entry) = properties_name.and_then(|name| archive.by_name(&name).ok()) {
+        let _ = entry.read_to_string(&mut content);
+    }
+    let embedded = content
+        .lines()
+        .find_map(|line| line.strip_prefix("version="))
+        .and_then(|version| split(version.trim()));
+
+    embedded.unwrap_or_else(|| (stem.to_string(), None))
+}
+
+/// 列出jar中的全部类名(全限定名)，跳过匿名内部类和module-info
+pub fn list_jar_classes(jar_path: &Path) -> Result<Vec<String>> {
+    let file = fs::File::open(jar_path)?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("无法读取jar文件: {}", jar_path.display()))?;
+    Ok(list_archive_classes(&mut archive))
+}
+
+/// 从jar内的 META-INF/maven/*/*/pom.properties 读取构件坐标
+pub fn read_jar_coordinates(jar_path: &Path) -> Option<(String, String, String)> {
+    let file = fs::File::open(jar_path).ok()?;
+    let mut archive = zip::ZipArchive::new(file).ok()?;
+    let properties_name = archive
+        .file_names()
+        .find(|name| name.starts_with("META-INF/maven/") && name.ends_with("/pom.properties"))?
+        .to_string();
+
+    let mut content = String::new();
+    archive
+        .by_name(&properties_name)
+        .ok()?
+        .read_to_string(&mut content)
+        .ok()?;
+
+    let mut properties = HashMap::new();
+    for line in content.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            properties.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    Some((
+        properties.remove("groupId")?,
+        properties.remove("artifactId")?,
+        properties.remove("version")?,
+    ))
+}
+
+fn list_archive_classes<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>) -> Vec<String> {
+    let mut classes: Vec<String> = archive
+        .file_names()
+        .filter(|name| name.ends_with(".class") && !name.starts_with("META-INF/"))
+        .filter_map(|name| {
+            let class = name.trim_end_matches(".class").replace('/', ".");
+            let simple = class.rsplit('.').next().unwrap_or(&class);
+            let anonymous = simple
+                .split('$')
+                .skip(1)
+                .any(|part| part.chars().all(|c| c.is_ascii_digit()));
+
+            if anonymous || simple == "module-info" || simple == "package-info" {
+                None
+            } else {
+                Some(class)
+            }
+        })
+        .collect();
+
+    classes.sort();
+    classes
+}
+
+/// 按数字段比较版本号，非数字段按字符串比较
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let split = |v: &str| -> Vec<String> {
+        v.split(['.', '-'])
+            .map(|s| s.to_string())
+            .collect()
+    };
+    let a_parts = split(a);
+    let b_parts = split(b);
+
+    for (x, y) in a_parts.iter().zip(b_parts.iter()) {
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if ordering != std::cmp::Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    // 1.0 > 1.0-SNAPSHOT，1.0.1 > 1.0
+    let (longer, ordering) = if a_parts.len() > b_parts.len() {
+        (&a_parts, std::cmp::Ordering::Greater)
+    } else {
+        (&b_parts, std::cmp::Ordering::Less)
+    };
+    match longer.get(a_parts.len().min(b_parts.len())) {
+        Some(extra) if extra.parse::<u64>().is_err() => ordering.reverse(),
+        Some(_) => ordering,
+        None => std::cmp::Ordering::Equal,
+    }
+}
@@ -1,33 +1,53 @@
 use anyhow::Result;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
 pub struct Installer {
     lib_dir: String,
+    offline: bool,
 }
 
 impl Installer {
     pub fn new() -> Self {
         let lib_dir = "lib".to_string();
-        Self { lib_dir }
+        Self {
+            lib_dir,
+            offline: false,
+        }
+    }
+
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
     }
 
-    pub async fn install_dependencies(&self, dependencies: &[crate::dependency::Dependency]) -> Result<()> {
+    /// 下载到缓存后先用`verify`检查每个jar (参数为依赖的下标和缓存路径)，全部通过才复制到lib目录
+    pub async fn install_dependencies(
+        &self,
+        dependencies: &[crate::dependency::Dependency],
+        verify: impl Fn(usize, &Path) -> Result<()>,
+    ) -> Result<()> {
         println!("📦 开始安装依赖...");
-        
+
+        if self.offline {
+            crate::download::Downloader::new().ensure_cached(dependencies)?;
+        }
+
+        // 下载依赖
+        let downloader = crate::download::Downloader::new().offline(self.offline);
+        let cache_paths = downloader.download_all(dependencies).await?;
+        for (index, cache_path) in cache_paths.iter().enumerate() {
+            verify(index, Path::new(cache_path))?;
+        }
+
         // 创建lib目录
         fs::create_dir_all(&self.lib_dir)?;
 
         println!("正在安装 {} 个依赖...", dependencies.len());
 
-        for (i, dep) in dependencies.iter().enumerate() {
+        for (i, (dep, cache_path)) in dependencies.iter().zip(cache_paths).enumerate() {
             println!("[{}/{}] 安装 {}", i + 1, dependencies.len(), dep.coordinate());
-            
-            // 下载依赖
-            let downloader = crate::download::Downloader::new();
-            let cache_path = downloader
-                .download_dependency(&dep.group_id, &dep.artifact_id, &dep.version, dep.classifier.as_deref())
-                .await?;
 
             // 复制到lib目录
             let lib_path = format!("{}/{}", self.lib_dir, dep.filename());
@@ -86,6 +106,111 @@ impl Installer {
     }
 }
 
+/// 项目依赖: 优先使用jx.lock中锁定的完整依赖，没有锁文件时使用jx.toml中的直接依赖
+pub fn project_dependencies(project_dir: &Path) -> Result<Vec<crate::dependency::Dependency>> {
+    let lock_path = crate::workspace::lock_path(project_dir)?;
+    if lock_path.exists() {
+        println!("使用锁文件: jx.lock");
+        let lock_file = crate::lock::LockFile::load(&lock_path)?;
+        return Ok(lock_file
+            .dependencies
+            .values()
+            .map(|dep| dep.to_dependency())
+            .collect());
+    }
+
+    if project_dir.join("jx.toml").exists() {
+        Ok(crate::workspace::lock_manifest(project_dir)?.1)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// 安装到项目lib/中的锁定依赖
+///
+/// 工作区共享jx.lock，成员只安装从自己的依赖 (包括所有profile中的依赖) 出发能到达的部分；
+/// 不在工作区中或在工作区根目录时为锁文件中的全部依赖
+pub fn lib_dependencies(
+    project_dir: &Path,
+    lock_file: &crate::lock::LockFile,
+) -> Result<Vec<crate::lock::LockedDependency>> {
+    if let Some(workspace) = crate::workspace::Workspace::discover(project_dir)? {
+        if let Some(member) = workspace.member_at(project_dir) {
+            return Ok(lock_file.reachable_from(&member.config.lock_dependencies()));
+        }
+    }
+    Ok(lock_file.dependencies.values().cloned().collect())
+}
+
+/// `--production` 时安装的依赖: 不包括test、provided依赖和注解处理器
+pub fn is_production(dep: &crate::lock::LockedDependency) -> bool {
+    !matches!(
+        crate::dependency::DependencyScope::from_name(&dep.scope),
+        crate::dependency::DependencyScope::Test
+            | crate::dependency::DependencyScope::Provided
+            | crate::dependency::DependencyScope::AnnotationProcessor
+    )
+}
+
+/// 离线模式下在构建、运行、测试前检查项目依赖是否都已缓存
+pub fn ensure_offline_ready(project_dir: &Path) -> Result<()> {
+    let dependencies = project_dependencies(project_dir)?;
+    crate::download::Downloader::new().ensure_cached(&dependencies)
+}
+
+/// 解析完整依赖图，下载到缓存并写入jx.lock
+pub async fn resolve_and_lock(
+    lock_path: &Path,
+    manifest: &[crate::dependency::Dependency],
+    pinned: HashMap<String, String>,
+    offline: bool,
+) -> Result<crate::lock::LockFile> {
+    println!("🔗 解析依赖图...");
+
+    // 注解处理器单独解析，它们的传递依赖不参与classpath的版本仲裁
+    let (processors, dependencies): (Vec<_>, Vec<_>) = manifest
+        .iter()
+        .cloned()
+        .partition(|dep| matches!(dep.scope, crate::dependency::DependencyScope::AnnotationProcessor));
+
+    let mut resolver = crate::resolve::DependencyResolver::new()
+        .offline(offline)
+        .pin_versions(pinned.clone());
+    let mut resolved = resolver.resolve_dependencies(&dependencies).await?;
+    let mut processor_resolver = crate::resolve::DependencyResolver::new()
+        .offline(offline)
+        .pin_versions(pinned);
+    if !processors.is_empty() {
+        resolved.extend(processor_resolver.resolve_dependencies(&processors).await?);
+    }
+
+    for conflict in resolver
+        .detect_conflicts()
+        .into_iter()
+        .chain(processor_resolver.detect_conflicts())
+    {
+        println!(
+            "  版本仲裁: {}:{} 使用 {}，忽略 {}",
+            conflict.group_id, conflict.artifact_id, conflict.versions[0], conflict.versions[1]
+        );
+    }
+    println!(
+        "解析完成: {} 个直接依赖，{} 个传递依赖",
+        manifest.len(),
+        resolved.len().saturating_sub(manifest.len())
+    );
+
+    let downloader = crate::download::Downloader::new().offline(offline);
+    downloader.download_all(&resolved).await?;
+
+    let mut lock_file = crate::lock::LockFile::from_resolver(&resolver, &downloader)?;
+    lock_file.merge(crate::lock::LockFile::from_resolver(&processor_resolver, &downloader)?);
+    lock_file.save(lock_path)?;
+    println!("🔒 已写入锁文件: jx.lock");
+
+    Ok(lock_file)
+}
+
 impl Default for Installer {
     fn default() -> Self {
         Self::new()
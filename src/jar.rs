@@ -0,0 +1,201 @@
+use anyhow::{Context, Result};
+use chrono::{Datelike, Timelike};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+pub const MANIFEST_PATH: &str = "META-INF/MANIFEST.MF";
+
+/// MANIFEST.MF的主属性，按加入的顺序写出
+pub struct Manifest {
+    attributes: Vec<(String, String)>,
+}
+
+impl Manifest {
+    pub fn new() -> Self {
+        Self {
+            attributes: vec![
+                ("Manifest-Version".to_string(), "1.0".to_string()),
+                ("Created-By".to_string(), format!("jx {}", env!("CARGO_PKG_VERSION"))),
+            ],
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.attributes.retain(|(existing, _)| existing != name);
+        self.attributes.push((name.to_string(), value.to_string()));
+    }
+
+    /// 每行不超过72字节，超出部分以空格开头续行，使用CRLF换行
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (name, value) in &self.attributes {
+            let line = format!("{}: {}", name, value);
+            let mut rest = line.as_str();
+            let mut limit = 72;
+            while rest.len() > limit {
+                // 不能在UTF-8字符中间断开
+                let mut split = limit;
+                while !rest.is_char_boundary(split) {
+                    split -= 1;
+                }
+                out.extend_from_slice(&rest.as_bytes()[..split]);
+                out.extend_from_slice(b"\r\n ");
+                rest = &rest[split..];
+                limit = 71;
+            }
+            out.extend_from_slice(rest.as_bytes());
+            out.extend_from_slice(b"\r\n");
+        }
+        out.extend_from_slice(b"\r\n");
+        out
+    }
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// jar中一个文件的内容
+pub enum Entry {
+    File(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+/// 可重现的jar: 条目按路径排序，目录条目自动补齐，时间戳固定
+///
+/// 时间戳取自`SOURCE_DATE_EPOCH`，没有设置时使用zip格式能表示的最早时间 1980-01-01
+pub struct Jar {
+    entries: BTreeMap<String, Entry>,
+}
+
+impl Jar {
+    pub fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    pub fn insert(&mut self, name: String, entry: Entry) {
+        self.entries.insert(name, entry);
+    }
+
+    /// 按相对路径加入目录中的所有文件，已有的同名条目会被覆盖
+    pub fn add_dir(&mut self, dir: &Path) -> Result<()> {
+        for entry in walkdir::WalkDir::new(dir) {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let name = entry
+                .path()
+                .strip_prefix(dir)?
+                .to_string_lossy()
+                .replace('\\', "/");
+            self.entries.insert(name, Entry::File(entry.into_path()));
+        }
+        Ok(())
+    }
+
+    /// 写出jar，MANIFEST.MF总是第一个文件条目
+    pub fn write(mut self, path: &Path, manifest: &Manifest) -> Result<()> {
+        self.entries.remove(MANIFEST_PATH);
+
+        let mut directories: Vec<String> = Vec::new();
+        for name in self.entries.keys() {
+            let mut end = 0;
+            while let Some(index) = name[end..].find('/') {
+                end += index + 1;
+                directories.push(name[..end].to_string());
+            }
+        }
+        directories.sort();
+        directories.dedup();
+        directories.retain(|dir| dir != "META-INF/");
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = fs::File::create(path).with_context(|| format!("无法创建 {}", path.display()))?;
+        let mut zip = ZipWriter::new(file);
+        let time = source_date()?;
+        let dir_options = FileOptions::default()
+            .last_modified_time(time)
+            .unix_permissions(0o755);
+        let file_options = FileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .last_modified_time(time)
+            .unix_permissions(0o644);
+
+        zip.add_directory("META-INF/", dir_options)?;
+        zip.start_file(MANIFEST_PATH, file_options)?;
+        zip.write_all(&manifest.to_bytes())?;
+
+        // 目录和文件合在一起按路径排序
+        let mut names: Vec<&str> = directories
+            .iter()
+            .map(String::as_str)
+            .chain(self.entries.keys().map(String::as_str))
+            .collect();
+        names.sort_unstable();
+        for name in names {
+            match self.entries.get(name) {
+                None => zip.add_directory(name, dir_options)?,
+                Some(Entry::File(source)) => {
+                    let bytes =
+                        fs::read(source).with_context(|| format!("无法读取 {}", source.display()))?;
+                    zip.start_file(name, file_options)?;
+                    zip.write_all(&bytes)?;
+                }
+                Some(Entry::Bytes(bytes)) => {
+                    zip.start_file(name, file_options)?;
+                    zip.write_all(bytes)?;
+                }
+            }
+        }
+        zip.finish().with_context(|| format!("无法写入 {}", path.display()))?;
+        Ok(())
+    }
+}
+
+impl Default for Jar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `SOURCE_DATE_EPOCH`对应的UTC时间，早于1980年时取1980-01-01
+fn source_date() -> Result<DateTime> {
+    let epoch = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(value) if !value.trim().is_empty() => value
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| anyhow::anyhow!("SOURCE_DATE_EPOCH 必须是秒数，当前为 `{}`", value))?,
+        _ => 0,
+    };
+    let time = chrono::DateTime::from_timestamp(epoch, 0)
+        .ok_or_else(|| anyhow::anyhow!("SOURCE_DATE_EPOCH 超出范围: {}", epoch))?
+        .naive_utc();
+
+    if time.year() < 1980 {
+        return Ok(DateTime::default());
+    }
+    DateTime::from_date_and_time(
+        time.year() as u16,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    )
+    .map_err(|_| anyhow::anyhow!("SOURCE_DATE_EPOCH 超出zip能表示的时间范围: {}", epoch))
+}
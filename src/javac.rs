@@ -0,0 +1,369 @@
+use crate::build_state::{hash_classpath_entry, hash_file, BuildState, Plan};
+use crate::classpath;
+use crate::commands::venv;
+use crate::config::JxConfig;
+use crate::dependency::{Dependency, DependencyScope};
+use crate::download::Downloader;
+use crate::profile::Profile;
+use crate::project::Project;
+use crate::workspace;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 注解处理器的jar和生成源码的目录
+struct Processors {
+    path: Vec<PathBuf>,
+    generated_dir: PathBuf,
+}
+
+/// 不经过Maven或Gradle，用javac编译`type = "jx"`的项目
+///
+/// 源码目录编译到`<target_dir>/classes`，资源目录原样复制过去。
+/// 上次构建的状态保存在`<target_dir>/jx-build-state.json`，只重新编译变化的源文件和引用了它们的源文件；
+/// 配置了注解处理器时总是全量编译，因为生成的类无法对应到项目中的源文件
+pub fn build(project_dir: &Path, config: &JxConfig, profile: &Profile) -> Result<()> {
+    let project = Project::from_directory(project_dir)?;
+    let target_dir = project_dir.join(&project.target_dir);
+    let output_dir = target_dir.join("classes");
+    let state_path = target_dir.join("jx-build-state.json");
+
+    // 键为相对于项目目录的路径，统一使用 / 分隔
+    let mut sources: BTreeMap<String, PathBuf> = BTreeMap::new();
+    for dir in &project.source_dirs {
+        collect_files(&project_dir.join(dir), &mut |path| {
+            if path.extension().and_then(|s| s.to_str()) == Some("java") {
+                sources.insert(relative(project_dir, path), path.to_path_buf());
+            }
+        })?;
+    }
+
+    if sources.is_empty() {
+        println!("⚠️  源码目录中没有Java文件: {}", project.source_dirs.join(", "));
+        clean_output(&output_dir, &state_path)?;
+    } else {
+        let classpath = compile_classpath(project_dir, config, profile)?;
+        let processor_path = processor_path(project_dir, config, profile)?;
+        let processors = (!processor_path.is_empty()).then(|| Processors {
+            path: processor_path,
+            generated_dir: classpath::generated_sources_dir(project_dir, config),
+        });
+        let mut current = BuildState::new(
+            options_digest(&project, profile),
+            classpath
+                .iter()
+                .map(|entry| Ok((entry.to_string_lossy().to_string(), hash_classpath_entry(entry)?)))
+                .collect::<Result<_>>()?,
+        );
+        let hashes: BTreeMap<String, String> = sources
+            .iter()
+            .map(|(source, path)| Ok((source.clone(), hash_file(path)?)))
+            .collect::<Result<_>>()?;
+        let roots: Vec<String> = project
+            .source_dirs
+            .iter()
+            .map(|dir| dir.trim_start_matches("./").trim_end_matches('/').to_string())
+            .collect();
+
+        let previous = BuildState::load(&state_path)
+            .filter(|state| processors.is_none() && state.outputs_exist(&output_dir));
+        let plan = match &previous {
+            Some(previous) => previous.plan(&current, &hashes),
+            None => Plan::Full,
+        };
+
+        let (mut state, compiled) = match (plan, previous) {
+            (Plan::UpToDate, Some(previous)) => {
+                println!("源文件没有变化，跳过编译 ({} 个源文件)", sources.len());
+                (previous, None)
+            }
+            (Plan::Incremental { recompile, removed }, Some(mut previous)) => {
+                let stale = recompile.union(&removed).cloned().collect();
+                let deleted = previous.remove_sources(&stale, &output_dir)?;
+                // javac失败时，这些源文件不在状态中，下次构建仍会重新编译
+                previous.save(&state_path)?;
+
+                println!(
+                    "增量编译 {} 个源文件 (共 {} 个)，删除 {} 个过期的class文件",
+                    recompile.len(),
+                    sources.len(),
+                    deleted
+                );
+                // 只删除了源文件时不需要运行javac
+                if !recompile.is_empty() {
+                    let files: Vec<PathBuf> = recompile.iter().map(|source| sources[source].clone()).collect();
+                    // 没有重新编译的类从输出目录中读取
+                    let mut classpath = classpath;
+                    classpath.insert(0, output_dir.clone());
+                    compile(project_dir, &project, profile, &files, &classpath, &output_dir, None)?;
+                }
+
+                let compiled = recompile
+                    .into_iter()
+                    .map(|source| {
+                        let hash = hashes[&source].clone();
+                        (source, hash)
+                    })
+                    .collect();
+                (previous, Some(compiled))
+            }
+            _ => {
+                clean_output(&output_dir, &state_path)?;
+                println!(
+                    "使用javac编译 {} 个源文件 -> {}",
+                    sources.len(),
+                    relative(project_dir, &output_dir)
+                );
+                if let Some(processors) = &processors {
+                    if processors.generated_dir.exists() {
+                        fs::remove_dir_all(&processors.generated_dir).with_context(|| {
+                            format!("无法清理 {}", processors.generated_dir.display())
+                        })?;
+                    }
+                    fs::create_dir_all(&processors.generated_dir)?;
+                    println!(
+                        "注解处理器: {} 个jar，生成的源码 -> {}",
+                        processors.path.len(),
+                        relative(project_dir, &processors.generated_dir)
+                    );
+                }
+                let files: Vec<PathBuf> = sources.values().cloned().collect();
+                compile(
+                    project_dir,
+                    &project,
+                    profile,
+                    &files,
+                    &classpath,
+                    &output_dir,
+                    processors.as_ref(),
+                )?;
+                current.sources.clear();
+                (current, Some(hashes))
+            }
+        };
+
+        // 有注解处理器时不保存构建状态，下次仍然全量编译
+        if let Some(compiled) = compiled.filter(|_| processors.is_none()) {
+            match state.record(&output_dir, &roots, &compiled) {
+                Ok(()) => state.save(&state_path)?,
+                Err(e) => {
+                    println!("⚠️  {}，下次构建将全量编译", e);
+                    if state_path.exists() {
+                        fs::remove_file(&state_path)?;
+                    }
+                }
+            }
+        }
+    }
+    fs::create_dir_all(&output_dir)
+        .with_context(|| format!("无法创建输出目录 {}", output_dir.display()))?;
+
+    let mut copied = 0;
+    for dir in &project.resource_dirs {
+        copied += copy_resources(&project_dir.join(dir), &output_dir)?;
+    }
+    if copied > 0 {
+        println!("复制 {} 个资源文件", copied);
+    }
+    Ok(())
+}
+
+/// 全量编译前清掉上次的输出和构建状态，避免已删除源码的class残留
+fn clean_output(output_dir: &Path, state_path: &Path) -> Result<()> {
+    if output_dir.exists() {
+        fs::remove_dir_all(output_dir)
+            .with_context(|| format!("无法清理输出目录 {}", output_dir.display()))?;
+    }
+    if state_path.exists() {
+        fs::remove_file(state_path)?;
+    }
+    fs::create_dir_all(output_dir)
+        .with_context(|| format!("无法创建输出目录 {}", output_dir.display()))
+}
+
+/// 影响编译结果的参数: javac、`--release`和profile的javac参数，变化时全量编译
+fn options_digest(project: &Project, profile: &Profile) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(javac().to_string_lossy().as_bytes());
+    hasher.update(b"\0");
+    hasher.update(release(&project.java_version).as_bytes());
+    for flag in &profile.javac_flags {
+        hasher.update(b"\0");
+        hasher.update(flag.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// 当前profile的编译classpath: compile、provided和system依赖的缓存jar，以及路径依赖成员的输出目录
+pub fn compile_classpath(project_dir: &Path, config: &JxConfig, profile: &Profile) -> Result<Vec<PathBuf>> {
+    let has_dependencies = !config.dependencies().is_empty() || !profile.dependencies.is_empty();
+    if has_dependencies && !workspace::lock_path(project_dir)?.exists() {
+        println!("⚠️  没有jx.lock，只使用直接依赖，运行 'jx lock' 解析传递依赖");
+    }
+    let dependencies: Vec<Dependency> = classpath::profile_dependencies(project_dir, config, profile)?
+        .into_iter()
+        .filter(|dep| {
+            matches!(
+                dep.scope,
+                DependencyScope::Compile | DependencyScope::Provided | DependencyScope::System
+            )
+        })
+        .collect();
+
+    let downloader = Downloader::new();
+    let missing = downloader.missing_from_cache(&dependencies);
+    if !missing.is_empty() {
+        return Err(anyhow::anyhow!(
+            "本地缓存缺少 {} 个编译依赖:\n  {}\n请先运行 'jx install'",
+            missing.len(),
+            missing.join("\n  ")
+        ));
+    }
+
+    let mut classpath = Vec::new();
+    for dependency in config.path_dependencies() {
+        let member_dir = project_dir.join(&dependency.path);
+        let member_config = JxConfig::load_from_dir(&member_dir)?;
+        classpath.extend(classpath::output_dirs(&member_dir, &member_config));
+    }
+    classpath.extend(dependencies.iter().map(|dep| downloader.cached_path(dep)));
+    Ok(classpath)
+}
+
+/// 当前profile的注解处理器路径: annotation-processor依赖及其传递依赖的缓存jar
+pub fn processor_path(project_dir: &Path, config: &JxConfig, profile: &Profile) -> Result<Vec<PathBuf>> {
+    let dependencies = classpath::processor_dependencies(project_dir, config, profile)?;
+    let downloader = Downloader::new();
+    let missing = downloader.missing_from_cache(&dependencies);
+    if !missing.is_empty() {
+        return Err(anyhow::anyhow!(
+            "本地缓存缺少 {} 个注解处理器依赖:\n  {}\n请先运行 'jx install'",
+            missing.len(),
+            missing.join("\n  ")
+        ));
+    }
+    Ok(dependencies.iter().map(|dep| downloader.cached_path(dep)).collect())
+}
+
+fn compile(
+    project_dir: &Path,
+    project: &Project,
+    profile: &Profile,
+    sources: &[PathBuf],
+    classpath: &[PathBuf],
+    output_dir: &Path,
+    processors: Option<&Processors>,
+) -> Result<()> {
+    // 源文件列表写入参数文件，避免命令行过长
+    let args_file = project_dir.join(&project.target_dir).join("javac-sources.txt");
+    let listing: Vec<String> = sources.iter().map(|path| argfile_quote(&path.to_string_lossy())).collect();
+    fs::write(&args_file, listing.join("\n"))
+        .with_context(|| format!("无法写入 {}", args_file.display()))?;
+
+    let mut command = Command::new(javac());
+    command
+        .current_dir(project_dir)
+        .arg("-d")
+        .arg(output_dir)
+        .args(["-encoding", "UTF-8"])
+        .args(["--release", &release(&project.java_version)]);
+    if !classpath.is_empty() {
+        command.arg("-cp").arg(classpath::join(classpath)?);
+    }
+    if let Some(processors) = processors {
+        command
+            .arg("-processorpath")
+            .arg(classpath::join(&processors.path)?)
+            .arg("-s")
+            .arg(&processors.generated_dir);
+    }
+    command.args(&profile.javac_flags);
+    command.arg(format!("@{}", args_file.display()));
+
+    let output = command
+        .output()
+        .context("无法启动javac，请安装JDK或使用 'jx venv' 创建包含JDK的虚拟环境")?;
+
+    // javac的诊断信息写到stderr，路径改为相对于项目目录
+    let diagnostics = String::from_utf8_lossy(&output.stderr)
+        .replace(&format!("{}{}", project_dir.display(), std::path::MAIN_SEPARATOR), "");
+    if !diagnostics.trim().is_empty() {
+        eprintln!("{}", diagnostics.trim_end());
+    }
+
+    if !output.status.success() {
+        let errors = diagnostics.lines().filter(|line| line.contains(": error:")).count();
+        return Err(if errors > 0 {
+            anyhow::anyhow!("javac编译失败，{} 个错误", errors)
+        } else {
+            anyhow::anyhow!("javac编译失败，退出码: {}", output.status)
+        });
+    }
+    Ok(())
+}
+
+/// 虚拟环境中有JDK时使用其中的javac
+fn javac() -> PathBuf {
+    match venv::active_java_home() {
+        Ok(Some(java_home)) => java_home.join("bin").join("javac"),
+        _ => PathBuf::from("javac"),
+    }
+}
+
+/// `--release`只接受主版本号: 1.8 -> 8
+fn release(java_version: &str) -> String {
+    java_version
+        .strip_prefix("1.")
+        .unwrap_or(java_version)
+        .to_string()
+}
+
+/// javac参数文件中带空格或引号的路径需要加引号
+fn argfile_quote(path: &str) -> String {
+    if !path.contains([' ', '"', '\'', '\\', '#']) {
+        return path.to_string();
+    }
+    format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// 按相对路径把资源目录复制到输出目录，返回复制的文件数
+fn copy_resources(resource_dir: &Path, output_dir: &Path) -> Result<usize> {
+    let mut files = Vec::new();
+    collect_files(resource_dir, &mut |path| files.push(path.to_path_buf()))?;
+
+    for file in &files {
+        let relative = file.strip_prefix(resource_dir)?;
+        let target = output_dir.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(file, &target)
+            .with_context(|| format!("无法复制资源文件 {}", file.display()))?;
+    }
+    Ok(files.len())
+}
+
+fn collect_files(dir: &Path, visit: &mut dyn FnMut(&Path)) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir).with_context(|| format!("无法读取目录 {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, visit)?;
+        } else {
+            visit(&path);
+        }
+    }
+    Ok(())
+}
+
+fn relative(project_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(project_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
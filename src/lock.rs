@@ -1,14 +1,24 @@
+use crate::dependency::{Dependency, DependencyScope};
+use crate::download::Downloader;
+use crate::resolve::DependencyResolver;
+use crate::workspace::scope_rank;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
+/// 锁文件格式版本，"1.0" 为旧格式 (HashMap顺序、带时间戳的metadata)
+pub const LOCK_VERSION: u32 = 2;
+
+const LOCK_HEADER: &str = "# 此文件由jx自动生成，请勿手动编辑\n\n";
+
+/// 依赖按坐标排序，不含时间戳，相同的依赖图总是生成相同的内容
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LockFile {
-    pub version: String,
-    pub dependencies: HashMap<String, LockedDependency>,
-    pub metadata: LockMetadata,
+    pub version: u32,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, LockedDependency>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,45 +31,26 @@ pub struct LockedDependency {
     pub checksum: String,
     pub url: String,
     pub dependencies: Vec<String>, // 传递依赖的坐标
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct LockMetadata {
-    pub created_at: String,
-    pub updated_at: String,
-    pub total_dependencies: usize,
-    pub total_size: u64,
+    #[serde(default)]
+    pub direct: bool, // 是否为jx.toml中声明的直接依赖
 }
 
 impl LockFile {
     pub fn new() -> Self {
         Self {
-            version: "1.0".to_string(),
-            dependencies: HashMap::new(),
-            metadata: LockMetadata {
-                created_at: chrono::Utc::now().to_rfc3339(),
-                updated_at: chrono::Utc::now().to_rfc3339(),
-                total_dependencies: 0,
-                total_size: 0,
-            },
+            version: LOCK_VERSION,
+            dependencies: BTreeMap::new(),
         }
     }
 
     pub fn add_dependency(&mut self, dep: LockedDependency) {
         let key = format!("{}:{}:{}", dep.group_id, dep.artifact_id, dep.version);
         self.dependencies.insert(key, dep);
-        self.metadata.total_dependencies = self.dependencies.len();
-        self.metadata.updated_at = chrono::Utc::now().to_rfc3339();
     }
 
     pub fn remove_dependency(&mut self, group_id: &str, artifact_id: &str, version: &str) -> bool {
         let key = format!("{}:{}:{}", group_id, artifact_id, version);
-        let removed = self.dependencies.remove(&key).is_some();
-        if removed {
-            self.metadata.total_dependencies = self.dependencies.len();
-            self.metadata.updated_at = chrono::Utc::now().to_rfc3339();
-        }
-        removed
+        self.dependencies.remove(&key).is_some()
     }
 
     pub fn get_dependency(
@@ -77,22 +68,162 @@ impl LockFile {
         self.dependencies.contains_key(&key)
     }
 
+    /// 根据解析结果生成锁文件，依赖必须已下载到缓存以计算校验和
+    pub fn from_resolver(resolver: &DependencyResolver, downloader: &Downloader) -> Result<Self> {
+        let mut lock_file = LockFile::new();
+
+        for dep in resolver.resolved_dependencies() {
+            let cache_path = downloader.cached_path(&dep);
+            let checksum = crate::utils::sha256_file(&cache_path)?;
+
+            lock_file.add_dependency(LockedDependency {
+                group_id: dep.group_id.clone(),
+                artifact_id: dep.artifact_id.clone(),
+                version: dep.version.clone(),
+                classifier: dep.classifier.clone(),
+                scope: dep.scope.as_str().to_string(),
+                checksum,
+                url: downloader.build_maven_central_url(
+                    &dep.group_id,
+                    &dep.artifact_id,
+                    &dep.version,
+                    dep.classifier.as_deref(),
+                ),
+                dependencies: resolver.dependencies_of(&dep.coordinate()),
+                direct: resolver.is_direct(&dep),
+            });
+        }
+
+        Ok(lock_file)
+    }
+
+    /// 加入单独解析的依赖图，同一构件已经锁定时保留原有的条目
+    pub fn merge(&mut self, other: LockFile) {
+        for (key, dep) in other.dependencies {
+            self.dependencies.entry(key).or_insert(dep);
+        }
+    }
+
+    /// 比较jx.toml中的直接依赖与锁文件，返回所有差异
+    pub fn drift(&self, manifest: &[Dependency]) -> Vec<String> {
+        let mut changes = Vec::new();
+        let locked: Vec<&LockedDependency> =
+            self.dependencies.values().filter(|dep| dep.direct).collect();
+
+        for dep in manifest {
+            let existing = locked
+                .iter()
+                .find(|l| l.group_id == dep.group_id && l.artifact_id == dep.artifact_id);
+            match existing {
+                None => changes.push(format!("新增依赖: {}", dep.coordinate())),
+                Some(l) if l.version != dep.version => changes.push(format!(
+                    "版本变更: {}:{} {} -> {}",
+                    dep.group_id, dep.artifact_id, l.version, dep.version
+                )),
+                Some(l) if l.scope != dep.scope.as_str() => changes.push(format!(
+                    "scope变更: {} {} -> {}",
+                    dep.coordinate(),
+                    l.scope,
+                    dep.scope.as_str()
+                )),
+                Some(_) => {}
+            }
+        }
+
+        for l in locked {
+            let still_declared = manifest
+                .iter()
+                .any(|dep| dep.group_id == l.group_id && dep.artifact_id == l.artifact_id);
+            if !still_declared {
+                changes.push(format!("移除依赖: {}", l.coordinate()));
+            }
+        }
+
+        changes.sort();
+        changes
+    }
+
+    /// 校验缓存中的jar与锁定的校验和一致
+    pub fn check_checksum(&self, dep: &LockedDependency, path: &Path) -> Result<()> {
+        if dep.checksum.is_empty() {
+            return Ok(());
+        }
+
+        let actual = crate::utils::sha256_file(path)?;
+        if actual != dep.checksum {
+            return Err(anyhow::anyhow!(
+                "校验和不匹配: {}\n  锁定: {}\n  实际: {}\n  文件: {}",
+                dep.coordinate(),
+                dep.checksum,
+                actual,
+                path.display()
+            ));
+        }
+        Ok(())
+    }
+
+    /// 规范化的文件内容: 依赖关系也排序，保证输出稳定
+    pub fn to_canonical_string(&self) -> Result<String> {
+        let mut dependencies = self.dependencies.clone();
+        for dep in dependencies.values_mut() {
+            dep.dependencies.sort();
+            dep.dependencies.dedup();
+        }
+
+        let canonical = LockFile {
+            version: LOCK_VERSION,
+            dependencies,
+        };
+        Ok(format!("{}{}", LOCK_HEADER, toml::to_string_pretty(&canonical)?))
+    }
+
     pub fn save(&self, path: &Path) -> Result<()> {
-        let content = toml::to_string_pretty(self)?;
-        fs::write(path, content)?;
+        fs::write(path, self.to_canonical_string()?)?;
         Ok(())
     }
 
     pub fn load(path: &Path) -> Result<Self> {
         if path.exists() {
             let content = fs::read_to_string(path)?;
-            let lock_file: LockFile = toml::from_str(&content)?;
-            Ok(lock_file)
+            Self::parse(&content)
+                .map_err(|e| anyhow::anyhow!("解析 {} 失败: {}", path.display(), e))
         } else {
             Ok(LockFile::new())
         }
     }
 
+    /// 解析锁文件，旧版本格式在内存中迁移，下次保存时写成新格式
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut value: toml::Value = toml::from_str(content)?;
+        let table = value
+            .as_table_mut()
+            .ok_or_else(|| anyhow::anyhow!("锁文件格式错误"))?;
+
+        match table.get("version") {
+            Some(toml::Value::Integer(v)) if *v == LOCK_VERSION as i64 => {}
+            Some(toml::Value::String(v)) if v == "1.0" => {
+                table.remove("metadata");
+                table.insert("version".to_string(), toml::Value::Integer(LOCK_VERSION as i64));
+            }
+            Some(v) => {
+                return Err(anyhow::anyhow!(
+                    "不支持的锁文件版本 {}，当前jx支持版本 {}，请升级jx",
+                    v,
+                    LOCK_VERSION
+                ))
+            }
+            None => return Err(anyhow::anyhow!("锁文件缺少version字段")),
+        }
+
+        Ok(value.try_into()?)
+    }
+
+    /// 磁盘上的内容是否与规范格式一致
+    pub fn is_canonical(&self, path: &Path) -> Result<bool> {
+        let on_disk = fs::read_to_string(path)?;
+        Ok(on_disk == self.to_canonical_string()?)
+    }
+
     pub fn update_checksum(
         &mut self,
         group_id: &str,
@@ -103,7 +234,6 @@ impl LockFile {
         let key = format!("{}:{}:{}", group_id, artifact_id, version);
         if let Some(dep) = self.dependencies.get_mut(&key) {
             dep.checksum = checksum.to_string();
-            self.metadata.updated_at = chrono::Utc::now().to_rfc3339();
         }
         Ok(())
     }
@@ -118,16 +248,263 @@ impl LockFile {
         let key = format!("{}:{}:{}", group_id, artifact_id, version);
         if let Some(dep) = self.dependencies.get_mut(&key) {
             dep.url = url.to_string();
-            self.metadata.updated_at = chrono::Utc::now().to_rfc3339();
         }
         Ok(())
     }
 
+    /// 每个依赖 (groupId:artifactId) 是由哪些直接依赖引入的
+    pub fn direct_dependents(&self) -> HashMap<String, Vec<String>> {
+        let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+
+        for root in self.dependencies.values().filter(|dep| dep.direct) {
+            let mut visited = HashSet::new();
+            let mut stack = vec![root.coordinate()];
+
+            while let Some(coordinate) = stack.pop() {
+                if !visited.insert(coordinate.clone()) {
+                    continue;
+                }
+                if let Some(dep) = self.dependencies.get(&coordinate) {
+                    dependents
+                        .entry(dep.key())
+                        .or_default()
+                        .push(root.key());
+                    stack.extend(dep.dependencies.iter().cloned());
+                }
+            }
+        }
+
+        dependents
+    }
+
+    /// 从给定的直接依赖出发沿锁定的依赖图能到达的条目，按锁文件的顺序
+    ///
+    /// 工作区共享的jx.lock包含所有成员的依赖，每个成员只使用自己能到达的部分。
+    /// 直接依赖使用声明的scope，传递依赖继承它: compile依赖下锁定为runtime的依赖仍为runtime；
+    /// 经由多个直接依赖到达时取范围最大的scope。锁文件中没有的直接依赖被忽略
+    pub fn reachable_from(&self, roots: &[Dependency]) -> Vec<LockedDependency> {
+        let mut scopes: HashMap<String, DependencyScope> = HashMap::new();
+        for root in roots {
+            let Some(entry) = self.get_dependency(&root.group_id, &root.artifact_id, &root.version) else {
+                continue;
+            };
+            let mut visited = HashSet::new();
+            let mut stack = vec![(entry.coordinate(), root.scope.clone())];
+            while let Some((coordinate, scope)) = stack.pop() {
+                if !visited.insert(coordinate.clone()) {
+                    continue;
+                }
+                let Some(dep) = self.dependencies.get(&coordinate) else {
+                    continue;
+                };
+                for child in &dep.dependencies {
+                    let child_scope = match (&scope, self.dependencies.get(child)) {
+                        (DependencyScope::Compile, Some(locked)) if locked.scope == "runtime" => {
+                            DependencyScope::Runtime
+                        }
+                        _ => scope.clone(),
+                    };
+                    stack.push((child.clone(), child_scope));
+                }
+                let wider = scopes
+                    .get(&coordinate)
+                    .is_none_or(|existing| scope_rank(&scope) < scope_rank(existing));
+                if wider {
+                    scopes.insert(coordinate, scope);
+                }
+            }
+        }
+
+        self.dependencies
+            .values()
+            .filter_map(|dep| {
+                let scope = scopes.get(&dep.coordinate())?;
+                let mut dep = dep.clone();
+                dep.scope = scope.as_str().to_string();
+                Some(dep)
+            })
+            .collect()
+    }
+
+    /// 只能经由给定构件 (groupId:artifactId) 到达的依赖，包括这些构件本身
+    pub fn exclusive_dependencies(&self, keys: &HashSet<String>) -> HashSet<String> {
+        let walk = |starts: Vec<String>, stop_at: Option<&HashSet<String>>| {
+            let mut reached = HashSet::new();
+            let mut visited = HashSet::new();
+            let mut stack = starts;
+
+            while let Some(coordinate) = stack.pop() {
+                if !visited.insert(coordinate.clone()) {
+                    continue;
+                }
+                if let Some(dep) = self.dependencies.get(&coordinate) {
+                    if stop_at.is_some_and(|stop| stop.contains(&dep.ga_key())) {
+                        continue;
+                    }
+                    reached.insert(dep.ga_key());
+                    stack.extend(dep.dependencies.iter().cloned());
+                }
+            }
+            reached
+        };
+
+        let roots = self
+            .dependencies
+            .values()
+            .filter(|dep| dep.direct)
+            .map(|dep| dep.coordinate())
+            .collect();
+        let shared = walk(roots, Some(keys));
+
+        let starts = self
+            .dependencies
+            .values()
+            .filter(|dep| keys.contains(&dep.ga_key()))
+            .map(|dep| dep.coordinate())
+            .collect();
+        let below = walk(starts, None);
+
+        below.difference(&shared).cloned().collect()
+    }
+
+    /// 比较两个锁文件，按groupId:artifactId匹配
+    pub fn diff(old: &LockFile, new: &LockFile) -> Vec<LockChange> {
+        let old_deps: BTreeMap<String, &LockedDependency> =
+            old.dependencies.values().map(|dep| (dep.key(), dep)).collect();
+        let new_deps: BTreeMap<String, &LockedDependency> =
+            new.dependencies.values().map(|dep| (dep.key(), dep)).collect();
+        let old_dependents = old.direct_dependents();
+        let new_dependents = new.direct_dependents();
+
+        let mut keys: Vec<&String> = old_deps.keys().chain(new_deps.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        let mut changes = Vec::new();
+        for key in keys {
+            let (old_dep, new_dep) = (old_deps.get(key), new_deps.get(key));
+            let kind = match (old_dep, new_dep) {
+                (None, Some(_)) => LockChangeKind::Added,
+                (Some(_), None) => LockChangeKind::Removed,
+                (Some(o), Some(n)) => match crate::index::compare_versions(&n.version, &o.version) {
+                    std::cmp::Ordering::Greater => LockChangeKind::Upgraded,
+                    std::cmp::Ordering::Less => LockChangeKind::Downgraded,
+                    std::cmp::Ordering::Equal if o.scope != n.scope => LockChangeKind::ScopeChanged,
+                    std::cmp::Ordering::Equal => continue,
+                },
+                (None, None) => continue,
+            };
+
+            // 移除的依赖看旧图，其余看新图
+            let introduced_by = match kind {
+                LockChangeKind::Removed => old_dependents.get(key),
+                _ => new_dependents.get(key),
+            }
+            .cloned()
+            .unwrap_or_default();
+
+            changes.push(LockChange {
+                kind,
+                artifact: key.clone(),
+                old_version: old_dep.map(|d| d.version.clone()),
+                new_version: new_dep.map(|d| d.version.clone()),
+                old_scope: old_dep.map(|d| d.scope.clone()),
+                new_scope: new_dep.map(|d| d.scope.clone()),
+                direct: new_dep.or(old_dep).is_some_and(|d| d.direct),
+                introduced_by,
+            });
+        }
+
+        changes
+    }
+
+    /// 生成Gradle的gradle.lockfile，scope映射到对应的classpath配置
+    pub fn to_gradle_lockfile(&self) -> String {
+        let mut lines: Vec<String> = self
+            .dependencies
+            .values()
+            .map(|dep| format!("{}={}", dep.coordinate(), gradle_configurations(&dep.scope).join(",")))
+            .collect();
+        lines.sort();
+
+        let used: HashSet<&str> = self
+            .dependencies
+            .values()
+            .flat_map(|dep| gradle_configurations(&dep.scope))
+            .collect();
+        let empty: Vec<&str> = GRADLE_CONFIGURATIONS
+            .iter()
+            .copied()
+            .filter(|c| !used.contains(c))
+            .collect();
+
+        let mut content = String::from(
+            "# This is a Gradle generated file for dependency locking.\n\
+             # Manual edits can break the build and are not advised.\n\
+             # This file is expected to be part of source control.\n",
+        );
+        for line in lines {
+            content.push_str(&line);
+            content.push('\n');
+        }
+        content.push_str(&format!("empty={}\n", empty.join(",")));
+        content
+    }
+
+    /// 生成可以粘贴到pom.xml中的<dependencyManagement>块，注解处理器不在classpath上，不包括在内
+    pub fn to_maven_dependency_management(&self, indent: &str) -> String {
+        let mut xml = format!("{}<dependencyManagement>\n{}  <dependencies>\n", indent, indent);
+
+        for dep in self.dependencies.values().filter(|dep| dep.scope != "annotation-processor") {
+            xml.push_str(&format!("{}    <dependency>\n", indent));
+            xml.push_str(&format!("{}      <groupId>{}</groupId>\n", indent, dep.group_id));
+            xml.push_str(&format!("{}      <artifactId>{}</artifactId>\n", indent, dep.artifact_id));
+            xml.push_str(&format!("{}      <version>{}</version>\n", indent, dep.version));
+            if let Some(ref classifier) = dep.classifier {
+                xml.push_str(&format!("{}      <classifier>{}</classifier>\n", indent, classifier));
+            }
+            xml.push_str(&format!("{}    </dependency>\n", indent));
+        }
+
+        xml.push_str(&format!("{}  </dependencies>\n{}</dependencyManagement>\n", indent, indent));
+        xml
+    }
+
+    /// 生成BOM POM，其他Maven项目可以通过scope为import的依赖引入
+    pub fn to_bom_pom(&self, group_id: &str, artifact_id: &str, version: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0"
+         xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+         xsi:schemaLocation="http://maven.apache.org/POM/4.0.0 http://maven.apache.org/xsd/maven-4.0.0.xsd">
+  <modelVersion>4.0.0</modelVersion>
+
+  <!-- 此文件由jx根据jx.lock生成，请勿手动编辑 -->
+  <groupId>{}</groupId>
+  <artifactId>{}</artifactId>
+  <version>{}</version>
+  <packaging>pom</packaging>
+
+{}</project>
+"#,
+            group_id,
+            artifact_id,
+            version,
+            self.to_maven_dependency_management("  ")
+        )
+    }
+
     pub fn get_dependency_tree(&self) -> Vec<DependencyTreeNode> {
         let mut tree = Vec::new();
         let mut visited = HashMap::new();
 
+        // 旧锁文件没有direct标记时，所有依赖都作为根节点
+        let has_direct = self.dependencies.values().any(|dep| dep.direct);
+
         for (key, dep) in &self.dependencies {
+            if has_direct && !dep.direct {
+                continue;
+            }
             if !visited.contains_key(key) {
                 let node = self.build_tree_node(dep, &mut visited, 0);
                 tree.push(node);
@@ -165,6 +542,101 @@ impl LockFile {
     }
 }
 
+const GRADLE_CONFIGURATIONS: [&str; 4] = [
+    "compileClasspath",
+    "runtimeClasspath",
+    "testCompileClasspath",
+    "testRuntimeClasspath",
+];
+
+fn gradle_configurations(scope: &str) -> Vec<&'static str> {
+    match scope {
+        "runtime" => vec!["runtimeClasspath", "testRuntimeClasspath"],
+        "provided" | "system" => vec!["compileClasspath", "testCompileClasspath"],
+        "test" => vec!["testCompileClasspath", "testRuntimeClasspath"],
+        "annotation-processor" => vec!["annotationProcessor"],
+        _ => GRADLE_CONFIGURATIONS.to_vec(),
+    }
+}
+
+/// gradle.lockfile中的一个构件
+#[derive(Debug, Clone)]
+pub struct GradleLockEntry {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: String,
+    pub configurations: Vec<String>,
+}
+
+impl GradleLockEntry {
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.group_id, self.artifact_id)
+    }
+
+    /// annotationProcessor等配置中的构件不在类路径上
+    pub fn on_classpath(&self) -> bool {
+        self.configurations
+            .iter()
+            .any(|c| GRADLE_CONFIGURATIONS.contains(&c.as_str()))
+    }
+}
+
+pub fn parse_gradle_lockfile(content: &str) -> Result<Vec<GradleLockEntry>> {
+    let mut entries = Vec::new();
+
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("empty=") {
+            continue;
+        }
+
+        let (coordinate, configurations) = line.split_once('=').unwrap_or((line, ""));
+        let parts: Vec<&str> = coordinate.split(':').collect();
+        if parts.len() != 3 {
+            return Err(anyhow::anyhow!(
+                "gradle.lockfile 第 {} 行格式错误: {}",
+                number + 1,
+                line
+            ));
+        }
+
+        entries.push(GradleLockEntry {
+            group_id: parts[0].to_string(),
+            artifact_id: parts[1].to_string(),
+            version: parts[2].to_string(),
+            configurations: configurations
+                .split(',')
+                .filter(|c| !c.is_empty())
+                .map(|c| c.to_string())
+                .collect(),
+        });
+    }
+
+    Ok(entries)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockChangeKind {
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+    ScopeChanged,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LockChange {
+    pub kind: LockChangeKind,
+    pub artifact: String,
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+    pub old_scope: Option<String>,
+    pub new_scope: Option<String>,
+    pub direct: bool,
+    pub introduced_by: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct DependencyTreeNode {
     pub dependency: LockedDependency,
@@ -177,6 +649,19 @@ impl LockedDependency {
         format!("{}:{}:{}", self.group_id, self.artifact_id, self.version)
     }
 
+    /// groupId:artifactId，不含classifier和版本
+    pub fn ga_key(&self) -> String {
+        format!("{}:{}", self.group_id, self.artifact_id)
+    }
+
+    /// 不含版本的坐标，带classifier时附加在末尾
+    pub fn key(&self) -> String {
+        match self.classifier {
+            Some(ref classifier) => format!("{}:{}:{}", self.group_id, self.artifact_id, classifier),
+            None => format!("{}:{}", self.group_id, self.artifact_id),
+        }
+    }
+
     pub fn filename(&self) -> String {
         let mut filename = format!("{}-{}.jar", self.artifact_id, self.version);
         if let Some(ref classifier) = self.classifier {
@@ -184,6 +669,13 @@ impl LockedDependency {
         }
         filename
     }
+
+    pub fn to_dependency(&self) -> crate::dependency::Dependency {
+        let mut dep = crate::dependency::Dependency::new(&self.group_id, &self.artifact_id, &self.version)
+            .with_scope(crate::dependency::DependencyScope::from_name(&self.scope));
+        dep.classifier = self.classifier.clone();
+        dep
+    }
 }
 
 impl DependencyTreeNode {
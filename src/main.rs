@@ -2,16 +2,32 @@ use clap::{App, Arg, SubCommand};
 use log::error;
 use std::process;
 
+mod build_state;
+mod catalog;
+mod classfile;
+mod classpath;
 mod commands;
 mod config;
 mod dependency;
 mod download;
+mod gradle;
+mod index;
 mod install;
+mod jar;
+mod javac;
 mod lock;
+mod manifest;
+mod modules;
+mod pom;
+mod pom_editor;
+mod profile;
 mod project;
 mod registry;
 mod resolve;
+mod settings;
+mod shade;
 mod utils;
+mod workspace;
 
 #[tokio::main]
 async fn main() {
@@ -21,6 +37,8 @@ async fn main() {
     let matches = App::new("jx")
         .version(env!("CARGO_PKG_VERSION"))
         .about("A fast Java package manager written in Rust")
+        // 不是内置命令时当作jx.toml中的脚本名
+        .allow_external_subcommands(true)
         .arg(
             Arg::with_name("verbose")
                 .short('v')
@@ -33,6 +51,12 @@ async fn main() {
                 .long("quiet")
                 .help("静默模式"),
         )
+        .arg(
+            Arg::with_name("offline")
+                .long("offline")
+                .global(true)
+                .help("离线模式，仅使用本地缓存和锁文件"),
+        )
         .subcommand(
             SubCommand::with_name("init")
                 .about("初始化新的Java项目")
@@ -61,7 +85,89 @@ async fn main() {
                         .long("production")
                         .help("仅安装生产依赖"),
                 )
-                .arg(Arg::with_name("force").long("force").help("强制重新安装")),
+                .arg(Arg::with_name("force").long("force").help("强制重新解析并安装"))
+                .arg(
+                    Arg::with_name("locked")
+                        .long("locked")
+                        .help("要求jx.lock与jx.toml一致，不更新锁文件"),
+                )
+                .arg(
+                    Arg::with_name("frozen")
+                        .long("frozen")
+                        .help("等同于 --locked --offline"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lock")
+                .about("生成或更新jx.lock")
+                .arg(
+                    Arg::with_name("check")
+                        .long("check")
+                        .help("只检查锁文件是否需要更新，需要时以非零状态退出"),
+                )
+                .arg(
+                    Arg::with_name("upgrade-package")
+                        .long("upgrade-package")
+                        .value_name("GROUP:ARTIFACT[:VERSION]")
+                        .help("只升级指定的传递依赖，其余依赖保持锁定的版本")
+                        .takes_value(true)
+                        .multiple_occurrences(true)
+                        .conflicts_with("check"),
+                )
+                .subcommand(
+                    SubCommand::with_name("diff")
+                        .about("比较两个锁文件或git版本中的依赖图")
+                        .arg(
+                            Arg::with_name("OLD")
+                                .help("旧锁文件路径或git版本 (默认HEAD)")
+                                .index(1),
+                        )
+                        .arg(
+                            Arg::with_name("NEW")
+                                .help("新锁文件路径或git版本 (默认工作区的jx.lock)")
+                                .index(2),
+                        )
+                        .arg(
+                            Arg::with_name("json")
+                                .long("json")
+                                .help("以JSON输出差异"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("export")
+                        .about("从jx.lock导出Gradle或Maven的版本锁定")
+                        .arg(
+                            Arg::with_name("format")
+                                .long("format")
+                                .help("导出格式: gradle.lockfile、<dependencyManagement>块或BOM POM")
+                                .possible_values(["gradle", "maven", "bom"])
+                                .required(true)
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("output")
+                                .short('o')
+                                .long("output")
+                                .help("输出文件，默认输出到标准输出")
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("group-id")
+                                .long("group-id")
+                                .help("BOM的groupId")
+                                .takes_value(true),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("import")
+                        .about("根据gradle.lockfile中的版本生成jx.lock")
+                        .arg(
+                            Arg::with_name("FILE")
+                                .help("gradle.lockfile路径")
+                                .default_value("gradle.lockfile")
+                                .index(1),
+                        ),
+                ),
         )
         .subcommand(
             SubCommand::with_name("add")
@@ -76,76 +182,291 @@ async fn main() {
                     Arg::with_name("scope")
                         .short('s')
                         .long("scope")
-                        .help("依赖类型 (compile, runtime, test, provided)")
+                        .help("依赖类型 (compile, runtime, test, provided, annotation-processor)")
                         .default_value("compile")
-                        .possible_values(&["compile", "runtime", "test", "provided"]),
+                        .possible_values(&["compile", "runtime", "test", "provided", "annotation-processor"]),
+                )
+                .arg(
+                    Arg::with_name("managed")
+                        .long("managed")
+                        .help("操作pom.xml的<dependencyManagement>"),
+                )
+                .arg(
+                    Arg::with_name("alias")
+                        .long("alias")
+                        .value_name("ALIAS")
+                        .help("Gradle版本目录中使用的库别名，默认使用artifactId")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("package")
+                        .short('p')
+                        .long("package")
+                        .value_name("MEMBER")
+                        .help("工作区中要操作的成员")
+                        .takes_value(true),
                 ),
         )
         .subcommand(
-            SubCommand::with_name("remove").about("移除依赖").arg(
-                Arg::with_name("DEPENDENCY")
-                    .help("依赖坐标 (groupId:artifactId)")
-                    .required(true)
-                    .index(1),
-            ),
+            SubCommand::with_name("remove")
+                .about("移除依赖")
+                .arg(
+                    Arg::with_name("DEPENDENCY")
+                        .help("依赖坐标 (groupId:artifactId)")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("managed")
+                        .long("managed")
+                        .help("操作pom.xml的<dependencyManagement>"),
+                )
+                .arg(
+                    Arg::with_name("package")
+                        .short('p')
+                        .long("package")
+                        .value_name("MEMBER")
+                        .help("工作区中要操作的成员")
+                        .takes_value(true),
+                ),
         )
         .subcommand(
             SubCommand::with_name("update")
                 .about("更新依赖")
                 .arg(
                     Arg::with_name("DEPENDENCY")
-                        .help("依赖坐标 (groupId:artifactId)")
+                        .help("依赖坐标 (groupId:artifactId[:version])，不指定版本时更新到最新发布版本")
                         .index(1),
                 )
                 .arg(
                     Arg::with_name("latest")
                         .long("latest")
                         .help("更新到最新版本"),
+                )
+                .arg(
+                    Arg::with_name("managed")
+                        .long("managed")
+                        .help("操作pom.xml的<dependencyManagement>"),
+                )
+                .arg(
+                    Arg::with_name("package")
+                        .short('p')
+                        .long("package")
+                        .value_name("MEMBER")
+                        .help("工作区中要操作的成员")
+                        .takes_value(true),
                 ),
         )
         .subcommand(
             SubCommand::with_name("build")
                 .about("构建项目")
                 .arg(
-                    Arg::with_name("mode")
+                    Arg::with_name("profile")
+                        .long("profile")
                         .short('m')
-                        .long("mode")
-                        .help("构建模式 (debug, release)")
-                        .default_value("debug")
-                        .possible_values(&["debug", "release"]),
+                        .alias("mode")
+                        .value_name("PROFILE")
+                        .help("构建配置: 内置的debug、release或jx.toml中的[profile.<name>] (--mode为旧写法)")
+                        .takes_value(true),
                 )
-                .arg(Arg::with_name("no-test").long("no-test").help("跳过测试")),
+                .arg(Arg::with_name("no-test").long("no-test").help("跳过测试"))
+                .arg(
+                    Arg::with_name("package")
+                        .short('p')
+                        .long("package")
+                        .value_name("MEMBER")
+                        .help("只构建指定的工作区成员或Maven/Gradle子模块 (及其依赖)，可重复")
+                        .takes_value(true)
+                        .multiple_occurrences(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("package")
+                .about("编译jx项目并打包为 target/<name>-<version>.jar")
+                .arg(
+                    Arg::with_name("profile")
+                        .long("profile")
+                        .value_name("PROFILE")
+                        .help("构建配置，默认为release")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("package")
+                        .short('p')
+                        .long("package")
+                        .value_name("MEMBER")
+                        .help("工作区中要打包的成员，路径依赖的成员会先打包")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("uber")
+                        .long("uber")
+                        .help("把所有运行时依赖合并进 target/<name>-<version>-uber.jar"),
+                ),
         )
         .subcommand(
             SubCommand::with_name("run")
                 .about("运行项目")
                 .arg(Arg::with_name("MAIN_CLASS").help("主类名").index(1))
+                .arg(
+                    Arg::with_name("profile")
+                        .long("profile")
+                        .value_name("PROFILE")
+                        .help("构建配置: 内置的debug、release或jx.toml中的[profile.<name>]")
+                        .takes_value(true),
+                )
                 .arg(
                     Arg::with_name("ARGS")
                         .help("程序参数")
                         .multiple(true)
                         .index(2),
+                )
+                .arg(
+                    Arg::with_name("package")
+                        .short('p')
+                        .long("package")
+                        .value_name("MEMBER")
+                        .help("工作区中要运行的成员")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("run-script")
+                .about("运行jx.toml中[scripts]定义的脚本，不指定名称时列出所有脚本")
+                .arg(Arg::with_name("NAME").help("脚本名称").index(1))
+                .arg(
+                    Arg::with_name("ARGS")
+                        .help("传给脚本的额外参数 (以-开头的参数写在 -- 之后)")
+                        .multiple(true)
+                        .index(2),
+                )
+                .arg(
+                    Arg::with_name("package")
+                        .short('p')
+                        .long("package")
+                        .value_name("MEMBER")
+                        .help("运行指定工作区成员的脚本")
+                        .takes_value(true),
                 ),
         )
         .subcommand(
             SubCommand::with_name("test")
                 .about("运行测试")
                 .arg(Arg::with_name("TEST_CLASS").help("测试类名").index(1))
+                .arg(
+                    Arg::with_name("profile")
+                        .long("profile")
+                        .value_name("PROFILE")
+                        .help("构建配置: 内置的debug、release或jx.toml中的[profile.<name>]")
+                        .takes_value(true),
+                )
                 .arg(
                     Arg::with_name("method")
                         .long("method")
                         .help("测试方法名")
                         .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("package")
+                        .short('p')
+                        .long("package")
+                        .value_name("MEMBER")
+                        .help("只测试指定的工作区成员或Maven/Gradle子模块，可重复")
+                        .takes_value(true)
+                        .multiple_occurrences(true),
                 ),
         )
         .subcommand(SubCommand::with_name("clean").about("清理构建文件"))
         .subcommand(SubCommand::with_name("info").about("显示项目信息"))
         .subcommand(
-            SubCommand::with_name("tree").about("显示依赖树").arg(
-                Arg::with_name("transitive")
-                    .long("transitive")
-                    .help("显示传递依赖"),
-            ),
+            SubCommand::with_name("tree")
+                .about("显示依赖树")
+                .arg(
+                    Arg::with_name("transitive")
+                        .long("transitive")
+                        .help("显示传递依赖"),
+                )
+                .arg(
+                    Arg::with_name("package")
+                        .short('p')
+                        .long("package")
+                        .value_name("MODULE")
+                        .help("只显示指定模块或工作区成员的依赖树，可重复")
+                        .takes_value(true)
+                        .multiple_occurrences(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("config")
+                .about("管理jx.toml和jx的用户、项目配置")
+                .subcommand(
+                    SubCommand::with_name("validate")
+                        .about("校验jx.toml，报告出错的行和列")
+                        .arg(
+                            Arg::with_name("FILE")
+                                .help("配置文件路径 (默认当前目录的jx.toml)")
+                                .index(1),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("get")
+                        .about("查看合并后的配置项")
+                        .arg(
+                            Arg::with_name("KEY")
+                                .help("配置项，例如 net.offline")
+                                .required(true)
+                                .index(1),
+                        )
+                        .arg(
+                            Arg::with_name("show-origin")
+                                .long("show-origin")
+                                .help("显示配置值的来源"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("set")
+                        .about("写入用户配置 (~/.jx/config.toml)，或用--local写入项目的.jx/config.toml")
+                        .arg(
+                            Arg::with_name("KEY")
+                                .help("配置项，例如 repositories.mirror")
+                                .required(true)
+                                .index(1),
+                        )
+                        .arg(
+                            Arg::with_name("VALUE")
+                                .help("配置值")
+                                .required(true)
+                                .index(2),
+                        )
+                        .arg(
+                            Arg::with_name("local")
+                                .long("local")
+                                .help("写入项目的.jx/config.toml"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("列出合并后的所有配置项")
+                        .arg(
+                            Arg::with_name("show-origin")
+                                .long("show-origin")
+                                .help("显示配置值的来源"),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about("校验lib/和本地缓存与jx.lock是否一致")
+                .arg(
+                    Arg::with_name("fix")
+                        .long("fix")
+                        .help("删除多余的jar并恢复锁定的依赖"),
+                )
+                .arg(
+                    Arg::with_name("production")
+                        .long("production")
+                        .help("按 'jx install --production' 校验，lib/ 中不应有test和provided依赖"),
+                ),
         )
         .subcommand(
             SubCommand::with_name("search")
@@ -163,6 +484,70 @@ async fn main() {
                         .help("最大结果数")
                         .default_value("20")
                         .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("reindex")
+                        .long("reindex")
+                        .help("重建本地缓存索引"),
+                )
+                .arg(
+                    Arg::with_name("page")
+                        .short('p')
+                        .long("page")
+                        .help("Maven Central结果页码")
+                        .default_value("1")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("group")
+                        .short('g')
+                        .long("group")
+                        .help("按groupId过滤")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("packaging")
+                        .long("packaging")
+                        .help("按打包类型过滤 (jar, pom, aar...)")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("all-versions")
+                        .long("all-versions")
+                        .help("列出匹配构件的所有版本"),
+                )
+                .arg(
+                    Arg::with_name("sort")
+                        .long("sort")
+                        .help("当前页排序方式")
+                        .possible_values(["relevance", "name", "updated", "version"])
+                        .default_value("relevance")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("以JSON输出Maven Central结果"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("which-class")
+                .about("查找提供指定类的构件")
+                .arg(
+                    Arg::with_name("CLASS")
+                        .help("类名 (例如 org.slf4j.LoggerFactory)")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("remote")
+                        .long("remote")
+                        .help("同时查询Maven Central的类名索引"),
+                )
+                .arg(
+                    Arg::with_name("add")
+                        .long("add")
+                        .help("直接添加最佳匹配的依赖"),
                 ),
         )
         .subcommand(
@@ -175,8 +560,7 @@ async fn main() {
                         .arg(
                             Arg::with_name("java-version")
                                 .long("java-version")
-                                .help("Java版本 (8, 11, 17, 21)")
-                                .default_value("17")
+                                .help("Java版本 (8, 11, 17, 21)，默认为配置项 java.version")
                                 .takes_value(true),
                         )
                         .arg(
@@ -220,7 +604,28 @@ async fn main() {
         .get_matches();
 
     let verbose = matches.is_present("verbose");
-    let quiet = matches.is_present("quiet");
+    // JSON输出需要保持stdout干净
+    let quiet = matches.is_present("quiet")
+        || matches
+            .subcommand_matches("search")
+            .is_some_and(|m| m.is_present("json"))
+        || matches
+            .subcommand_matches("lock")
+            .and_then(|m| m.subcommand_matches("diff"))
+            .is_some_and(|m| m.is_present("json"))
+        || matches
+            .subcommand_matches("lock")
+            .and_then(|m| m.subcommand_matches("export"))
+            .is_some_and(|m| !m.is_present("output"));
+
+    // 配置有错误时只有config子命令可以继续执行，以便查看和修正
+    if let Err(e) = settings::init(&std::env::current_dir().unwrap_or_default()) {
+        if matches.subcommand_name() != Some("config") {
+            eprintln!("❌ 错误: {}", e);
+            process::exit(1);
+        }
+    }
+    let offline = config::is_offline(matches.is_present("offline"));
 
     // 设置日志级别
     if verbose {
@@ -250,32 +655,78 @@ async fn main() {
             let file = install_matches.value_of("file").map(|s| s.to_string());
             let production = install_matches.is_present("production");
             let force = install_matches.is_present("force");
-            commands::install::execute(file, production, force)
+            // --frozen 等同于 --locked --offline
+            let frozen = install_matches.is_present("frozen");
+            let locked = frozen || install_matches.is_present("locked");
+            commands::install::execute(file, production, force, offline || frozen, locked).await
         }
+        Some(("lock", lock_matches)) => match lock_matches.subcommand() {
+            Some(("diff", diff_matches)) => {
+                let old = diff_matches.value_of("OLD").map(|s| s.to_string());
+                let new = diff_matches.value_of("NEW").map(|s| s.to_string());
+                let json = diff_matches.is_present("json");
+                commands::lock::diff(old, new, json)
+            }
+            Some(("export", export_matches)) => {
+                let format = export_matches.value_of("format").unwrap().to_string();
+                let output = export_matches.value_of("output").map(|s| s.to_string());
+                let group_id = export_matches.value_of("group-id").map(|s| s.to_string());
+                commands::lock::export(format, output, group_id)
+            }
+            Some(("import", import_matches)) => {
+                let file = import_matches.value_of("FILE").unwrap().to_string();
+                commands::lock::import(file, offline).await
+            }
+            _ => {
+                let check = lock_matches.is_present("check");
+                let upgrade_packages = lock_matches
+                    .values_of("upgrade-package")
+                    .map(|values| values.map(|s| s.to_string()).collect())
+                    .unwrap_or_default();
+                commands::lock::execute(check, offline, upgrade_packages).await
+            }
+        },
         Some(("add", add_matches)) => {
             let dependency = add_matches.value_of("DEPENDENCY").unwrap().to_string();
             let scope = add_matches
                 .value_of("scope")
                 .unwrap_or("compile")
                 .to_string();
-            commands::add::execute(dependency, scope)
+            let managed = add_matches.is_present("managed");
+            let alias = add_matches.value_of("alias").map(|s| s.to_string());
+            let package = add_matches.value_of("package").map(|s| s.to_string());
+            commands::add::execute(dependency, scope, offline, managed, alias, package).await
         }
         Some(("remove", remove_matches)) => {
             let dependency = remove_matches.value_of("DEPENDENCY").unwrap().to_string();
-            commands::remove::execute(dependency)
+            let managed = remove_matches.is_present("managed");
+            let package = remove_matches.value_of("package").map(|s| s.to_string());
+            commands::remove::execute(dependency, managed, package)
         }
         Some(("update", update_matches)) => {
             let dependency = update_matches.value_of("DEPENDENCY").map(|s| s.to_string());
             let latest = update_matches.is_present("latest");
-            commands::update::execute(dependency, latest)
+            let managed = update_matches.is_present("managed");
+            let package = update_matches.value_of("package").map(|s| s.to_string());
+            commands::update::execute(dependency, latest, offline, managed, package).await
         }
         Some(("build", build_matches)) => {
-            let mode = build_matches
-                .value_of("mode")
-                .unwrap_or("debug")
+            let profile = build_matches
+                .value_of("profile")
+                .unwrap_or(profile::DEFAULT_PROFILE)
                 .to_string();
             let no_test = build_matches.is_present("no-test");
-            commands::build::execute(mode, no_test)
+            let packages: Vec<String> = build_matches
+                .values_of("package")
+                .map(|values| values.map(|s| s.to_string()).collect())
+                .unwrap_or_default();
+            commands::build::execute(profile, no_test, offline, packages)
+        }
+        Some(("package", package_matches)) => {
+            let profile = package_matches.value_of("profile").unwrap_or("release").to_string();
+            let package = package_matches.value_of("package").map(|s| s.to_string());
+            let uber = package_matches.is_present("uber");
+            commands::package::execute(profile, offline, package, uber)
         }
         Some(("run", run_matches)) => {
             let main_class = run_matches.value_of("MAIN_CLASS").map(|s| s.to_string());
@@ -284,18 +735,66 @@ async fn main() {
                 .unwrap_or_default()
                 .map(|s| s.to_string())
                 .collect();
-            commands::run::execute(main_class, args)
+            let package = run_matches.value_of("package").map(|s| s.to_string());
+            let profile = run_matches
+                .value_of("profile")
+                .unwrap_or(profile::DEFAULT_PROFILE)
+                .to_string();
+            commands::run::execute(main_class, args, offline, package, profile)
         }
         Some(("test", test_matches)) => {
             let test_class = test_matches.value_of("TEST_CLASS").map(|s| s.to_string());
             let method = test_matches.value_of("method").map(|s| s.to_string());
-            commands::test::execute(test_class, method)
+            let packages: Vec<String> = test_matches
+                .values_of("package")
+                .map(|values| values.map(|s| s.to_string()).collect())
+                .unwrap_or_default();
+            let profile = test_matches
+                .value_of("profile")
+                .unwrap_or(profile::DEFAULT_PROFILE)
+                .to_string();
+            commands::test::execute(test_class, method, offline, packages, profile)
         }
         Some(("clean", _)) => commands::clean::execute(),
         Some(("info", _)) => commands::info::execute(),
         Some(("tree", tree_matches)) => {
             let transitive = tree_matches.is_present("transitive");
-            commands::tree::execute(transitive)
+            let packages: Vec<String> = tree_matches
+                .values_of("package")
+                .map(|values| values.map(|s| s.to_string()).collect())
+                .unwrap_or_default();
+            commands::tree::execute(transitive, offline, packages)
+        }
+        Some(("config", config_matches)) => match config_matches.subcommand() {
+            Some(("validate", validate_matches)) => {
+                let file = validate_matches.value_of("FILE").map(|s| s.to_string());
+                commands::config::validate(file)
+            }
+            Some(("get", get_matches)) => {
+                let key = get_matches.value_of("KEY").unwrap();
+                commands::config::get(key, get_matches.is_present("show-origin"))
+            }
+            Some(("set", set_matches)) => {
+                let key = set_matches.value_of("KEY").unwrap();
+                let value = set_matches.value_of("VALUE").unwrap();
+                commands::config::set(key, value, set_matches.is_present("local"))
+            }
+            Some(("list", list_matches)) => {
+                commands::config::list(list_matches.is_present("show-origin"))
+            }
+            _ => {
+                println!("使用方法:");
+                println!("  jx config validate [FILE]");
+                println!("  jx config get <KEY> [--show-origin]");
+                println!("  jx config set <KEY> <VALUE> [--local]");
+                println!("  jx config list [--show-origin]");
+                Ok(())
+            }
+        },
+        Some(("verify", verify_matches)) => {
+            let fix = verify_matches.is_present("fix");
+            let production = verify_matches.is_present("production");
+            commands::verify::execute(fix, production, offline).await
         }
         Some(("search", search_matches)) => {
             let query = search_matches.value_of("QUERY").unwrap().to_string();
@@ -304,7 +803,45 @@ async fn main() {
                 .unwrap_or("20")
                 .parse()
                 .unwrap_or(20);
-            commands::search::execute(query, limit)
+            let page = search_matches
+                .value_of("page")
+                .unwrap_or("1")
+                .parse()
+                .unwrap_or(1);
+            let sort = search_matches.value_of("sort").unwrap_or("relevance");
+            match registry::SearchSort::from_name(sort) {
+                Ok(sort) => {
+                    let options = commands::search::SearchOptions {
+                        limit,
+                        page,
+                        group: search_matches.value_of("group").map(|s| s.to_string()),
+                        packaging: search_matches.value_of("packaging").map(|s| s.to_string()),
+                        all_versions: search_matches.is_present("all-versions"),
+                        sort,
+                        json: search_matches.is_present("json"),
+                        offline,
+                        reindex: search_matches.is_present("reindex"),
+                    };
+                    commands::search::execute(query, options).await
+                }
+                Err(e) => Err(e),
+            }
+        }
+        Some(("run-script", script_matches)) => {
+            let name = script_matches.value_of("NAME").map(|s| s.to_string());
+            let args: Vec<String> = script_matches
+                .values_of("ARGS")
+                .unwrap_or_default()
+                .map(|s| s.to_string())
+                .collect();
+            let package = script_matches.value_of("package").map(|s| s.to_string());
+            commands::run_script::execute(name, args, package)
+        }
+        Some(("which-class", which_matches)) => {
+            let class_name = which_matches.value_of("CLASS").unwrap().to_string();
+            let remote = which_matches.is_present("remote");
+            let add = which_matches.is_present("add");
+            commands::which_class::execute(class_name, remote, offline, add).await
         }
         Some(("venv", venv_matches)) => {
             match venv_matches.subcommand() {
@@ -312,7 +849,7 @@ async fn main() {
                     let name = create_matches.value_of("NAME").map(|s| s.to_string());
                     let java_version = create_matches
                         .value_of("java-version")
-                        .unwrap_or("17")
+                        .unwrap_or_else(|| settings::current().java_version())
                         .to_string();
 
                     // 确定构建工具类型
@@ -363,23 +900,43 @@ async fn main() {
                 }
             }
         }
+        Some((name, external_matches)) => {
+            let args: Vec<String> = external_matches
+                .values_of("")
+                .unwrap_or_default()
+                .map(|s| s.to_string())
+                .collect();
+            commands::run_script::fallback(name, args)
+        }
         _ => {
             println!("jx - Fast Java Package Manager");
             println!("");
             println!("使用方法:");
             println!("  jx init [NAME] --template <maven|gradle>  # 初始化新项目");
-            println!("  jx install [--production] [--force]       # 安装依赖");
+            println!("  jx install [--production] [--force] [--locked|--frozen]  # 安装依赖");
+            println!("  jx lock [--check] [--upgrade-package G:A[:V]]  # 生成、检查或选择性升级jx.lock");
+            println!("  jx lock diff [OLD] [NEW] [--json]          # 比较锁文件或git版本");
+            println!("  jx lock export --format <gradle|maven|bom> [-o FILE]  # 导出版本锁定");
+            println!("  jx lock import [gradle.lockfile]           # 从gradle.lockfile生成jx.lock");
             println!("  jx add <DEPENDENCY> [--scope SCOPE]       # 添加依赖");
             println!("  jx remove <DEPENDENCY>                     # 移除依赖");
             println!("  jx update [DEPENDENCY] [--latest]          # 更新依赖");
-            println!("  jx build [--mode <debug|release>]          # 构建项目");
-            println!("  jx run [MAIN_CLASS] [ARGS...]             # 运行项目");
-            println!("  jx test [TEST_CLASS] [--method METHOD]     # 运行测试");
+            println!("  jx build [--profile PROFILE] [-p MEMBER]  # 构建项目或工作区成员");
+            println!("  jx package [--uber] [--profile PROFILE] [-p MEMBER]  # 打包为jar或uber jar");
+            println!("  jx run [MAIN_CLASS] [ARGS...] [--profile PROFILE]  # 运行项目");
+            println!("  jx test [TEST_CLASS] [--method METHOD] [--profile PROFILE] [-p MEMBER]  # 运行测试");
+            println!("  jx run-script [NAME] [ARGS...]            # 运行jx.toml中的脚本，也可以直接 jx <NAME>");
             println!("  jx clean                                  # 清理构建文件");
             println!("  jx info                                   # 显示项目信息");
             println!("  jx tree [--transitive]                     # 显示依赖树");
-            println!("  jx search <QUERY> [--limit N]              # 搜索依赖");
+            println!("  jx verify [--fix] [--production]           # 校验lib/与jx.lock");
+            println!("  jx config validate [FILE]                  # 校验jx.toml");
+            println!("  jx config get|set|list [--show-origin]     # 查看或修改用户、项目配置");
+            println!("  jx search <QUERY> [--limit N] [--page N] [--group G] [--packaging P]");
+            println!("            [--all-versions] [--sort ORDER] [--json] [--reindex]  # 搜索依赖");
+            println!("  jx which-class <CLASS> [--remote] [--add]  # 查找提供类的构件");
             println!("  jx venv <COMMAND>                          # 管理虚拟环境");
+            println!("  jx --offline <COMMAND>                     # 离线模式，仅使用本地缓存");
             println!("  jx --help                                 # 查看详细帮助");
             Ok(())
         }
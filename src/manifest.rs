@@ -0,0 +1,256 @@
+use crate::config::{quote_legacy_keys, JxConfig};
+use crate::dependency::DependencyScope;
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{Decor, Document, Item, Table, Value};
+
+/// 保留格式和注释的jx.toml编辑器
+///
+/// 只改动被编辑的依赖条目，其余内容按原样写回。
+/// compile依赖写在`[dependencies]`中，其他scope写在`[dependencies.<scope>]`中，表内按坐标排序
+pub struct ManifestEditor {
+    path: PathBuf,
+    document: Document,
+    /// 原文件使用CRLF换行，写回时保持
+    crlf: bool,
+}
+
+impl ManifestEditor {
+    pub fn open(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        // 先按类型化模型校验，出错时报告行列
+        JxConfig::parse(&content, path)?;
+        // 旧格式的依赖坐标会改写成带引号的形式
+        let document = quote_legacy_keys(&content).parse::<Document>()?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            document,
+            crlf: content.contains("\r\n"),
+        })
+    }
+
+    /// 添加依赖，已声明时更新版本并移到指定的scope，返回原来的版本和scope
+    pub fn upsert_dependency(
+        &mut self,
+        key: &str,
+        version: &str,
+        scope: &DependencyScope,
+    ) -> Option<(String, String)> {
+        let target = scope_table_name(scope);
+
+        if let Some(location) = self.locate(key) {
+            let old_scope = location_scope(location.as_deref()).to_string();
+            if old_scope == scope.as_str() {
+                let item = self.table_mut(location.as_deref())?.get_mut(key)?;
+                return set_item_version(item, version).map(|old| (old, old_scope));
+            }
+
+            let mut item = self.take_entry(location.as_deref(), key)?;
+            let old_version = set_item_version(&mut item, version)?;
+            insert_sorted(self.table_or_insert(target), key, item);
+            return Some((old_version, old_scope));
+        }
+
+        insert_sorted(
+            self.table_or_insert(target),
+            key,
+            toml_edit::value(version),
+        );
+        None
+    }
+
+    /// 从所有scope表中移除依赖
+    pub fn remove_dependency(&mut self, key: &str) -> bool {
+        let Some(location) = self.locate(key) else {
+            return false;
+        };
+        self.take_entry(location.as_deref(), key).is_some()
+    }
+
+    /// 修改已声明依赖的版本，返回原来的版本
+    pub fn set_version(&mut self, key: &str, version: &str) -> Option<String> {
+        let location = self.locate(key)?;
+        let item = self.table_mut(location.as_deref())?.get_mut(key)?;
+        set_item_version(item, version)
+    }
+
+    /// 写回前再次校验，保证不会写出无效的jx.toml
+    pub fn save(&self) -> Result<()> {
+        let mut content = self.document.to_string();
+        if self.crlf {
+            content = content.replace("\r\n", "\n").replace('\n', "\r\n");
+        }
+        JxConfig::parse(&content, &self.path)?;
+        fs::write(&self.path, content)?;
+        Ok(())
+    }
+
+    /// 依赖所在的表: None为`[dependencies]`，Some(scope)为`[dependencies.<scope>]`
+    fn locate(&self, key: &str) -> Option<Option<String>> {
+        let dependencies = self.document.get("dependencies")?.as_table()?;
+        if dependencies.contains_key(key) {
+            return Some(None);
+        }
+
+        dependencies
+            .iter()
+            .filter(|(name, _)| !name.contains(':'))
+            .find(|(_, item)| {
+                item.as_table()
+                    .is_some_and(|table| table.contains_key(key))
+            })
+            .map(|(name, _)| Some(name.to_string()))
+    }
+
+    /// 移除条目，scope子表因此变空时一并删除
+    fn take_entry(&mut self, scope: Option<&str>, key: &str) -> Option<Item> {
+        let item = remove_entry(self.table_mut(scope)?, key)?;
+        if let Some(scope) = scope {
+            if self.table_mut(Some(scope)).is_some_and(|table| table.is_empty()) {
+                self.table_mut(None)?.remove(scope);
+            }
+        }
+        Some(item)
+    }
+
+    fn table_mut(&mut self, scope: Option<&str>) -> Option<&mut Table> {
+        let dependencies = self.document.get_mut("dependencies")?.as_table_mut()?;
+        match scope {
+            None => Some(dependencies),
+            Some(scope) => dependencies.get_mut(scope)?.as_table_mut(),
+        }
+    }
+
+    fn table_or_insert(&mut self, scope: Option<&str>) -> &mut Table {
+        let dependencies = self
+            .document
+            .entry("dependencies")
+            .or_insert_with(|| {
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            })
+            .as_table_mut()
+            .expect("[dependencies] 必须是表");
+
+        match scope {
+            None => {
+                dependencies.set_implicit(false);
+                dependencies
+            }
+            Some(scope) => dependencies
+                .entry(scope)
+                .or_insert_with(|| Item::Table(Table::new()))
+                .as_table_mut()
+                .expect("[dependencies.<scope>] 必须是表"),
+        }
+    }
+}
+
+/// compile依赖直接写在`[dependencies]`中
+fn scope_table_name(scope: &DependencyScope) -> Option<&'static str> {
+    match scope {
+        DependencyScope::Compile => None,
+        other => Some(other.as_str()),
+    }
+}
+
+fn location_scope(location: Option<&str>) -> &str {
+    location.unwrap_or("compile")
+}
+
+/// 表中依赖条目的键，不包括scope子表
+fn dependency_keys(table: &Table) -> Vec<String> {
+    table
+        .iter()
+        .filter(|(key, _)| key.contains(':'))
+        .map(|(key, _)| key.to_string())
+        .collect()
+}
+
+/// 按坐标顺序插入，不改变已有条目之间的相对顺序
+fn insert_sorted(table: &mut Table, key: &str, item: Item) {
+    let keys = dependency_keys(table);
+    let Some(index) = keys.iter().position(|existing| existing.as_str() > key) else {
+        table.insert(key, item);
+        return;
+    };
+
+    // 插到最前面时，表头下的注释 (例如分组说明) 仍然留在最前面
+    let header_comment = if index == 0 {
+        table
+            .key_decor_mut(&keys[0])
+            .and_then(take_comment)
+    } else {
+        None
+    };
+
+    let tail: Vec<_> = keys[index..]
+        .iter()
+        .filter_map(|existing| table.remove_entry(existing))
+        .collect();
+    table.insert(key, item);
+    if let (Some(comment), Some(decor)) = (header_comment, table.key_decor_mut(key)) {
+        decor.set_prefix(comment);
+    }
+    for (existing_key, existing_item) in tail {
+        table.insert_formatted(&existing_key, existing_item);
+    }
+}
+
+/// 移除条目；它上方的注释 (例如分组说明) 转给下一个条目
+fn remove_entry(table: &mut Table, key: &str) -> Option<Item> {
+    let keys = dependency_keys(table);
+    let next = keys
+        .iter()
+        .position(|existing| existing == key)
+        .and_then(|index| keys.get(index + 1))
+        .cloned();
+
+    let (mut removed_key, item) = table.remove_entry(key)?;
+    let comment = take_comment(removed_key.decor_mut());
+
+    if let (Some(comment), Some(next)) = (comment, next) {
+        if let Some(decor) = table.key_decor_mut(&next) {
+            let existing = decor
+                .prefix()
+                .and_then(|prefix| prefix.as_str())
+                .unwrap_or("")
+                .to_string();
+            decor.set_prefix(format!("{}{}", comment, existing));
+        }
+    }
+
+    Some(item)
+}
+
+/// 取出键上方的注释行，没有注释时返回None
+fn take_comment(decor: &mut Decor) -> Option<String> {
+    let prefix = decor.prefix()?.as_str()?.to_string();
+    if !prefix.contains('#') {
+        return None;
+    }
+    decor.set_prefix("");
+    Some(prefix)
+}
+
+/// 支持 `"1.0"`、`{ version = "1.0", .. }` 和 `[dependencies."g:a"]` 三种写法
+fn set_item_version(item: &mut Item, version: &str) -> Option<String> {
+    match item {
+        Item::Value(Value::InlineTable(table)) => replace_string(table.get_mut("version")?, version),
+        Item::Value(value) => replace_string(value, version),
+        Item::Table(table) => replace_string(table.get_mut("version")?.as_value_mut()?, version),
+        _ => None,
+    }
+}
+
+/// 替换字符串的值，保留等号两边的空白和行尾注释
+pub(crate) fn replace_string(value: &mut Value, version: &str) -> Option<String> {
+    let old = value.as_str()?.to_string();
+    let decor = value.decor().clone();
+    *value = Value::from(version);
+    *value.decor_mut() = decor;
+    Some(old)
+}
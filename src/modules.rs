@@ -0,0 +1,156 @@
+use crate::gradle;
+use crate::pom::parse_pom;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maven聚合工程 (`<modules>`) 或Gradle多项目构建 (`include`) 中的子模块
+#[derive(Debug)]
+pub struct Module {
+    /// 传给构建工具的标识: Maven为`-pl`使用的相对路径，Gradle为项目路径 (例如`:services:api`)
+    pub id: String,
+    /// Maven为artifactId，Gradle为项目路径的最后一段
+    pub name: String,
+    /// 相对于根项目的目录
+    pub path: String,
+    pub dir: PathBuf,
+}
+
+/// 项目目录中的子模块，与其他命令一样pom.xml优先于Gradle脚本
+pub fn detect(project_dir: &Path) -> Result<Vec<Module>> {
+    if project_dir.join("pom.xml").exists() {
+        maven_modules(project_dir)
+    } else if gradle::is_gradle_project(project_dir) {
+        Ok(gradle_modules(project_dir))
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// `-p`选择的子模块，名称可以是模块名、相对路径或Gradle项目路径；没有指定时为空，表示整个项目
+pub fn select(project_dir: &Path, names: &[String]) -> Result<Vec<Module>> {
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut modules = detect(project_dir)?;
+    if modules.is_empty() {
+        return Err(anyhow::anyhow!(
+            "-p 只能用于jx工作区、Maven聚合工程或Gradle多项目构建"
+        ));
+    }
+
+    let mut selected = Vec::new();
+    for name in names {
+        let name = name.trim_end_matches('/');
+        let index = modules
+            .iter()
+            .position(|module| {
+                module.name == name
+                    || module.path == name
+                    || module.id == name
+                    || module.id.trim_start_matches(':') == name
+            })
+            .ok_or_else(|| {
+                let available: Vec<&str> = modules.iter().map(|m| m.name.as_str()).collect();
+                anyhow::anyhow!("项目中没有模块 {}，可选: {}", name, available.join(", "))
+            })?;
+        selected.push(modules.remove(index));
+    }
+    Ok(selected)
+}
+
+/// Maven选择模块的参数: 只构建选中的模块及其依赖的模块
+pub fn maven_arguments(modules: &[Module]) -> Vec<String> {
+    if modules.is_empty() {
+        return Vec::new();
+    }
+    let projects: Vec<&str> = modules.iter().map(|module| module.id.as_str()).collect();
+    vec!["-pl".to_string(), projects.join(","), "-am".to_string()]
+}
+
+/// 选中模块时Gradle任务写成`:module:task`，否则作用于整个构建
+pub fn gradle_tasks(modules: &[Module], tasks: &[&str]) -> Vec<String> {
+    tasks
+        .iter()
+        .flat_map(|task| {
+            if modules.is_empty() {
+                vec![task.to_string()]
+            } else {
+                modules
+                    .iter()
+                    .map(|module| format!("{}:{}", module.id, task))
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+/// 递归读取`<modules>`，子模块本身也可以是聚合工程
+fn maven_modules(root: &Path) -> Result<Vec<Module>> {
+    let mut modules = Vec::new();
+    collect_maven_modules(root, root, &mut modules)?;
+    Ok(modules)
+}
+
+fn collect_maven_modules(root: &Path, dir: &Path, modules: &mut Vec<Module>) -> Result<()> {
+    let pom_path = dir.join("pom.xml");
+    let content = fs::read_to_string(&pom_path)
+        .with_context(|| format!("无法读取 {}", pom_path.display()))?;
+    let pom = parse_pom(&content).with_context(|| format!("无法解析 {}", pom_path.display()))?;
+
+    for module in &pom.modules {
+        // <module>也可以直接指向子模块的pom文件
+        let module_dir = match module.strip_suffix(".xml") {
+            Some(_) => dir.join(module).parent().map(Path::to_path_buf).unwrap_or_default(),
+            None => dir.join(module),
+        };
+        if !module_dir.join("pom.xml").exists() {
+            return Err(anyhow::anyhow!(
+                "{} 中的模块 {} 找不到pom.xml",
+                pom_path.display(),
+                module
+            ));
+        }
+
+        let path = relative_path(root, &module_dir);
+        let module_pom = parse_pom(&fs::read_to_string(module_dir.join("pom.xml"))?)?;
+        let name = module_pom
+            .artifact_id
+            .unwrap_or_else(|| last_segment(&path).to_string());
+
+        modules.push(Module {
+            id: path.clone(),
+            name,
+            path,
+            dir: module_dir.clone(),
+        });
+        collect_maven_modules(root, &module_dir, modules)?;
+    }
+    Ok(())
+}
+
+fn gradle_modules(root: &Path) -> Vec<Module> {
+    gradle::included_projects(root)
+        .into_iter()
+        .map(|(id, dir)| Module {
+            name: last_segment(&id.replace(':', "/")).to_string(),
+            path: relative_path(root, &dir),
+            id,
+            dir,
+        })
+        .collect()
+}
+
+fn relative_path(root: &Path, dir: &Path) -> String {
+    dir.strip_prefix(root)
+        .unwrap_or(dir)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
@@ -0,0 +1,327 @@
+use anyhow::{Context, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// POM中用于展示和搜索的元数据
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PomInfo {
+    pub group_id: Option<String>,
+    pub artifact_id: Option<String>,
+    pub version: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub licenses: Vec<String>,
+}
+
+pub fn parse_pom_info(content: &str) -> Result<PomInfo> {
+    let mut reader = Reader::from_str(content);
+    reader.trim_text(true);
+
+    let mut info = PomInfo::default();
+    let mut path: Vec<String> = Vec::new();
+
+    loop {
+        match reader.read_event().context("解析POM失败")? {
+            Event::Start(e) => {
+                path.push(String::from_utf8_lossy(e.local_name().as_ref()).to_string());
+            }
+            Event::End(_) => {
+                path.pop();
+            }
+            Event::Text(t) => {
+                let text = t.unescape().context("解析POM文本失败")?.trim().to_string();
+                let path: Vec<&str> = path.iter().map(|s| s.as_str()).collect();
+
+                match path.as_slice() {
+                    ["project", "groupId"] => info.group_id = Some(text),
+                    ["project", "artifactId"] => info.artifact_id = Some(text),
+                    ["project", "version"] => info.version = Some(text),
+                    ["project", "name"] => info.name = Some(text),
+                    ["project", "description"] => {
+                        // 描述经常跨多行，压缩空白
+                        info.description = Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+                    }
+                    ["project", "licenses", "license", "name"] => info.licenses.push(text),
+                    // 没有显式groupId/version时继承parent
+                    ["project", "parent", "groupId"] if info.group_id.is_none() => {
+                        info.group_id = Some(text)
+                    }
+                    ["project", "parent", "version"] if info.version.is_none() => {
+                        info.version = Some(text)
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(info)
+}
+
+/// POM中的依赖声明，版本和scope可能来自dependencyManagement
+#[derive(Debug, Clone, Default)]
+pub struct PomDependency {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: Option<String>,
+    pub scope: Option<String>,
+    pub classifier: Option<String>,
+    pub type_: Option<String>,
+    pub optional: bool,
+    pub exclusions: Vec<(String, String)>,
+}
+
+impl PomDependency {
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.group_id, self.artifact_id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PomParent {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: String,
+}
+
+/// 依赖解析需要的POM模型
+#[derive(Debug, Clone, Default)]
+pub struct Pom {
+    pub group_id: Option<String>,
+    pub artifact_id: Option<String>,
+    pub version: Option<String>,
+    pub packaging: Option<String>,
+    pub parent: Option<PomParent>,
+    pub properties: HashMap<String, String>,
+    pub dependencies: Vec<PomDependency>,
+    pub dependency_management: Vec<PomDependency>,
+    /// 聚合工程`<modules>`中的子模块目录
+    pub modules: Vec<String>,
+}
+
+pub fn parse_pom(content: &str) -> Result<Pom> {
+    let mut reader = Reader::from_str(content);
+    reader.trim_text(true);
+
+    let mut pom = Pom::default();
+    let mut path: Vec<String> = Vec::new();
+    let mut parent = PomParent {
+        group_id: String::new(),
+        artifact_id: String::new(),
+        version: String::new(),
+    };
+    let mut dependency = PomDependency::default();
+    let mut exclusion = (String::new(), String::new());
+
+    loop {
+        match reader.read_event().context("解析POM失败")? {
+            Event::Start(e) => {
+                path.push(String::from_utf8_lossy(e.local_name().as_ref()).to_string());
+            }
+            Event::End(_) => {
+                let path_ref: Vec<&str> = path.iter().map(|s| s.as_str()).collect();
+                match path_ref.as_slice() {
+                    ["project", "parent"] => pom.parent = Some(parent.clone()),
+                    ["project", "dependencies", "dependency"] => {
+                        pom.dependencies.push(std::mem::take(&mut dependency))
+                    }
+                    ["project", "dependencyManagement", "dependencies", "dependency"] => {
+                        pom.dependency_management.push(std::mem::take(&mut dependency))
+                    }
+                    ["project", "dependencies", "dependency", "exclusions", "exclusion"]
+                    | ["project", "dependencyManagement", "dependencies", "dependency", "exclusions", "exclusion"] => {
+                        dependency.exclusions.push(std::mem::take(&mut exclusion))
+                    }
+                    _ => {}
+                }
+                path.pop();
+            }
+            Event::Text(t) => {
+                let text = t.unescape().context("解析POM文本失败")?.trim().to_string();
+                let path_ref: Vec<&str> = path.iter().map(|s| s.as_str()).collect();
+
+                // profiles和build/plugins下的依赖不参与解析
+                let dependency_path = match path_ref.as_slice() {
+                    ["project", "dependencies", "dependency", rest @ ..]
+                    | ["project", "dependencyManagement", "dependencies", "dependency", rest @ ..] => {
+                        Some(rest)
+                    }
+                    _ => None,
+                };
+
+                if let Some(field) = dependency_path {
+                    match field {
+                        ["groupId"] => dependency.group_id = text,
+                        ["artifactId"] => dependency.artifact_id = text,
+                        ["version"] => dependency.version = Some(text),
+                        ["scope"] => dependency.scope = Some(text),
+                        ["classifier"] => dependency.classifier = Some(text),
+                        ["type"] => dependency.type_ = Some(text),
+                        ["optional"] => dependency.optional = text == "true",
+                        ["exclusions", "exclusion", "groupId"] => exclusion.0 = text,
+                        ["exclusions", "exclusion", "artifactId"] => exclusion.1 = text,
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                match path_ref.as_slice() {
+                    ["project", "groupId"] => pom.group_id = Some(text),
+                    ["project", "artifactId"] => pom.artifact_id = Some(text),
+                    ["project", "version"] => pom.version = Some(text),
+                    ["project", "packaging"] => pom.packaging = Some(text),
+                    ["project", "parent", "groupId"] => parent.group_id = text,
+                    ["project", "parent", "artifactId"] => parent.artifact_id = text,
+                    ["project", "parent", "version"] => parent.version = text,
+                    ["project", "properties", name] => {
+                        pom.properties.insert(name.to_string(), text);
+                    }
+                    ["project", "modules", "module"] => pom.modules.push(text),
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(pom)
+}
+
+/// 读取项目目录中的pom.xml，并合并上级目录中的父POM
+///
+/// 多模块项目的子模块通常从本地父POM继承属性和依赖管理，这里按默认的`../pom.xml`查找，
+/// 上级目录的artifactId与`<parent>`不一致时停止
+pub fn load_local_pom(project_dir: &Path) -> Result<Pom> {
+    let pom_path = project_dir.join("pom.xml");
+    let content = fs::read_to_string(&pom_path)
+        .with_context(|| format!("无法读取 {}", pom_path.display()))?;
+    let mut pom = parse_pom(&content).with_context(|| format!("无法解析 {}", pom_path.display()))?;
+
+    let mut parent = pom.parent.clone();
+    let mut dir = project_dir;
+    while let (Some(expected), Some(parent_dir)) = (parent, dir.parent()) {
+        let Ok(content) = fs::read_to_string(parent_dir.join("pom.xml")) else {
+            break;
+        };
+        let parent_pom = parse_pom(&content)?;
+        if parent_pom.artifact_id.as_deref() != Some(expected.artifact_id.as_str()) {
+            break;
+        }
+
+        pom.inherit(&parent_pom);
+        parent = parent_pom.parent.clone();
+        dir = parent_dir;
+    }
+
+    Ok(pom)
+}
+
+impl Pom {
+    /// 合并父POM: 属性、依赖管理和依赖都会被继承，子POM优先
+    pub fn inherit(&mut self, parent: &Pom) {
+        if self.group_id.is_none() {
+            self.group_id = parent.group_id.clone();
+        }
+        if self.version.is_none() {
+            self.version = parent.version.clone();
+        }
+
+        for (name, value) in &parent.properties {
+            self.properties
+                .entry(name.clone())
+                .or_insert_with(|| value.clone());
+        }
+
+        for managed in &parent.dependency_management {
+            if !self.dependency_management.iter().any(|d| d.key() == managed.key()) {
+                self.dependency_management.push(managed.clone());
+            }
+        }
+
+        for dep in &parent.dependencies {
+            if !self.dependencies.iter().any(|d| d.key() == dep.key()) {
+                self.dependencies.push(dep.clone());
+            }
+        }
+    }
+
+    /// 替换 ${...} 占位符，无法解析的保持原样
+    pub fn interpolate(&self, value: &str) -> String {
+        let mut result = value.to_string();
+
+        // 属性值本身可能引用其他属性，限制展开次数避免循环引用
+        for _ in 0..10 {
+            let start = match result.find("${") {
+                Some(start) => start,
+                None => break,
+            };
+            let end = match result[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+
+            let name = &result[start + 2..end];
+            let replacement = match self.lookup_property(name) {
+                Some(replacement) => replacement,
+                None => break,
+            };
+            result.replace_range(start..=end, &replacement);
+        }
+
+        result
+    }
+
+    fn lookup_property(&self, name: &str) -> Option<String> {
+        match name {
+            "project.groupId" | "pom.groupId" | "groupId" => self.group_id.clone(),
+            "project.artifactId" | "pom.artifactId" | "artifactId" => self.artifact_id.clone(),
+            "project.version" | "pom.version" | "version" => self.version.clone(),
+            "project.parent.groupId" => self.parent.as_ref().map(|p| p.group_id.clone()),
+            "project.parent.version" => self.parent.as_ref().map(|p| p.version.clone()),
+            _ => self.properties.get(name).cloned(),
+        }
+    }
+
+    /// 对依赖的坐标、版本和scope做属性替换
+    pub fn interpolate_dependency(&self, dep: &PomDependency) -> PomDependency {
+        PomDependency {
+            group_id: self.interpolate(&dep.group_id),
+            artifact_id: self.interpolate(&dep.artifact_id),
+            version: dep.version.as_deref().map(|v| self.interpolate(v)),
+            scope: dep.scope.as_deref().map(|s| self.interpolate(s)),
+            classifier: dep.classifier.as_deref().map(|c| self.interpolate(c)),
+            type_: dep.type_.clone(),
+            optional: dep.optional,
+            exclusions: dep.exclusions.clone(),
+        }
+    }
+
+    /// 声明的依赖，做属性替换后用依赖管理补全版本和scope
+    pub fn effective_dependencies(&self) -> Vec<PomDependency> {
+        self.dependencies
+            .iter()
+            .map(|dep| {
+                let mut dep = self.interpolate_dependency(dep);
+                if let Some(managed) = self.managed(&dep.group_id, &dep.artifact_id) {
+                    let managed = self.interpolate_dependency(managed);
+                    dep.version = dep.version.or(managed.version);
+                    dep.scope = dep.scope.or(managed.scope);
+                }
+                dep
+            })
+            .collect()
+    }
+
+    pub fn managed(&self, group_id: &str, artifact_id: &str) -> Option<&PomDependency> {
+        self.dependency_management
+            .iter()
+            .find(|d| d.group_id == group_id && d.artifact_id == artifact_id)
+    }
+}
@@ -0,0 +1,421 @@
+use anyhow::{Context, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 基于quick-xml位置信息的pom.xml编辑器
+///
+/// 只替换或插入被编辑的片段，缩进、注释和其余内容保持原样。
+/// 默认操作项目级的`<project><dependencies>`，`managed`为true时操作`<dependencyManagement>`
+pub struct PomEditor {
+    path: PathBuf,
+    content: String,
+}
+
+/// 版本修改的结果
+pub struct VersionChange {
+    pub old_version: String,
+    /// 版本写成`${property}`时更新的是这个属性
+    pub property: Option<String>,
+    /// 版本由`<dependencyManagement>`管理
+    pub managed: bool,
+}
+
+/// 元素在文本中的位置
+#[derive(Debug)]
+struct Element {
+    name: String,
+    /// `<`的位置
+    start: usize,
+    /// 开始标签之后
+    content_start: usize,
+    /// 结束标签的`<`
+    content_end: usize,
+    /// 结束标签之后
+    end: usize,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn path(&self, names: &[&str]) -> Option<&Element> {
+        names
+            .iter()
+            .try_fold(self, |element, name| element.child(name))
+    }
+}
+
+impl PomEditor {
+    pub fn open(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("无法读取 {}", path.display()))?;
+        parse_project(&content).with_context(|| format!("无法解析 {}", path.display()))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            content,
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        parse_project(&self.content).context("修改后的pom.xml无效")?;
+        fs::write(&self.path, &self.content)?;
+        Ok(())
+    }
+
+    pub fn has_dependency(&self, group_id: &str, artifact_id: &str, managed: bool) -> bool {
+        parse_project(&self.content).is_ok_and(|project| {
+            dependencies_section(&project, managed)
+                .and_then(|section| find_dependency(&self.content, section, group_id, artifact_id))
+                .is_some()
+        })
+    }
+
+    /// 在依赖列表末尾添加依赖，列表不存在时创建
+    pub fn add_dependency(
+        &mut self,
+        group_id: &str,
+        artifact_id: &str,
+        version: Option<&str>,
+        scope: Option<&str>,
+        managed: bool,
+    ) -> Result<()> {
+        let mut lines = vec![
+            (0, "<dependency>".to_string()),
+            (1, format!("<groupId>{}</groupId>", group_id)),
+            (1, format!("<artifactId>{}</artifactId>", artifact_id)),
+        ];
+        if let Some(version) = version {
+            lines.push((1, format!("<version>{}</version>", version)));
+        }
+        if let Some(scope) = scope.filter(|scope| *scope != "compile") {
+            lines.push((1, format!("<scope>{}</scope>", scope)));
+        }
+        lines.push((0, "</dependency>".to_string()));
+
+        let project = parse_project(&self.content)?;
+        let unit = indent_unit(&self.content, &project);
+
+        if let Some(section) = dependencies_section(&project, managed) {
+            self.append_child(section, &lines, &unit);
+            return Ok(());
+        }
+
+        // 依赖列表不存在: 创建<dependencies> (以及<dependencyManagement>)
+        let mut section = vec![(0, "<dependencies>".to_string())];
+        section.extend(lines.into_iter().map(|(depth, line)| (depth + 1, line)));
+        section.push((0, "</dependencies>".to_string()));
+        if managed {
+            section = std::iter::once((0, "<dependencyManagement>".to_string()))
+                .chain(section.into_iter().map(|(depth, line)| (depth + 1, line)))
+                .chain(std::iter::once((0, "</dependencyManagement>".to_string())))
+                .collect();
+        }
+
+        // 放在<build>之前 (管理依赖放在项目依赖之前)，否则追加到<project>末尾
+        let anchor = if managed {
+            project.child("dependencies").or_else(|| project.child("build"))
+        } else {
+            project.child("build")
+        };
+        match anchor {
+            Some(anchor) => self.insert_before(anchor, &section, &unit),
+            None => self.append_child(&project, &section, &unit),
+        }
+        Ok(())
+    }
+
+    /// 移除依赖，返回是否找到
+    pub fn remove_dependency(&mut self, group_id: &str, artifact_id: &str, managed: bool) -> Result<bool> {
+        let project = parse_project(&self.content)?;
+        let Some(dependency) = dependencies_section(&project, managed)
+            .and_then(|section| find_dependency(&self.content, section, group_id, artifact_id))
+        else {
+            return Ok(false);
+        };
+
+        let start = line_start_if_blank(&self.content, dependency.start);
+        let mut end = dependency.end;
+        let rest = &self.content[end..];
+        let trailing = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+        if rest[trailing..].starts_with("\r\n") {
+            end += trailing + 2;
+        } else if rest[trailing..].starts_with('\n') {
+            end += trailing + 1;
+        }
+        self.content.replace_range(start..end, "");
+        Ok(true)
+    }
+
+    /// 修改依赖版本
+    ///
+    /// `${property}`形式的版本更新`<properties>`中的属性；
+    /// 没有`<version>`时更新`<dependencyManagement>`中的条目
+    pub fn set_version(
+        &mut self,
+        group_id: &str,
+        artifact_id: &str,
+        version: &str,
+        managed: bool,
+    ) -> Result<VersionChange> {
+        let project = parse_project(&self.content)?;
+        let coordinate = format!("{}:{}", group_id, artifact_id);
+        let dependency = dependencies_section(&project, managed)
+            .and_then(|section| find_dependency(&self.content, section, group_id, artifact_id))
+            .ok_or_else(|| {
+                let location = if managed { "<dependencyManagement>" } else { "<dependencies>" };
+                anyhow::anyhow!("pom.xml的{}中没有声明依赖 {}", location, coordinate)
+            })?;
+
+        let Some(version_element) = dependency.child("version") else {
+            if managed {
+                return Err(anyhow::anyhow!(
+                    "{} 在<dependencyManagement>中没有<version>，版本可能来自父POM或BOM",
+                    coordinate
+                ));
+            }
+            if !self.has_dependency(group_id, artifact_id, true) {
+                return Err(anyhow::anyhow!(
+                    "{} 的版本由父POM或BOM管理，请在对应的POM中更新",
+                    coordinate
+                ));
+            }
+            return self.set_version(group_id, artifact_id, version, true);
+        };
+
+        let current = element_text(&self.content, version_element).to_string();
+        let property = current
+            .strip_prefix("${")
+            .and_then(|rest| rest.strip_suffix('}'))
+            .map(str::to_string);
+
+        let target = match &property {
+            None => version_element,
+            Some(name) => project
+                .path(&["properties", name])
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "{} 的版本引用了属性 ${{{}}}，但它不在当前pom.xml的<properties>中定义",
+                        coordinate,
+                        name
+                    )
+                })?,
+        };
+
+        let old_version = element_text(&self.content, target).to_string();
+        let (start, end) = text_range(&self.content, target);
+        self.content.replace_range(start..end, version);
+
+        Ok(VersionChange {
+            old_version,
+            property,
+            managed,
+        })
+    }
+
+    /// 引用了`${property}`的位置数量，用于提示一个属性被多个依赖共享
+    pub fn property_usages(&self, property: &str) -> usize {
+        self.content.matches(&format!("${{{}}}", property)).count()
+    }
+
+    /// 追加到父元素的最后一个子元素之后，缩进与已有子元素一致
+    fn append_child(&mut self, parent: &Element, lines: &[(usize, String)], unit: &str) {
+        let parent_indent = line_indent(&self.content, parent.start).to_string();
+        let indent = parent
+            .children
+            .last()
+            .map(|child| line_indent(&self.content, child.start).to_string())
+            .filter(|indent| !indent.is_empty())
+            .unwrap_or_else(|| format!("{}{}", parent_indent, unit));
+        let block = render(lines, &indent, unit);
+
+        let line_start = line_start_if_blank(&self.content, parent.content_end);
+        if line_start != parent.content_end && line_start > parent.content_start {
+            // 结束标签独占一行: 插在这一行之前
+            self.content.insert_str(line_start, &block);
+        } else {
+            self.content
+                .insert_str(parent.content_end, &format!("\n{}{}", block, parent_indent));
+        }
+    }
+
+    /// 插在某个元素所在行之前，使用相同的缩进
+    fn insert_before(&mut self, sibling: &Element, lines: &[(usize, String)], unit: &str) {
+        let indent = line_indent(&self.content, sibling.start).to_string();
+        let position = line_start_if_blank(&self.content, sibling.start);
+        let position = if position == sibling.start {
+            position
+        } else {
+            leading_comments_start(&self.content, position)
+        };
+        let mut block = render(lines, &indent, unit);
+        if position == sibling.start {
+            // 与前面的内容在同一行
+            block = format!("{}\n{}", block.trim(), indent);
+        } else if self.content[..position]
+            .trim_end_matches([' ', '\t', '\r'])
+            .ends_with("\n\n")
+        {
+            // 元素之间用空行分隔时保持同样的风格
+            block.push('\n');
+        }
+        self.content.insert_str(position, &block);
+    }
+}
+
+fn dependencies_section(project: &Element, managed: bool) -> Option<&Element> {
+    if managed {
+        project.path(&["dependencyManagement", "dependencies"])
+    } else {
+        project.child("dependencies")
+    }
+}
+
+fn find_dependency<'a>(
+    content: &str,
+    section: &'a Element,
+    group_id: &str,
+    artifact_id: &str,
+) -> Option<&'a Element> {
+    section.children.iter().find(|dependency| {
+        dependency.name == "dependency"
+            && dependency
+                .child("groupId")
+                .is_some_and(|e| element_text(content, e) == group_id)
+            && dependency
+                .child("artifactId")
+                .is_some_and(|e| element_text(content, e) == artifact_id)
+    })
+}
+
+/// 解析出`<project>`元素及所有子元素的位置
+fn parse_project(content: &str) -> Result<Element> {
+    let mut reader = Reader::from_str(content);
+    let mut stack = vec![Element {
+        name: String::new(),
+        start: 0,
+        content_start: 0,
+        content_end: content.len(),
+        end: content.len(),
+        children: Vec::new(),
+    }];
+
+    loop {
+        let before = reader.buffer_position();
+        match reader.read_event()? {
+            Event::Start(e) => {
+                let after = reader.buffer_position();
+                stack.push(Element {
+                    name: String::from_utf8_lossy(e.name().as_ref()).to_string(),
+                    start: before,
+                    content_start: after,
+                    content_end: after,
+                    end: after,
+                    children: Vec::new(),
+                });
+            }
+            Event::Empty(e) => {
+                let after = reader.buffer_position();
+                let parent = stack.last_mut().context("XML结构无效")?;
+                parent.children.push(Element {
+                    name: String::from_utf8_lossy(e.name().as_ref()).to_string(),
+                    start: before,
+                    content_start: after,
+                    content_end: after,
+                    end: after,
+                    children: Vec::new(),
+                });
+            }
+            Event::End(_) => {
+                let mut element = stack.pop().context("XML结构无效")?;
+                element.content_end = before;
+                element.end = reader.buffer_position();
+                stack.last_mut().context("XML结构无效")?.children.push(element);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let document = stack.pop().context("XML结构无效")?;
+    document
+        .children
+        .into_iter()
+        .find(|element| element.name == "project")
+        .ok_or_else(|| anyhow::anyhow!("找不到<project>元素"))
+}
+
+fn element_text<'a>(content: &'a str, element: &Element) -> &'a str {
+    content[element.content_start..element.content_end].trim()
+}
+
+/// 元素内容去掉首尾空白后的范围
+fn text_range(content: &str, element: &Element) -> (usize, usize) {
+    let raw = &content[element.content_start..element.content_end];
+    let start = element.content_start + (raw.len() - raw.trim_start().len());
+    let end = element.content_end - (raw.len() - raw.trim_end().len());
+    (start, end.max(start))
+}
+
+/// 位置前面只有空白时返回行首，否则返回原位置
+fn line_start_if_blank(content: &str, position: usize) -> usize {
+    let line_start = content[..position].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    if content[line_start..position].trim().is_empty() {
+        line_start
+    } else {
+        position
+    }
+}
+
+/// 紧贴在行首位置之前(中间没有空行)的注释属于后面的元素，返回这些注释的行首
+fn leading_comments_start(content: &str, line_start: usize) -> usize {
+    let mut position = line_start;
+    loop {
+        let Some(before) = content[..position].strip_suffix('\n') else {
+            return position;
+        };
+        let before = before.strip_suffix('\r').unwrap_or(before);
+        let previous_line = &before[before.rfind('\n').map(|i| i + 1).unwrap_or(0)..];
+        if !previous_line.trim_end().ends_with("-->") {
+            return position;
+        }
+        let Some(comment_start) = before.rfind("<!--") else {
+            return position;
+        };
+        let comment_line = line_start_if_blank(content, comment_start);
+        if comment_line == comment_start {
+            // 注释前面还有其他内容
+            return position;
+        }
+        position = comment_line;
+    }
+}
+
+fn line_indent(content: &str, position: usize) -> &str {
+    let line_start = line_start_if_blank(content, position);
+    &content[line_start..position]
+}
+
+/// 推断缩进单位: `<project>`第一个子元素相对它的缩进，默认4个空格
+fn indent_unit(content: &str, project: &Element) -> String {
+    let project_indent = line_indent(content, project.start);
+    project
+        .children
+        .first()
+        .map(|child| line_indent(content, child.start))
+        .and_then(|indent| indent.strip_prefix(project_indent))
+        .filter(|unit| !unit.is_empty())
+        .unwrap_or("    ")
+        .to_string()
+}
+
+fn render(lines: &[(usize, String)], indent: &str, unit: &str) -> String {
+    lines
+        .iter()
+        .map(|(depth, line)| format!("{}{}{}\n", indent, unit.repeat(*depth), line))
+        .collect()
+}
@@ -0,0 +1,247 @@
+use crate::config::{JxConfig, ProfileConfig};
+use crate::dependency::Dependency;
+use crate::gradle;
+use anyhow::{Context, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 没有指定`--profile`时使用的profile
+pub const DEFAULT_PROFILE: &str = "debug";
+
+/// 内置的profile: debug只编译，release打包
+const BUILTIN_PROFILES: [&str; 2] = ["debug", "release"];
+
+/// 沿`inherits`合并之后的构建配置，build、test和run共用
+///
+/// 子profile的`package`覆盖父profile，列表追加在父profile之后，
+/// Gradle属性和依赖按键覆盖
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub package: bool,
+    pub javac_flags: Vec<String>,
+    pub jvm_opts: Vec<String>,
+    pub maven_profiles: Vec<String>,
+    pub gradle_properties: BTreeMap<String, String>,
+    pub dependencies: Vec<Dependency>,
+}
+
+impl Profile {
+    /// 项目目录中jx.toml定义的profile；没有jx.toml的Maven/Gradle项目只能使用内置profile
+    pub fn load(project_dir: &Path, name: &str) -> Result<Self> {
+        let config_path = project_dir.join("jx.toml");
+        if config_path.exists() {
+            let config = JxConfig::load(&config_path)?;
+            Self::resolve(Some(&config), name)
+        } else {
+            Self::resolve(None, name)
+        }
+    }
+
+    pub fn resolve(config: Option<&JxConfig>, name: &str) -> Result<Self> {
+        let tables = config.map(|config| &config.profile);
+        let mut chain: Vec<(&str, &ProfileConfig)> = Vec::new();
+        let mut current = name;
+
+        let mut profile = loop {
+            if chain.iter().any(|(visited, _)| *visited == current) {
+                let path: Vec<&str> = chain.iter().map(|(name, _)| *name).collect();
+                return Err(anyhow::anyhow!(
+                    "profile的继承关系成环: {} -> {}",
+                    path.join(" -> "),
+                    current
+                ));
+            }
+
+            match tables.and_then(|tables| tables.get(current)) {
+                Some(table) => {
+                    chain.push((current, table));
+                    match table.inherits.as_deref() {
+                        Some(parent) => current = parent,
+                        // 没有inherits时继承同名的内置profile，自定义profile继承debug
+                        None => break Self::builtin(current).unwrap_or_default(),
+                    }
+                }
+                None => {
+                    break Self::builtin(current).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "未定义的profile `{}`，可选: {}",
+                            current,
+                            available(config).join(", ")
+                        )
+                    })?
+                }
+            }
+        };
+
+        for (_, table) in chain.iter().rev() {
+            profile.apply(table);
+        }
+        profile.name = name.to_string();
+        Ok(profile)
+    }
+
+    fn builtin(name: &str) -> Option<Self> {
+        BUILTIN_PROFILES.contains(&name).then(|| Self {
+            name: name.to_string(),
+            package: name == "release",
+            javac_flags: Vec::new(),
+            jvm_opts: Vec::new(),
+            maven_profiles: Vec::new(),
+            gradle_properties: BTreeMap::new(),
+            dependencies: Vec::new(),
+        })
+    }
+
+    fn apply(&mut self, table: &ProfileConfig) {
+        if let Some(package) = table.package {
+            self.package = package;
+        }
+        self.javac_flags.extend(table.javac_flags.iter().cloned());
+        self.jvm_opts.extend(table.jvm_opts.iter().cloned());
+        for profile in &table.maven_profiles {
+            if !self.maven_profiles.contains(profile) {
+                self.maven_profiles.push(profile.clone());
+            }
+        }
+        self.gradle_properties.extend(
+            table
+                .gradle_properties
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+        for dependency in table.dependencies() {
+            self.dependencies.retain(|dep| {
+                dep.group_id != dependency.group_id || dep.artifact_id != dependency.artifact_id
+            });
+            self.dependencies.push(dependency.clone());
+        }
+    }
+
+    /// Maven的`-P`参数
+    pub fn maven_arguments(&self) -> Vec<String> {
+        if self.maven_profiles.is_empty() {
+            return Vec::new();
+        }
+        vec![format!("-P{}", self.maven_profiles.join(","))]
+    }
+
+    /// 运行测试或程序的JVM参数在Maven中的写法: surefire读取`argLine`
+    pub fn maven_arg_line(&self) -> Option<String> {
+        (!self.jvm_opts.is_empty()).then(|| format!("-DargLine={}", self.jvm_opts.join(" ")))
+    }
+
+    /// Maven无法从命令行追加javac参数和依赖，需要写在pom.xml的`<profile>`中
+    pub fn maven_warning(&self) -> Option<String> {
+        let mut unsupported = Vec::new();
+        if !self.javac_flags.is_empty() {
+            unsupported.push("javac_flags");
+        }
+        if !self.dependencies.is_empty() {
+            unsupported.push("dependencies");
+        }
+        (!unsupported.is_empty()).then(|| {
+            format!(
+                "⚠️  Maven不能从命令行设置profile中的 {}，请在pom.xml的<profile>中配置并加入maven_profiles",
+                unsupported.join("、")
+            )
+        })
+    }
+
+    /// Gradle的`-P`参数，以及设置javac参数、JVM参数和额外依赖的初始化脚本
+    pub fn gradle_arguments(&self) -> Result<Vec<String>> {
+        let mut arguments: Vec<String> = self
+            .gradle_properties
+            .iter()
+            .map(|(key, value)| format!("-P{}={}", key, value))
+            .collect();
+
+        if let Some(script) = self.gradle_init_script() {
+            let path = init_script_path(&script);
+            fs::write(&path, script)
+                .with_context(|| format!("无法写入Gradle初始化脚本 {}", path.display()))?;
+            arguments.push("--init-script".to_string());
+            arguments.push(path.to_string_lossy().to_string());
+        }
+        Ok(arguments)
+    }
+
+    fn gradle_init_script(&self) -> Option<String> {
+        if self.javac_flags.is_empty() && self.jvm_opts.is_empty() && self.dependencies.is_empty() {
+            return None;
+        }
+
+        let mut script = format!("// jx profile {}\nallprojects {{\n", self.name);
+        if !self.javac_flags.is_empty() {
+            script.push_str(&format!(
+                "    tasks.withType(JavaCompile).configureEach {{\n        options.compilerArgs.addAll({})\n    }}\n",
+                groovy_list(&self.javac_flags)
+            ));
+        }
+        if !self.jvm_opts.is_empty() {
+            for task in ["Test", "JavaExec"] {
+                script.push_str(&format!(
+                    "    tasks.withType({}).configureEach {{\n        jvmArgs({})\n    }}\n",
+                    task,
+                    groovy_list(&self.jvm_opts)
+                ));
+            }
+        }
+        if !self.dependencies.is_empty() {
+            script.push_str("    plugins.withId('java') {\n        dependencies {\n");
+            for dep in &self.dependencies {
+                let mut notation = dep.coordinate();
+                if let Some(classifier) = &dep.classifier {
+                    notation.push(':');
+                    notation.push_str(classifier);
+                }
+                script.push_str(&format!(
+                    "            {} {}\n",
+                    gradle::configuration(dep.scope.as_str()),
+                    groovy_string(&notation)
+                ));
+            }
+            script.push_str("        }\n    }\n");
+        }
+        script.push_str("}\n");
+        Some(script)
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::builtin(DEFAULT_PROFILE).expect("debug是内置profile")
+    }
+}
+
+/// 可以选择的profile: 内置profile和jx.toml中定义的profile
+fn available(config: Option<&JxConfig>) -> Vec<&str> {
+    let mut names: Vec<&str> = BUILTIN_PROFILES.to_vec();
+    if let Some(config) = config {
+        for name in config.profile.keys() {
+            if !names.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+/// 按内容命名，相同的profile重复使用同一个文件
+fn init_script_path(script: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    script.hash(&mut hasher);
+    std::env::temp_dir().join(format!("jx-profile-{:016x}.gradle", hasher.finish()))
+}
+
+fn groovy_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn groovy_list(values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|value| groovy_string(value)).collect();
+    format!("[{}]", items.join(", "))
+}
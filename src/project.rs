@@ -1,3 +1,5 @@
+pub use crate::config::ProjectType;
+use crate::config::JxConfig;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -20,13 +22,6 @@ pub struct Project {
     pub repositories: Vec<Repository>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub enum ProjectType {
-    Maven,
-    Gradle,
-    Jx,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProjectDependency {
     pub group_id: String,
@@ -43,6 +38,7 @@ pub enum DependencyScope {
     Test,
     Provided,
     System,
+    AnnotationProcessor,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -95,35 +91,35 @@ impl Project {
     }
 
     fn from_jx_config(config_path: &Path) -> Result<Self> {
-        let content = fs::read_to_string(config_path)?;
-        let config: toml::Value = toml::from_str(&content)?;
-
-        // 解析jx.toml配置
-        let project = config
-            .get("project")
-            .ok_or_else(|| anyhow::anyhow!("缺少project部分"))?;
-
-        let name = project
-            .get("name")
-            .and_then(|n| n.as_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        let version = project
-            .get("version")
-            .and_then(|v| v.as_str())
-            .unwrap_or("1.0.0")
-            .to_string();
-
-        let project_type = project.get("type").and_then(|t| t.as_str()).unwrap_or("jx");
-
-        let project_type = match project_type {
-            "maven" => ProjectType::Maven,
-            "gradle" => ProjectType::Gradle,
-            _ => ProjectType::Jx,
-        };
-
-        Ok(Self::new(&name, project_type))
+        let config = JxConfig::load(config_path)?;
+        let mut project = Self::new(&config.project.name, config.project.project_type);
+
+        project.version = config.project.version.clone();
+        project.description = config.project.description.clone();
+        if let Some(java_version) = config.java_version() {
+            project.java_version = java_version.to_string();
+        }
+        project.dependencies = config
+            .dependencies()
+            .iter()
+            .map(|dep| ProjectDependency {
+                group_id: dep.group_id.clone(),
+                artifact_id: dep.artifact_id.clone(),
+                version: dep.version.clone(),
+                scope: DependencyScope::from(&dep.scope),
+                optional: dep.optional,
+            })
+            .collect();
+        if let Some(source_dir) = config.build.source_dir {
+            project.source_dirs = vec![source_dir];
+        }
+        if let Some(target_dir) = config.build.target_dir {
+            project.target_dir = target_dir;
+        }
+        project.main_class = config.build.main_class.or(project.main_class);
+        project.test_class = config.build.test_class.or(project.test_class);
+
+        Ok(project)
     }
 
     fn from_maven_pom(pom_path: &Path) -> Result<Self> {
@@ -261,6 +257,19 @@ impl Project {
     }
 }
 
+impl From<&crate::dependency::DependencyScope> for DependencyScope {
+    fn from(scope: &crate::dependency::DependencyScope) -> Self {
+        match scope {
+            crate::dependency::DependencyScope::Compile => DependencyScope::Compile,
+            crate::dependency::DependencyScope::Runtime => DependencyScope::Runtime,
+            crate::dependency::DependencyScope::Test => DependencyScope::Test,
+            crate::dependency::DependencyScope::Provided => DependencyScope::Provided,
+            crate::dependency::DependencyScope::System => DependencyScope::System,
+            crate::dependency::DependencyScope::AnnotationProcessor => DependencyScope::AnnotationProcessor,
+        }
+    }
+}
+
 impl ProjectDependency {
     pub fn new(group_id: &str, artifact_id: &str, version: &str, scope: DependencyScope) -> Self {
         Self {
@@ -2,6 +2,8 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+const CENTRAL_SEARCH_URL: &str = "https://search.maven.org/solrsearch/select";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MavenRepository {
     pub name: String,
@@ -32,6 +34,129 @@ pub struct ArtifactInfo {
     pub size: Option<u64>,
     pub checksum: Option<String>,
     pub last_updated: String,
+    pub version_count: Option<u64>,
+}
+
+/// 远程搜索结果的排序方式，作用于当前页
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchSort {
+    Relevance,
+    Name,
+    Updated,
+    Version,
+}
+
+impl SearchSort {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "relevance" => Ok(SearchSort::Relevance),
+            "name" => Ok(SearchSort::Name),
+            "updated" => Ok(SearchSort::Updated),
+            "version" => Ok(SearchSort::Version),
+            _ => Err(anyhow::anyhow!(
+                "不支持的排序方式: {} (可选: relevance, name, updated, version)",
+                name
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    text: String,
+    group: Option<String>,
+    packaging: Option<String>,
+    all_versions: bool,
+    page: usize,
+    rows: usize,
+}
+
+impl SearchQuery {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.trim().to_string(),
+            group: None,
+            packaging: None,
+            all_versions: false,
+            page: 1,
+            rows: 20,
+        }
+    }
+
+    pub fn group(mut self, group: Option<String>) -> Self {
+        self.group = group;
+        self
+    }
+
+    pub fn packaging(mut self, packaging: Option<String>) -> Self {
+        self.packaging = packaging;
+        self
+    }
+
+    /// 使用gav核心，每个版本单独返回
+    pub fn all_versions(mut self, all_versions: bool) -> Self {
+        self.all_versions = all_versions;
+        self
+    }
+
+    /// 页码从1开始
+    pub fn page(mut self, page: usize, rows: usize) -> Self {
+        self.page = page.max(1);
+        self.rows = rows.max(1);
+        self
+    }
+
+    fn to_solr_query(&self) -> String {
+        let mut clauses = Vec::new();
+
+        if !self.text.is_empty() {
+            // gav核心不支持全文检索，裸关键词按artifactId匹配
+            if self.all_versions && !self.text.contains(':') {
+                clauses.push(format!("a:\"{}\"", self.text));
+            } else {
+                clauses.push(self.text.clone());
+            }
+        }
+        if let Some(ref group) = self.group {
+            clauses.push(format!("g:\"{}\"", group));
+        }
+        if let Some(ref packaging) = self.packaging {
+            clauses.push(format!("p:\"{}\"", packaging));
+        }
+
+        clauses.join(" AND ")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchPage {
+    pub query: String,
+    pub total: u64,
+    pub page: usize,
+    pub rows: usize,
+    pub artifacts: Vec<ArtifactInfo>,
+}
+
+impl SearchPage {
+    pub fn total_pages(&self) -> u64 {
+        self.total.div_ceil(self.rows as u64)
+    }
+
+    pub fn sort(&mut self, sort: SearchSort) {
+        match sort {
+            SearchSort::Relevance => {}
+            SearchSort::Name => self.artifacts.sort_by(|a, b| {
+                (&a.group_id, &a.artifact_id).cmp(&(&b.group_id, &b.artifact_id))
+            }),
+            // 日期格式为YYYY-MM-DD，字符串比较即可
+            SearchSort::Updated => self
+                .artifacts
+                .sort_by(|a, b| b.last_updated.cmp(&a.last_updated)),
+            SearchSort::Version => self
+                .artifacts
+                .sort_by(|a, b| crate::index::compare_versions(&b.version, &a.version)),
+        }
+    }
 }
 
 pub struct MavenRegistry {
@@ -49,7 +174,7 @@ impl MavenRegistry {
         // 添加默认仓库
         registry.add_repository(MavenRepository {
             name: "Maven Central".to_string(),
-            url: "https://repo1.maven.org/maven2/".to_string(),
+            url: format!("{}/", crate::settings::current().repository_url()),
             username: None,
             password: None,
             releases: true,
@@ -82,12 +207,89 @@ impl MavenRegistry {
         self.repositories.iter().find(|r| r.name == name)
     }
 
-    pub async fn search_artifacts(&self, query: &str, limit: usize) -> Result<Vec<ArtifactInfo>> {
-        // TODO: 实现实际的搜索逻辑
-        println!("搜索Maven Central: {}", query);
-        println!("注意: 搜索功能需要实现HTTP客户端");
+    pub async fn search_artifacts(&self, query: &SearchQuery) -> Result<SearchPage> {
+        let solr_query = query.to_solr_query();
+        if solr_query.is_empty() {
+            return Err(anyhow::anyhow!("搜索关键词不能为空"));
+        }
 
-        Ok(Vec::new())
+        self.central_select(&solr_query, query.all_versions, query.page, query.rows)
+            .await
+    }
+
+    /// 按类名搜索，全限定名使用fc:，简单类名使用c:
+    pub async fn search_class(&self, class_name: &str, rows: usize) -> Result<SearchPage> {
+        let solr_query = if class_name.contains('.') {
+            format!("fc:\"{}\"", class_name)
+        } else {
+            format!("c:\"{}\"", class_name)
+        };
+
+        self.central_select(&solr_query, false, 1, rows).await
+    }
+
+    async fn central_select(
+        &self,
+        solr_query: &str,
+        gav: bool,
+        page: usize,
+        rows: usize,
+    ) -> Result<SearchPage> {
+        let start = (page - 1) * rows;
+        let mut params = vec![
+            ("q", solr_query.to_string()),
+            ("start", start.to_string()),
+            ("rows", rows.to_string()),
+            ("wt", "json".to_string()),
+        ];
+        if gav {
+            params.push(("core", "gav".to_string()));
+        }
+
+        log::debug!("搜索Maven Central: {} {:?}", CENTRAL_SEARCH_URL, params);
+
+        let client = crate::settings::client_builder()?
+            .user_agent(concat!("jx/", env!("CARGO_PKG_VERSION")))
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .context("创建HTTP客户端失败")?;
+
+        let response = client
+            .get(CENTRAL_SEARCH_URL)
+            .query(&params)
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .context("Maven Central搜索请求失败")?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Maven Central搜索失败，状态码: {}",
+                response.status()
+            ));
+        }
+
+        let body = response.text().await.context("读取Maven Central响应失败")?;
+        let json: serde_json::Value =
+            serde_json::from_str(&body).context("解析Maven Central响应失败")?;
+        let result = json
+            .get("response")
+            .ok_or_else(|| anyhow::anyhow!("Maven Central响应缺少response字段"))?;
+
+        let total = result.get("numFound").and_then(|n| n.as_u64()).unwrap_or(0);
+        let artifacts = result
+            .get("docs")
+            .and_then(|d| d.as_array())
+            .map(|docs| docs.iter().filter_map(parse_search_doc).collect())
+            .unwrap_or_default();
+
+        Ok(SearchPage {
+            query: solr_query.to_string(),
+            total,
+            page,
+            rows,
+            artifacts,
+        })
     }
 
     pub async fn get_artifact_metadata(
@@ -102,18 +304,30 @@ impl MavenRegistry {
             return Ok(cached.clone());
         }
 
-        // TODO: 实现实际的元数据获取
-        println!("获取元数据: {}:{}", group_id, artifact_id);
-        println!("注意: 元数据获取功能需要实现HTTP客户端");
-
-        let metadata = ArtifactMetadata {
-            group_id: group_id.to_string(),
-            artifact_id: artifact_id.to_string(),
-            versions: vec!["1.0.0".to_string()],
-            latest: "1.0.0".to_string(),
-            release: Some("1.0.0".to_string()),
-            last_updated: "2024-01-01".to_string(),
-        };
+        let url = format!(
+            "{}/{}/{}/maven-metadata.xml",
+            crate::settings::current().repository_url(),
+            group_id.replace('.', "/"),
+            artifact_id
+        );
+        log::debug!("获取元数据: {}", url);
+
+        let response = crate::settings::http_client()?
+            .get(&url)
+            .send()
+            .await
+            .context("获取构件元数据失败")?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "获取 {}:{} 的元数据失败，状态码: {}",
+                group_id,
+                artifact_id,
+                response.status()
+            ));
+        }
+
+        let content = response.text().await.context("读取构件元数据失败")?;
+        let metadata = parse_maven_metadata(group_id, artifact_id, &content)?;
 
         // 缓存结果
         self.cache.insert(cache_key, metadata.clone());
@@ -121,6 +335,12 @@ impl MavenRegistry {
         Ok(metadata)
     }
 
+    /// 最新的发布版本，没有release时使用latest
+    pub async fn latest_release(&mut self, group_id: &str, artifact_id: &str) -> Result<String> {
+        let metadata = self.get_artifact_metadata(group_id, artifact_id).await?;
+        Ok(metadata.release.unwrap_or(metadata.latest))
+    }
+
     pub async fn download_artifact(
         &self,
         group_id: &str,
@@ -143,7 +363,8 @@ impl MavenRegistry {
         classifier: Option<&str>,
     ) -> String {
         let mut url = format!(
-            "https://repo1.maven.org/maven2/{}/{}/{}/{}-{}",
+            "{}/{}/{}/{}/{}-{}",
+            crate::settings::current().repository_url(),
             group_id.replace('.', "/"),
             artifact_id,
             version,
@@ -168,6 +389,76 @@ impl MavenRegistry {
     }
 }
 
+fn parse_maven_metadata(group_id: &str, artifact_id: &str, content: &str) -> Result<ArtifactMetadata> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(content);
+    reader.trim_text(true);
+
+    let mut metadata = ArtifactMetadata {
+        group_id: group_id.to_string(),
+        artifact_id: artifact_id.to_string(),
+        versions: Vec::new(),
+        latest: String::new(),
+        release: None,
+        last_updated: String::new(),
+    };
+    let mut path: Vec<String> = Vec::new();
+
+    loop {
+        match reader.read_event().context("解析maven-metadata.xml失败")? {
+            Event::Start(e) => path.push(String::from_utf8_lossy(e.local_name().as_ref()).to_string()),
+            Event::Text(t) => {
+                let text = t.unescape().context("解析maven-metadata.xml失败")?.to_string();
+                let path: Vec<&str> = path.iter().map(|s| s.as_str()).collect();
+                match path.as_slice() {
+                    ["metadata", "versioning", "versions", "version"] => metadata.versions.push(text),
+                    ["metadata", "versioning", "latest"] => metadata.latest = text,
+                    ["metadata", "versioning", "release"] => metadata.release = Some(text),
+                    ["metadata", "versioning", "lastUpdated"] => metadata.last_updated = text,
+                    _ => {}
+                }
+            }
+            Event::End(_) => {
+                path.pop();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if metadata.latest.is_empty() {
+        metadata.latest = metadata.versions.last().cloned().unwrap_or_default();
+    }
+
+    Ok(metadata)
+}
+
+/// ga核心返回latestVersion，gav核心返回v
+fn parse_search_doc(doc: &serde_json::Value) -> Option<ArtifactInfo> {
+    let field = |name: &str| doc.get(name).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    let version = field("v").or_else(|| field("latestVersion"))?;
+    let last_updated = doc
+        .get("timestamp")
+        .and_then(|t| t.as_i64())
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+
+    Some(ArtifactInfo {
+        group_id: field("g")?,
+        artifact_id: field("a")?,
+        version,
+        packaging: field("p").unwrap_or_else(|| "jar".to_string()),
+        classifier: None,
+        size: None,
+        checksum: None,
+        last_updated,
+        version_count: doc.get("versionCount").and_then(|c| c.as_u64()),
+    })
+}
+
 impl Default for MavenRegistry {
     fn default() -> Self {
         Self::new()
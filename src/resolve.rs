@@ -1,74 +1,280 @@
-use crate::dependency::Dependency;
+use crate::dependency::{Dependency, DependencyScope, Exclusion};
+use crate::download::Downloader;
+use crate::pom::{parse_pom, Pom, PomDependency};
+use crate::workspace::scope_rank;
 use anyhow::{Context, Result};
-use std::collections::{HashMap, HashSet};
+use futures_util::future::{FutureExt, LocalBoxFuture};
+use std::collections::{HashMap, HashSet, VecDeque};
 
+/// 按Maven规则解析传递依赖: 广度优先，路径最短的版本胜出，
+/// 同一深度按声明顺序，optional和test/provided的传递依赖不继承
 pub struct DependencyResolver {
+    // groupId:artifactId -> 胜出的依赖
     resolved: HashMap<String, Dependency>,
-    unresolved: HashSet<String>,
-    in_progress: HashSet<String>,
+    // 解析顺序，同时也是锁文件和类路径的顺序
+    order: Vec<String>,
+    direct: HashSet<String>,
+    // 坐标 -> 直接依赖的坐标
+    edges: HashMap<String, Vec<String>>,
+    // 被仲裁掉的版本: (依赖, 胜出版本)
+    omitted: Vec<(Dependency, String)>,
+    poms: HashMap<String, Pom>,
+    // groupId:artifactId -> 固定版本，优先于POM中声明的版本
+    pinned: HashMap<String, String>,
+    downloader: Downloader,
+}
+
+struct PendingDependency {
+    dependency: Dependency,
+    parent: Option<String>,
 }
 
 impl DependencyResolver {
     pub fn new() -> Self {
         Self {
             resolved: HashMap::new(),
-            unresolved: HashSet::new(),
-            in_progress: HashSet::new(),
+            order: Vec::new(),
+            direct: HashSet::new(),
+            edges: HashMap::new(),
+            omitted: Vec::new(),
+            poms: HashMap::new(),
+            pinned: HashMap::new(),
+            downloader: Downloader::new(),
         }
     }
 
+    /// 离线模式下只使用缓存中的POM
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.downloader = Downloader::new().offline(offline);
+        self
+    }
+
+    /// 固定部分依赖的版本，用于只升级锁文件中的个别构件
+    pub fn pin_versions(mut self, pinned: HashMap<String, String>) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
     pub async fn resolve_dependencies(
         &mut self,
         dependencies: &[Dependency],
     ) -> Result<Vec<Dependency>> {
-        let mut resolved_deps = Vec::new();
+        let mut queue: VecDeque<PendingDependency> = dependencies
+            .iter()
+            .map(|dep| PendingDependency {
+                dependency: dep.clone(),
+                parent: None,
+            })
+            .collect();
 
         for dep in dependencies {
-            let resolved = self.resolve_dependency(dep).await?;
-            resolved_deps.extend(resolved);
+            self.direct.insert(ga_key(&dep.group_id, &dep.artifact_id));
         }
 
-        Ok(resolved_deps)
-    }
+        while let Some(pending) = queue.pop_front() {
+            let mut dependency = pending.dependency;
+            let key = ga_key(&dependency.group_id, &dependency.artifact_id);
+            if let Some(version) = self.pinned.get(&key) {
+                dependency.version = version.clone();
+            }
 
-    async fn resolve_dependency(&mut self, dependency: &Dependency) -> Result<Vec<Dependency>> {
-        let key = dependency.coordinate();
+            // 已有更近的版本，只记录依赖边
+            if let Some(winner) = self.resolved.get_mut(&key) {
+                let winner_coordinate = winner.coordinate();
+                if winner.version != dependency.version {
+                    let recorded = self.omitted.iter().any(|(omitted, version)| {
+                        omitted.coordinate() == dependency.coordinate() && version == &winner.version
+                    });
+                    if !recorded {
+                        self.omitted.push((dependency.clone(), winner.version.clone()));
+                    }
+                }
 
-        // 检查是否已经解析过
-        if let Some(resolved) = self.resolved.get(&key) {
-            return Ok(vec![resolved.clone()]);
+                // 先经由test/provided路径到达、后来又有compile等更宽的路径时放宽scope，
+                // 并把新的scope传递给它的依赖；直接依赖保持声明的scope
+                let widen = !self.direct.contains(&key)
+                    && scope_rank(&dependency.scope) < scope_rank(&winner.scope);
+                if widen {
+                    winner.scope = dependency.scope.clone();
+                    let winner = winner.clone();
+                    for child in self.resolve_transitive_dependencies(&winner).await? {
+                        queue.push_back(PendingDependency {
+                            dependency: child,
+                            parent: Some(winner_coordinate.clone()),
+                        });
+                    }
+                }
+
+                if let Some(parent) = pending.parent {
+                    self.add_edge(&parent, winner_coordinate);
+                }
+                continue;
+            }
+
+            let coordinate = dependency.coordinate();
+            if let Some(parent) = pending.parent {
+                self.add_edge(&parent, coordinate.clone());
+            }
+            self.edges.entry(coordinate.clone()).or_default();
+            self.resolved.insert(key.clone(), dependency.clone());
+            self.order.push(key);
+
+            for child in self.resolve_transitive_dependencies(&dependency).await? {
+                queue.push_back(PendingDependency {
+                    dependency: child,
+                    parent: Some(coordinate.clone()),
+                });
+            }
         }
 
-        // 检查是否正在解析中（循环依赖检测）
-        if self.in_progress.contains(&key) {
-            return Err(anyhow::anyhow!("检测到循环依赖: {}", key));
+        Ok(self.resolved_dependencies())
+    }
+
+    async fn resolve_transitive_dependencies(
+        &mut self,
+        dependency: &Dependency,
+    ) -> Result<Vec<Dependency>> {
+        let pom = self
+            .effective_pom(&dependency.group_id, &dependency.artifact_id, &dependency.version)
+            .await
+            .with_context(|| format!("解析 {} 的依赖失败", dependency.coordinate()))?;
+
+        let mut children = Vec::new();
+        for declared in &pom.dependencies {
+            let declared = pom.interpolate_dependency(declared);
+            let managed = pom.managed(&declared.group_id, &declared.artifact_id);
+
+            if declared.optional || !is_jar_type(declared.type_.as_deref()) {
+                continue;
+            }
+            if is_excluded(&dependency.exclusions, &declared) {
+                continue;
+            }
+
+            let scope = declared
+                .scope
+                .clone()
+                .or_else(|| managed.and_then(|m| m.scope.clone()))
+                .unwrap_or_else(|| "compile".to_string());
+            let scope = match transitive_scope(&dependency.scope, &scope) {
+                Some(scope) => scope,
+                None => continue,
+            };
+
+            let version = match declared.version.clone().or_else(|| managed.and_then(|m| m.version.clone())) {
+                Some(version) => select_version(&pom.interpolate(&version)),
+                None => {
+                    log::warn!(
+                        "{} 的依赖 {} 没有版本，已跳过",
+                        dependency.coordinate(),
+                        declared.key()
+                    );
+                    continue;
+                }
+            };
+
+            // 排除规则沿依赖路径向下传递
+            let mut exclusions = dependency.exclusions.clone();
+            let declared_exclusions = declared
+                .exclusions
+                .iter()
+                .chain(managed.map(|m| m.exclusions.iter()).into_iter().flatten());
+            for (group_id, artifact_id) in declared_exclusions {
+                exclusions.push(Exclusion {
+                    group_id: group_id.clone(),
+                    artifact_id: artifact_id.clone(),
+                });
+            }
+
+            let mut child = Dependency::new(&declared.group_id, &declared.artifact_id, &version)
+                .with_scope(scope)
+                .with_exclusions(exclusions);
+            child.classifier = declared.classifier.clone();
+            children.push(child);
         }
 
-        // 标记为正在解析
-        self.in_progress.insert(key.clone());
+        Ok(children)
+    }
 
-        let mut resolved_deps = vec![dependency.clone()];
+    /// 合并父POM并展开导入的BOM后的有效POM
+    fn effective_pom<'a>(
+        &'a mut self,
+        group_id: &'a str,
+        artifact_id: &'a str,
+        version: &'a str,
+    ) -> LocalBoxFuture<'a, Result<Pom>> {
+        async move {
+            let coordinate = format!("{}:{}:{}", group_id, artifact_id, version);
+            if let Some(pom) = self.poms.get(&coordinate) {
+                return Ok(pom.clone());
+            }
 
-        // 解析传递依赖
-        let transitive_deps = self.resolve_transitive_dependencies(dependency).await?;
-        resolved_deps.extend(transitive_deps);
+            let content = self
+                .downloader
+                .download_pom(group_id, artifact_id, version)
+                .await?;
+            let mut pom = parse_pom(&content)
+                .with_context(|| format!("解析POM失败: {}", coordinate))?;
+
+            if let Some(parent) = pom.parent.clone() {
+                let parent_pom = self
+                    .effective_pom(&parent.group_id, &parent.artifact_id, &parent.version)
+                    .await?;
+                pom.inherit(&parent_pom);
+            }
 
-        // 标记为已解析
-        self.resolved.insert(key.clone(), dependency.clone());
-        self.in_progress.remove(&key);
+            let (imports, managed): (Vec<PomDependency>, Vec<PomDependency>) = pom
+                .dependency_management
+                .drain(..)
+                .partition(|d| d.scope.as_deref() == Some("import"));
+            pom.dependency_management = managed;
+
+            for import in imports {
+                let import = pom.interpolate_dependency(&import);
+                let version = match import.version {
+                    Some(ref version) => version.clone(),
+                    None => continue,
+                };
+                let bom = self
+                    .effective_pom(&import.group_id, &import.artifact_id, &version)
+                    .await?;
+                for managed in &bom.dependency_management {
+                    let managed = bom.interpolate_dependency(managed);
+                    if pom.managed(&managed.group_id, &managed.artifact_id).is_none() {
+                        pom.dependency_management.push(managed);
+                    }
+                }
+            }
 
-        Ok(resolved_deps)
+            self.poms.insert(coordinate, pom.clone());
+            Ok(pom)
+        }
+        .boxed_local()
     }
 
-    async fn resolve_transitive_dependencies(
-        &self,
-        dependency: &Dependency,
-    ) -> Result<Vec<Dependency>> {
-        // TODO: 实现传递依赖解析
-        // 这里应该查询Maven Central或其他仓库来获取传递依赖信息
+    fn add_edge(&mut self, parent: &str, child: String) {
+        let children = self.edges.entry(parent.to_string()).or_default();
+        if !children.contains(&child) {
+            children.push(child);
+        }
+    }
 
-        // 临时返回空向量
-        Ok(Vec::new())
+    /// 按解析顺序返回所有胜出的依赖
+    pub fn resolved_dependencies(&self) -> Vec<Dependency> {
+        self.order
+            .iter()
+            .filter_map(|key| self.resolved.get(key))
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_direct(&self, dependency: &Dependency) -> bool {
+        self.direct
+            .contains(&ga_key(&dependency.group_id, &dependency.artifact_id))
+    }
+
+    pub fn dependencies_of(&self, coordinate: &str) -> Vec<String> {
+        self.edges.get(coordinate).cloned().unwrap_or_default()
     }
 
     pub fn get_resolution_order(&self) -> Vec<String> {
@@ -77,10 +283,11 @@ impl DependencyResolver {
         let mut visited = HashSet::new();
         let mut temp_visited = HashSet::new();
 
-        for key in self.resolved.keys() {
-            if !visited.contains(key) {
+        for dep in self.resolved_dependencies() {
+            let key = dep.coordinate();
+            if !visited.contains(&key) {
                 if let Err(e) =
-                    self.topological_sort(key, &mut visited, &mut temp_visited, &mut order)
+                    self.topological_sort(&key, &mut visited, &mut temp_visited, &mut order)
                 {
                     eprintln!("警告: 依赖排序失败: {}", e);
                     // 继续处理其他依赖
@@ -88,7 +295,6 @@ impl DependencyResolver {
             }
         }
 
-        order.reverse();
         order
     }
 
@@ -109,11 +315,10 @@ impl DependencyResolver {
 
         temp_visited.insert(key.to_string());
 
-        // TODO: 获取依赖的传递依赖
-        // let transitive = self.get_transitive_dependencies(key)?;
-        // for dep_key in transitive {
-        //     self.topological_sort(&dep_key, visited, temp_visited, order)?;
-        // }
+        // 先放入被依赖的构件
+        for dep_key in self.dependencies_of(key) {
+            self.topological_sort(&dep_key, visited, temp_visited, order)?;
+        }
 
         temp_visited.remove(key);
         visited.insert(key.to_string());
@@ -122,39 +327,26 @@ impl DependencyResolver {
         Ok(())
     }
 
+    /// 版本仲裁中被忽略的版本
     pub fn detect_conflicts(&self) -> Vec<DependencyConflict> {
-        let mut conflicts = Vec::new();
-        let mut version_map: HashMap<String, HashMap<String, String>> = HashMap::new();
-
-        for dep in self.resolved.values() {
-            let key = format!("{}:{}", dep.group_id, dep.artifact_id);
-            let versions = version_map.entry(key.clone()).or_insert_with(HashMap::new);
-
-            if let Some(existing_version) = versions.get(&dep.version) {
-                if existing_version != &dep.version {
-                    conflicts.push(DependencyConflict {
-                        group_id: dep.group_id.clone(),
-                        artifact_id: dep.artifact_id.clone(),
-                        versions: vec![existing_version.clone(), dep.version.clone()],
-                        conflict_type: ConflictType::VersionConflict,
-                    });
-                }
-            } else {
-                versions.insert(dep.version.clone(), dep.version.clone());
-            }
-        }
-
-        conflicts
+        self.omitted
+            .iter()
+            .map(|(dep, winner)| DependencyConflict {
+                group_id: dep.group_id.clone(),
+                artifact_id: dep.artifact_id.clone(),
+                versions: vec![winner.clone(), dep.version.clone()],
+                conflict_type: ConflictType::VersionConflict,
+            })
+            .collect()
     }
 
     pub fn get_dependency_tree(&self) -> Vec<DependencyTreeNode> {
         let mut tree = Vec::new();
         let mut visited = HashSet::new();
 
-        for dep in self.resolved.values() {
-            let key = dep.coordinate();
-            if !visited.contains(&key) {
-                let node = self.build_tree_node(dep, &mut visited, 0);
+        for dep in self.resolved_dependencies() {
+            if self.is_direct(&dep) {
+                let node = self.build_tree_node(&dep, &mut visited, 0);
                 tree.push(node);
             }
         }
@@ -176,27 +368,75 @@ impl DependencyResolver {
             depth,
         };
 
-        // TODO: 添加传递依赖节点
-        // let transitive = self.get_transitive_dependencies(&dep.coordinate())?;
-        // for dep_key in transitive {
-        //     if let Some(child_dep) = self.resolved.get(&dep_key) {
-        //         if !visited.contains(&dep_key) {
-        //             let child_node = self.build_tree_node(child_dep, visited, depth + 1);
-        //             node.children.push(child_node);
-        //         }
-        //     }
-        // }
+        for dep_key in self.dependencies_of(&dep.coordinate()) {
+            let child_dep = self
+                .resolved
+                .values()
+                .find(|d| d.coordinate() == dep_key);
+            if let Some(child_dep) = child_dep {
+                if !visited.contains(&dep_key) {
+                    let child_node = self.build_tree_node(child_dep, visited, depth + 1);
+                    node.children.push(child_node);
+                }
+            }
+        }
 
         node
     }
 
     pub fn clear(&mut self) {
         self.resolved.clear();
-        self.unresolved.clear();
-        self.in_progress.clear();
+        self.order.clear();
+        self.direct.clear();
+        self.edges.clear();
+        self.omitted.clear();
     }
 }
 
+fn ga_key(group_id: &str, artifact_id: &str) -> String {
+    format!("{}:{}", group_id, artifact_id)
+}
+
+fn is_jar_type(type_: Option<&str>) -> bool {
+    matches!(type_, None | Some("jar") | Some("bundle"))
+}
+
+fn is_excluded(exclusions: &[Exclusion], dep: &PomDependency) -> bool {
+    exclusions.iter().any(|e| {
+        (e.group_id == "*" || e.group_id == dep.group_id)
+            && (e.artifact_id == "*" || e.artifact_id == dep.artifact_id)
+    })
+}
+
+/// Maven的scope传递规则，None表示不传递
+fn transitive_scope(parent: &DependencyScope, child: &str) -> Option<DependencyScope> {
+    match (parent, child) {
+        (_, "test" | "provided" | "system" | "import") => None,
+        (DependencyScope::System, _) => None,
+        (DependencyScope::Compile, "runtime") => Some(DependencyScope::Runtime),
+        (DependencyScope::Compile, _) => Some(DependencyScope::Compile),
+        (DependencyScope::Runtime, _) => Some(DependencyScope::Runtime),
+        (DependencyScope::Test, _) => Some(DependencyScope::Test),
+        (DependencyScope::Provided, _) => Some(DependencyScope::Provided),
+        (DependencyScope::AnnotationProcessor, _) => Some(DependencyScope::AnnotationProcessor),
+    }
+}
+
+/// 版本范围取下界，例如 [1.2,2.0) -> 1.2
+fn select_version(version: &str) -> String {
+    if !version.starts_with('[') && !version.starts_with('(') {
+        return version.to_string();
+    }
+
+    version
+        .trim_matches(|c| matches!(c, '[' | ']' | '(' | ')'))
+        .split(',')
+        .map(|v| v.trim())
+        .find(|v| !v.is_empty())
+        .unwrap_or(version)
+        .to_string()
+}
+
 #[derive(Debug)]
 pub struct DependencyConflict {
     pub group_id: String,
@@ -235,3 +475,80 @@ impl Default for DependencyResolver {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// artifactId -> 依赖的(artifactId, scope)，groupId和版本固定为t和1.0
+    type Poms<'a> = [(&'a str, &'a [(&'a str, Option<&'a str>)])];
+
+    /// 预先放入解析器的POM缓存，不需要访问仓库
+    fn resolver(poms: &Poms) -> DependencyResolver {
+        let mut resolver = DependencyResolver::new().offline(true);
+        for (artifact_id, dependencies) in poms {
+            let dependencies: String = dependencies
+                .iter()
+                .map(|(child, scope)| {
+                    format!(
+                        "<dependency><groupId>t</groupId><artifactId>{}</artifactId><version>1.0</version>{}</dependency>",
+                        child,
+                        scope.map(|scope| format!("<scope>{}</scope>", scope)).unwrap_or_default()
+                    )
+                })
+                .collect();
+            let content = format!(
+                "<project><groupId>t</groupId><artifactId>{}</artifactId><version>1.0</version><dependencies>{}</dependencies></project>",
+                artifact_id, dependencies
+            );
+            resolver
+                .poms
+                .insert(format!("t:{}:1.0", artifact_id), parse_pom(&content).unwrap());
+        }
+        resolver
+    }
+
+    fn scopes(resolved: &[Dependency]) -> HashMap<String, DependencyScope> {
+        resolved
+            .iter()
+            .map(|dep| (dep.artifact_id.clone(), dep.scope.clone()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn compile_path_widens_scope_reached_via_test_first() {
+        // testlib(test) -> shared -> leaf；app(compile) -> middle -> shared
+        let mut resolver = resolver(&[
+            ("testlib", &[("shared", None)]),
+            ("app", &[("middle", None)]),
+            ("middle", &[("shared", None)]),
+            ("shared", &[("leaf", None), ("extra", Some("runtime"))]),
+            ("leaf", &[]),
+            ("extra", &[]),
+        ]);
+        let direct = [
+            Dependency::new("t", "testlib", "1.0").with_scope(DependencyScope::Test),
+            Dependency::new("t", "app", "1.0"),
+        ];
+
+        let resolved = resolver.resolve_dependencies(&direct).await.unwrap();
+        let scopes = scopes(&resolved);
+        assert!(matches!(scopes["testlib"], DependencyScope::Test));
+        assert!(matches!(scopes["shared"], DependencyScope::Compile));
+        assert!(matches!(scopes["leaf"], DependencyScope::Compile));
+        assert!(matches!(scopes["extra"], DependencyScope::Runtime));
+        assert!(resolver.dependencies_of("t:middle:1.0").contains(&"t:shared:1.0".to_string()));
+    }
+
+    #[tokio::test]
+    async fn direct_dependencies_keep_declared_scope() {
+        let mut resolver = resolver(&[("app", &[("junit", None)]), ("junit", &[])]);
+        let direct = [
+            Dependency::new("t", "junit", "1.0").with_scope(DependencyScope::Test),
+            Dependency::new("t", "app", "1.0"),
+        ];
+
+        let resolved = resolver.resolve_dependencies(&direct).await.unwrap();
+        assert!(matches!(scopes(&resolved)["junit"], DependencyScope::Test));
+    }
+}
@@ -0,0 +1,392 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use toml::Value;
+use toml_edit::{Document, Item, Table};
+
+/// 与机器相关的配置，按以下顺序合并，后面的覆盖前面的:
+///
+/// 1. 内置默认值
+/// 2. 用户配置 `~/.jx/config.toml` (可以用`$JX_CONFIG`指定其他文件)
+/// 3. 项目的 `jx.toml` (只有`[repositories]`和`[net]`中的部分配置项)
+/// 4. 项目的 `.jx/config.toml`
+/// 5. `JX_*` 环境变量，例如`JX_NET_OFFLINE=true`
+///
+/// 命令行参数 (例如`--offline`) 的优先级最高
+pub struct Settings {
+    values: BTreeMap<&'static str, (Value, Origin)>,
+}
+
+/// 配置值的来源
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    Default,
+    User(PathBuf),
+    Manifest(PathBuf),
+    Local(PathBuf),
+    Env(String),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "默认值"),
+            Origin::User(path) => write!(f, "用户配置 {}", path.display()),
+            Origin::Manifest(path) => write!(f, "项目清单 {}", path.display()),
+            Origin::Local(path) => write!(f, "项目配置 {}", path.display()),
+            Origin::Env(name) => write!(f, "环境变量 {}", name),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    String,
+    Bool,
+    Integer,
+}
+
+struct Key {
+    name: &'static str,
+    kind: Kind,
+    default: Option<&'static str>,
+    /// jx.toml中也可以设置
+    manifest: bool,
+}
+
+const KEYS: &[Key] = &[
+    Key {
+        name: "repositories.maven_central",
+        kind: Kind::String,
+        default: Some("https://repo1.maven.org/maven2/"),
+        manifest: true,
+    },
+    Key {
+        name: "repositories.mirror",
+        kind: Kind::String,
+        default: None,
+        manifest: false,
+    },
+    Key {
+        name: "cache.dir",
+        kind: Kind::String,
+        default: Some("~/.jx/cache"),
+        manifest: false,
+    },
+    Key {
+        name: "net.offline",
+        kind: Kind::Bool,
+        default: Some("false"),
+        manifest: true,
+    },
+    Key {
+        name: "net.concurrency",
+        kind: Kind::Integer,
+        default: Some("4"),
+        manifest: false,
+    },
+    Key {
+        name: "net.proxy",
+        kind: Kind::String,
+        default: None,
+        manifest: false,
+    },
+    Key {
+        name: "java.version",
+        kind: Kind::String,
+        default: Some("17"),
+        manifest: false,
+    },
+];
+
+static CURRENT: OnceLock<Settings> = OnceLock::new();
+
+/// 按项目目录读取本次命令使用的配置
+pub fn init(project_dir: &Path) -> Result<()> {
+    let settings = Settings::load(project_dir)?;
+    let _ = CURRENT.set(settings);
+    Ok(())
+}
+
+/// 本次命令使用的配置，没有初始化或读取失败时使用默认值
+pub fn current() -> &'static Settings {
+    CURRENT.get_or_init(|| {
+        let project_dir = env::current_dir().unwrap_or_default();
+        Settings::load(&project_dir).unwrap_or_else(|_| Settings::defaults())
+    })
+}
+
+/// 用户配置文件: `$JX_CONFIG`，默认`~/.jx/config.toml`
+pub fn user_config_path() -> Option<PathBuf> {
+    match env::var_os("JX_CONFIG") {
+        Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => dirs::home_dir().map(|home| home.join(".jx").join("config.toml")),
+    }
+}
+
+/// 项目配置文件: 从项目目录向上最近的`.jx/config.toml`，都不存在时为项目目录下的新文件
+pub fn local_config_path(project_dir: &Path) -> PathBuf {
+    let user_config = user_config_path();
+    project_dir
+        .ancestors()
+        .map(|dir| dir.join(".jx").join("config.toml"))
+        .filter(|path| Some(path) != user_config.as_ref())
+        .find(|path| path.exists())
+        .unwrap_or_else(|| project_dir.join(".jx").join("config.toml"))
+}
+
+/// 配置项对应的环境变量，例如`net.offline`对应`JX_NET_OFFLINE`
+pub fn env_name(key: &str) -> String {
+    format!("JX_{}", key.replace('.', "_").to_uppercase())
+}
+
+pub fn key_names() -> Vec<&'static str> {
+    KEYS.iter().map(|key| key.name).collect()
+}
+
+/// 把命令行或环境变量中的字符串转换成配置项的类型
+pub fn parse_value(name: &str, raw: &str) -> Result<Value> {
+    let key = find_key(name)?;
+    match key.kind {
+        Kind::String => Ok(Value::String(raw.to_string())),
+        Kind::Bool => match raw.trim().to_lowercase().as_str() {
+            "true" | "1" | "yes" => Ok(Value::Boolean(true)),
+            "false" | "0" | "no" => Ok(Value::Boolean(false)),
+            _ => Err(anyhow::anyhow!("{} 需要布尔值 (true/false)，实际为 {}", name, raw)),
+        },
+        Kind::Integer => raw
+            .trim()
+            .parse::<i64>()
+            .map(Value::Integer)
+            .map_err(|_| anyhow::anyhow!("{} 需要整数，实际为 {}", name, raw)),
+    }
+}
+
+/// 写入用户配置或项目的`.jx/config.toml`，保留文件中原有的格式和注释
+pub fn set(name: &str, raw: &str, local: bool, project_dir: &Path) -> Result<PathBuf> {
+    let value = parse_value(name, raw)?;
+    let path = if local {
+        local_config_path(project_dir)
+    } else {
+        user_config_path().ok_or_else(|| anyhow::anyhow!("无法确定用户主目录"))?
+    };
+
+    let content = if path.exists() {
+        fs::read_to_string(&path).with_context(|| format!("无法读取 {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let mut document = content
+        .parse::<Document>()
+        .with_context(|| format!("无法解析 {}", path.display()))?;
+
+    let (section, field) = name.split_once('.').expect("配置项都是 section.field 形式");
+    let table = document
+        .entry(section)
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_like_mut()
+        .ok_or_else(|| anyhow::anyhow!("{} 中的 {} 不是表", path.display(), section))?;
+    let item = match value {
+        Value::String(s) => toml_edit::value(s),
+        Value::Boolean(b) => toml_edit::value(b),
+        Value::Integer(i) => toml_edit::value(i),
+        _ => unreachable!("配置项只有字符串、布尔值和整数"),
+    };
+    table.insert(field, item);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, document.to_string())
+        .with_context(|| format!("无法写入 {}", path.display()))?;
+    Ok(path)
+}
+
+/// 按配置的代理创建HTTP客户端
+pub fn client_builder() -> Result<reqwest::ClientBuilder> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy) = current().proxy() {
+        let proxy = reqwest::Proxy::all(proxy)
+            .with_context(|| format!("无效的代理地址 net.proxy = {}", proxy))?;
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder)
+}
+
+pub fn http_client() -> Result<reqwest::Client> {
+    client_builder()?.build().context("创建HTTP客户端失败")
+}
+
+impl Settings {
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let mut settings = Self::defaults();
+
+        if let Some(path) = user_config_path().filter(|path| path.exists()) {
+            settings.merge_file(&path, Origin::User(path.clone()))?;
+        }
+
+        let manifest = project_dir.join("jx.toml");
+        if manifest.exists() {
+            settings.merge_manifest(&manifest);
+        }
+
+        let local = local_config_path(project_dir);
+        if local.exists() {
+            settings.merge_file(&local, Origin::Local(local.clone()))?;
+        }
+
+        settings.merge_env()?;
+        Ok(settings)
+    }
+
+    fn defaults() -> Self {
+        let values = KEYS
+            .iter()
+            .filter_map(|key| {
+                let value = parse_value(key.name, key.default?).expect("默认值必须有效");
+                Some((key.name, (value, Origin::Default)))
+            })
+            .collect();
+        Self { values }
+    }
+
+    /// 用户配置和项目配置中的未知配置项或类型错误直接报错
+    fn merge_file(&mut self, path: &Path, origin: Origin) -> Result<()> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("无法读取 {}", path.display()))?;
+        let table: toml::Table = toml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("无法解析 {}: {}", path.display(), e))?;
+
+        for (section, item) in table {
+            let Value::Table(fields) = item else {
+                return Err(anyhow::anyhow!("{}: {} 必须是表", path.display(), section));
+            };
+            for (field, value) in fields {
+                let name = format!("{}.{}", section, field);
+                let key = find_key(&name)
+                    .and_then(|key| check_kind(key, &value).map(|_| key))
+                    .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+                self.values.insert(key.name, (value, origin.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// jx.toml由项目模型校验，这里只读取其中的机器相关配置项
+    fn merge_manifest(&mut self, path: &Path) {
+        let Some(table) = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| toml::from_str::<toml::Table>(&content).ok())
+        else {
+            return;
+        };
+
+        for key in KEYS.iter().filter(|key| key.manifest) {
+            let (section, field) = key.name.split_once('.').expect("配置项都是 section.field 形式");
+            let Some(value) = table.get(section).and_then(|s| s.get(field)) else {
+                continue;
+            };
+            if check_kind(key, value).is_ok() {
+                self.values
+                    .insert(key.name, (value.clone(), Origin::Manifest(path.to_path_buf())));
+            }
+        }
+    }
+
+    fn merge_env(&mut self) -> Result<()> {
+        for key in KEYS {
+            let name = env_name(key.name);
+            if let Ok(raw) = env::var(&name) {
+                let value = parse_value(key.name, &raw)
+                    .map_err(|e| anyhow::anyhow!("环境变量 {}: {}", name, e))?;
+                self.values.insert(key.name, (value, Origin::Env(name)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<(&Value, &Origin)>> {
+        let key = find_key(name)?;
+        Ok(self.values.get(key.name).map(|(value, origin)| (value, origin)))
+    }
+
+    /// 所有已设置的配置项，按名称排序
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Value, &Origin)> {
+        self.values
+            .iter()
+            .map(|(name, (value, origin))| (*name, value, origin))
+    }
+
+    fn string(&self, name: &str) -> Option<&str> {
+        self.values.get(name).and_then(|(value, _)| value.as_str())
+    }
+
+    pub fn offline(&self) -> bool {
+        self.values
+            .get("net.offline")
+            .and_then(|(value, _)| value.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// 下载构件使用的仓库地址，配置了镜像时使用镜像，结尾不带`/`
+    pub fn repository_url(&self) -> String {
+        self.string("repositories.mirror")
+            .or_else(|| self.string("repositories.maven_central"))
+            .unwrap_or("https://repo1.maven.org/maven2")
+            .trim_end_matches('/')
+            .to_string()
+    }
+
+    pub fn cache_dir(&self) -> PathBuf {
+        let dir = self.string("cache.dir").unwrap_or("~/.jx/cache");
+        match dir.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+            None => PathBuf::from(dir),
+        }
+    }
+
+    /// 同时下载的构件数量，至少为1
+    pub fn concurrency(&self) -> usize {
+        self.values
+            .get("net.concurrency")
+            .and_then(|(value, _)| value.as_integer())
+            .map_or(4, |n| n.max(1) as usize)
+    }
+
+    pub fn proxy(&self) -> Option<&str> {
+        self.string("net.proxy").filter(|proxy| !proxy.is_empty())
+    }
+
+    /// 创建虚拟环境时默认安装的JDK版本
+    pub fn java_version(&self) -> &str {
+        self.string("java.version").unwrap_or("17")
+    }
+}
+
+fn find_key(name: &str) -> Result<&'static Key> {
+    KEYS.iter().find(|key| key.name == name).ok_or_else(|| {
+        anyhow::anyhow!("未知的配置项 {}，可选: {}", name, key_names().join(", "))
+    })
+}
+
+fn check_kind(key: &Key, value: &Value) -> Result<()> {
+    let matches = match key.kind {
+        Kind::String => value.is_str(),
+        Kind::Bool => value.is_bool(),
+        Kind::Integer => value.is_integer(),
+    };
+    if matches {
+        return Ok(());
+    }
+
+    let expected = match key.kind {
+        Kind::String => "字符串",
+        Kind::Bool => "布尔值",
+        Kind::Integer => "整数",
+    };
+    Err(anyhow::anyhow!("{} 需要{}，实际为 {}", key.name, expected, value))
+}
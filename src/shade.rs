@@ -0,0 +1,418 @@
+use crate::classfile;
+use crate::jar::{Entry, Jar, MANIFEST_PATH};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+const SERVICES_DIR: &str = "META-INF/services/";
+const SPRING_FACTORIES: &str = "META-INF/spring.factories";
+const REFERENCE_CONF: &str = "reference.conf";
+
+/// `[shade] relocate`中的包重定位规则，较长的包名优先匹配
+pub struct Relocator {
+    rules: Vec<Rule>,
+}
+
+struct Rule {
+    /// JVM内部格式，带结尾的 /: com/google/
+    from: String,
+    to: String,
+    /// 点分格式，带结尾的 .: com.google.
+    from_dotted: String,
+    to_dotted: String,
+}
+
+impl Relocator {
+    pub fn new(mapping: &BTreeMap<String, String>) -> Self {
+        let mut rules: Vec<Rule> = mapping
+            .iter()
+            .map(|(from, to)| Rule {
+                from: format!("{}/", from.replace('.', "/")),
+                to: format!("{}/", to.replace('.', "/")),
+                from_dotted: format!("{}.", from),
+                to_dotted: format!("{}.", to),
+            })
+            .collect();
+        rules.sort_by_key(|rule| std::cmp::Reverse(rule.from.len()));
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// jar中的路径: com/google/common/Foo.class -> myapp/shaded/google/common/Foo.class
+    pub fn path(&self, name: &str) -> Option<String> {
+        self.rules.iter().find_map(|rule| {
+            name.strip_prefix(&rule.from)
+                .map(|rest| format!("{}{}", rule.to, rest))
+        })
+    }
+
+    /// 点分的全限定类名
+    pub fn class_name(&self, name: &str) -> Option<String> {
+        self.rules.iter().find_map(|rule| {
+            name.strip_prefix(&rule.from_dotted)
+                .map(|rest| format!("{}{}", rule.to_dotted, rest))
+        })
+    }
+
+    /// 改写class文件常量池中的类名、描述符、签名和字符串常量
+    pub fn class_file(&self, bytes: &[u8]) -> Result<Option<Vec<u8>>> {
+        classfile::rewrite_utf8(bytes, |value| self.rewrite(value))
+    }
+
+    /// 开头的内部类名或点分类名，以及描述符中`L`之后的内部类名
+    fn rewrite(&self, value: &[u8]) -> Option<Vec<u8>> {
+        let mut out: Option<Vec<u8>> = None;
+        let mut index = 0;
+        while index < value.len() {
+            let type_start = index == 0 || value[index - 1] == b'L';
+            let matched = type_start
+                .then(|| {
+                    self.rules.iter().find_map(|rule| {
+                        let rest = &value[index..];
+                        if rest.starts_with(rule.from.as_bytes()) {
+                            Some((rule.from.len(), rule.to.as_bytes()))
+                        } else if index == 0 && rest.starts_with(rule.from_dotted.as_bytes()) {
+                            Some((rule.from_dotted.len(), rule.to_dotted.as_bytes()))
+                        } else {
+                            None
+                        }
+                    })
+                })
+                .flatten();
+
+            match matched {
+                Some((len, replacement)) => {
+                    let out = out.get_or_insert_with(|| value[..index].to_vec());
+                    out.extend_from_slice(replacement);
+                    index += len;
+                }
+                None => {
+                    if let Some(out) = out.as_mut() {
+                        out.push(value[index]);
+                    }
+                    index += 1;
+                }
+            }
+        }
+        out
+    }
+}
+
+/// 合并项目输出和所有运行时依赖的uber jar
+///
+/// 同名的类和资源保留先加入的版本；服务描述文件、spring.factories和reference.conf合并内容；
+/// 依赖中的签名文件、MANIFEST.MF和module-info.class被丢弃
+pub struct UberJar {
+    relocator: Relocator,
+    jar: Jar,
+    /// 条目来自哪个jar，用于报告重复的类
+    owners: BTreeMap<String, String>,
+    services: BTreeMap<String, Vec<String>>,
+    spring_factories: Vec<(String, Vec<String>)>,
+    reference_conf: Vec<String>,
+    report: UberReport,
+}
+
+#[derive(Default)]
+pub struct UberReport {
+    /// (先出现的来源, 重复的来源) -> 重复的类
+    pub duplicate_classes: BTreeMap<(String, String), Vec<String>>,
+    pub duplicate_resources: usize,
+    pub dropped_signatures: usize,
+    pub relocated_classes: usize,
+}
+
+impl UberJar {
+    pub fn new(relocator: Relocator) -> Self {
+        Self {
+            relocator,
+            jar: Jar::new(),
+            owners: BTreeMap::new(),
+            services: BTreeMap::new(),
+            spring_factories: Vec::new(),
+            reference_conf: Vec::new(),
+            report: UberReport::default(),
+        }
+    }
+
+    /// 加入项目的编译输出目录
+    pub fn add_dir(&mut self, dir: &Path, label: &str) -> Result<()> {
+        for entry in walkdir::WalkDir::new(dir).sort_by_file_name() {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let name = entry
+                .path()
+                .strip_prefix(dir)?
+                .to_string_lossy()
+                .replace('\\', "/");
+            let bytes = fs::read(entry.path())
+                .with_context(|| format!("无法读取 {}", entry.path().display()))?;
+            self.add(name, bytes, label, false)?;
+        }
+        Ok(())
+    }
+
+    /// 按jar中的顺序加入一个依赖jar的所有文件
+    pub fn add_jar(&mut self, path: &Path) -> Result<()> {
+        let label = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        let file = fs::File::open(path).with_context(|| format!("无法打开 {}", path.display()))?;
+        let mut archive =
+            zip::ZipArchive::new(file).with_context(|| format!("无法读取jar文件 {}", path.display()))?;
+
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index)?;
+            if file.is_dir() {
+                continue;
+            }
+            let name = file.name().to_string();
+            let mut bytes = Vec::with_capacity(file.size() as usize);
+            file.read_to_end(&mut bytes)
+                .with_context(|| format!("无法读取 {} 中的 {}", label, name))?;
+            self.add(name, bytes, &label, true)?;
+        }
+        Ok(())
+    }
+
+    fn add(&mut self, name: String, bytes: Vec<u8>, label: &str, dependency: bool) -> Result<()> {
+        if name == MANIFEST_PATH || name == "META-INF/INDEX.LIST" {
+            return Ok(());
+        }
+        if is_signature(&name) {
+            self.report.dropped_signatures += 1;
+            return Ok(());
+        }
+        // 依赖的模块声明在合并后的jar中没有意义
+        if dependency && (name == "module-info.class" || name.ends_with("/module-info.class")) {
+            return Ok(());
+        }
+
+        if let Some(service) = name.strip_prefix(SERVICES_DIR).filter(|s| !s.contains('/')) {
+            let service = self.relocator.class_name(service).unwrap_or_else(|| service.to_string());
+            let providers = self.services.entry(service).or_default();
+            for line in String::from_utf8_lossy(&bytes).lines() {
+                let provider = line.split('#').next().unwrap_or("").trim();
+                if provider.is_empty() {
+                    continue;
+                }
+                let provider = self
+                    .relocator
+                    .class_name(provider)
+                    .unwrap_or_else(|| provider.to_string());
+                if !providers.contains(&provider) {
+                    providers.push(provider);
+                }
+            }
+            return Ok(());
+        }
+        if name == SPRING_FACTORIES {
+            self.merge_spring_factories(&String::from_utf8_lossy(&bytes));
+            return Ok(());
+        }
+        if name == REFERENCE_CONF {
+            self.reference_conf.push(String::from_utf8_lossy(&bytes).into_owned());
+            return Ok(());
+        }
+
+        let name = self.relocator.path(&name).unwrap_or(name);
+        let is_class = name.ends_with(".class");
+        if let Some(owner) = self.owners.get(&name) {
+            if is_class {
+                self.report
+                    .duplicate_classes
+                    .entry((owner.clone(), label.to_string()))
+                    .or_default()
+                    .push(name);
+            } else {
+                self.report.duplicate_resources += 1;
+            }
+            return Ok(());
+        }
+
+        let bytes = if is_class && !self.relocator.is_empty() {
+            match self
+                .relocator
+                .class_file(&bytes)
+                .with_context(|| format!("无法重定位 {} 中的 {}", label, name))?
+            {
+                Some(relocated) => {
+                    self.report.relocated_classes += 1;
+                    relocated
+                }
+                None => bytes,
+            }
+        } else {
+            bytes
+        };
+        self.owners.insert(name.clone(), label.to_string());
+        self.jar.insert(name, Entry::Bytes(bytes));
+        Ok(())
+    }
+
+    /// 按键合并逗号分隔的值，保留首次出现的顺序
+    fn merge_spring_factories(&mut self, content: &str) {
+        // 以 \ 结尾的行与下一行相连
+        let mut logical = Vec::new();
+        let mut current = String::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if current.is_empty() && (line.starts_with('#') || line.starts_with('!')) {
+                continue;
+            }
+            match line.strip_suffix('\\') {
+                Some(rest) => current.push_str(rest),
+                None => {
+                    current.push_str(line);
+                    logical.push(std::mem::take(&mut current));
+                }
+            }
+        }
+        logical.push(current);
+
+        for line in logical {
+            let Some((key, values)) = line.split_once(['=', ':']) else {
+                continue;
+            };
+            let key = key.trim().to_string();
+            let index = match self.spring_factories.iter().position(|(existing, _)| *existing == key) {
+                Some(index) => index,
+                None => {
+                    self.spring_factories.push((key, Vec::new()));
+                    self.spring_factories.len() - 1
+                }
+            };
+            for value in values.split(',').map(str::trim).filter(|value| !value.is_empty()) {
+                let value = self.relocator.class_name(value).unwrap_or_else(|| value.to_string());
+                let merged = &mut self.spring_factories[index].1;
+                if !merged.contains(&value) {
+                    merged.push(value);
+                }
+            }
+        }
+    }
+
+    /// 写入合并后的文件，返回可以写出的jar
+    pub fn finish(mut self) -> (Jar, UberReport) {
+        for (service, providers) in &self.services {
+            let mut content = providers.join("\n");
+            content.push('\n');
+            self.jar
+                .insert(format!("{}{}", SERVICES_DIR, service), Entry::Bytes(content.into_bytes()));
+        }
+        if !self.spring_factories.is_empty() {
+            let content: String = self
+                .spring_factories
+                .iter()
+                .map(|(key, values)| format!("{}={}\n", key, values.join(",")))
+                .collect();
+            self.jar
+                .insert(SPRING_FACTORIES.to_string(), Entry::Bytes(content.into_bytes()));
+        }
+        if !self.reference_conf.is_empty() {
+            let content = self
+                .reference_conf
+                .iter()
+                .map(|part| part.trim_end())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            self.jar
+                .insert(REFERENCE_CONF.to_string(), Entry::Bytes(format!("{}\n", content).into_bytes()));
+        }
+        (self.jar, self.report)
+    }
+}
+
+/// META-INF下的签名文件: *.SF、*.DSA、*.RSA、*.EC和SIG-*
+fn is_signature(name: &str) -> bool {
+    let Some(file) = name.strip_prefix("META-INF/").filter(|file| !file.contains('/')) else {
+        return false;
+    };
+    let upper = file.to_ascii_uppercase();
+    upper.starts_with("SIG-")
+        || [".SF", ".DSA", ".RSA", ".EC"]
+            .iter()
+            .any(|extension| upper.ends_with(extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relocator(rules: &[(&str, &str)]) -> Relocator {
+        let mapping = rules
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect();
+        Relocator::new(&mapping)
+    }
+
+    fn rewrite(relocator: &Relocator, value: &str) -> Option<String> {
+        relocator
+            .rewrite(value.as_bytes())
+            .map(|bytes| String::from_utf8(bytes).unwrap())
+    }
+
+    #[test]
+    fn rewrites_internal_names_and_descriptors() {
+        let relocator = relocator(&[("com.google", "myapp.shaded.google")]);
+
+        assert_eq!(
+            rewrite(&relocator, "com/google/common/Foo").as_deref(),
+            Some("myapp/shaded/google/common/Foo")
+        );
+        assert_eq!(
+            rewrite(&relocator, "(Lcom/google/Foo;[[Lcom/google/Bar;I)Lcom/google/Baz;").as_deref(),
+            Some("(Lmyapp/shaded/google/Foo;[[Lmyapp/shaded/google/Bar;I)Lmyapp/shaded/google/Baz;")
+        );
+        assert_eq!(
+            rewrite(&relocator, "[Lcom/google/Foo;").as_deref(),
+            Some("[Lmyapp/shaded/google/Foo;")
+        );
+    }
+
+    #[test]
+    fn rewrites_generic_signatures() {
+        let relocator = relocator(&[("com.google", "shaded")]);
+        assert_eq!(
+            rewrite(&relocator, "Ljava/util/Map<Lcom/google/K;Ljava/util/List<Lcom/google/V;>;>;").as_deref(),
+            Some("Ljava/util/Map<Lshaded/K;Ljava/util/List<Lshaded/V;>;>;")
+        );
+    }
+
+    #[test]
+    fn rewrites_dotted_names_only_at_the_start() {
+        let relocator = relocator(&[("com.google", "shaded.google")]);
+        assert_eq!(
+            rewrite(&relocator, "com.google.inject.Module").as_deref(),
+            Some("shaded.google.inject.Module")
+        );
+        assert_eq!(rewrite(&relocator, "see com.google.inject.Module"), None);
+    }
+
+    #[test]
+    fn leaves_unrelated_values_alone() {
+        let relocator = relocator(&[("com.google", "shaded")]);
+        assert_eq!(rewrite(&relocator, "com/googlex/Foo"), None);
+        assert_eq!(rewrite(&relocator, "xcom/google/Foo"), None);
+        assert_eq!(rewrite(&relocator, "com.google"), None);
+        assert_eq!(rewrite(&relocator, ""), None);
+    }
+
+    #[test]
+    fn longest_package_wins() {
+        let relocator = relocator(&[("com.google", "a"), ("com.google.common", "b")]);
+        assert_eq!(rewrite(&relocator, "com/google/common/Foo").as_deref(), Some("b/Foo"));
+        assert_eq!(rewrite(&relocator, "com/google/inject/Foo").as_deref(), Some("a/inject/Foo"));
+        assert_eq!(relocator.path("com/google/common/Foo.class").as_deref(), Some("b/Foo.class"));
+        assert_eq!(relocator.class_name("com.google.inject.Foo").as_deref(), Some("a.inject.Foo"));
+    }
+}
@@ -0,0 +1,352 @@
+use crate::config::{JxConfig, WorkspaceManifest};
+use crate::dependency::{Dependency, DependencyScope};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// jx工作区: 根目录jx.toml中的`[workspace] members`列出的多个模块
+///
+/// 所有成员共用根目录的jx.lock，成员之间通过路径依赖互相引用
+pub struct Workspace {
+    pub root: PathBuf,
+    pub members: Vec<Member>,
+}
+
+pub struct Member {
+    /// 相对于工作区根目录的路径
+    pub path: String,
+    pub dir: PathBuf,
+    pub config: JxConfig,
+}
+
+impl Member {
+    pub fn name(&self) -> &str {
+        &self.config.project.name
+    }
+}
+
+impl Workspace {
+    /// 从目录向上查找包含`[workspace]`的jx.toml，目录必须是工作区根目录或某个成员
+    pub fn discover(dir: &Path) -> Result<Option<Self>> {
+        for ancestor in dir.ancestors() {
+            let config_path = ancestor.join("jx.toml");
+            // 成员自己的jx.toml有错误时交给具体命令报告
+            if !config_path.exists()
+                || !matches!(WorkspaceManifest::load(&config_path), Ok(Some(_)))
+            {
+                continue;
+            }
+
+            let workspace = Self::load(ancestor)?;
+            if ancestor == dir || workspace.member_at(dir).is_some() {
+                return Ok(Some(workspace));
+            }
+            return Ok(None);
+        }
+        Ok(None)
+    }
+
+    pub fn load(root: &Path) -> Result<Self> {
+        let config = WorkspaceManifest::load(&root.join("jx.toml"))?
+            .ok_or_else(|| anyhow::anyhow!("{} 中没有 [workspace]", root.join("jx.toml").display()))?;
+
+        let mut members = Vec::new();
+        for pattern in &config.members {
+            for dir in expand_member(root, pattern)? {
+                let path = dir
+                    .strip_prefix(root)
+                    .unwrap_or(&dir)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                let config = JxConfig::load_from_dir(&dir)
+                    .map_err(|e| anyhow::anyhow!("无法读取工作区成员 {}: {}", path, e))?;
+                members.push(Member { path, dir, config });
+            }
+        }
+
+        let mut names: HashMap<&str, &str> = HashMap::new();
+        for member in &members {
+            if let Some(other) = names.insert(member.name(), &member.path) {
+                return Err(anyhow::anyhow!(
+                    "工作区成员 {} 和 {} 的项目名称都是 {}",
+                    other,
+                    member.path,
+                    member.name()
+                ));
+            }
+        }
+
+        let workspace = Self {
+            root: root.to_path_buf(),
+            members,
+        };
+        workspace.check_path_dependencies()?;
+        Ok(workspace)
+    }
+
+    pub fn lock_path(&self) -> PathBuf {
+        self.root.join("jx.lock")
+    }
+
+    /// 按项目名称或路径查找成员
+    pub fn member(&self, name: &str) -> Result<&Member> {
+        let name = name.trim_end_matches('/');
+        self.members
+            .iter()
+            .find(|member| member.name() == name || member.path == name)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "工作区中没有成员 {}，可选: {}",
+                    name,
+                    self.member_names().join(", ")
+                )
+            })
+    }
+
+    /// 目录所在的成员
+    pub fn member_at(&self, dir: &Path) -> Option<&Member> {
+        self.members.iter().find(|member| dir.starts_with(&member.dir))
+    }
+
+    pub fn member_names(&self) -> Vec<&str> {
+        self.members.iter().map(|member| member.name()).collect()
+    }
+
+    /// 命令作用的成员: `-p`指定的成员，否则为当前目录所在的成员，在根目录时为全部成员。
+    /// 结果按路径依赖排序，被依赖的成员在前
+    pub fn select(&self, packages: &[String], current_dir: &Path) -> Result<Vec<&Member>> {
+        let selected: Vec<&Member> = if !packages.is_empty() {
+            packages
+                .iter()
+                .map(|name| self.member(name))
+                .collect::<Result<_>>()?
+        } else if let Some(member) = self.member_at(current_dir) {
+            vec![member]
+        } else {
+            self.members.iter().collect()
+        };
+
+        Ok(self
+            .build_order()?
+            .into_iter()
+            .filter(|member| selected.iter().any(|s| s.path == member.path))
+            .collect())
+    }
+
+    /// 加上成员直接或间接依赖的其他成员，按构建顺序排列
+    pub fn with_dependencies<'a>(&'a self, selected: &[&'a Member]) -> Result<Vec<&'a Member>> {
+        let mut needed: Vec<&str> = selected.iter().map(|member| member.path.as_str()).collect();
+        let mut index = 0;
+        while index < needed.len() {
+            let current = self.member(needed[index])?;
+            for dependency in self.path_targets(current)? {
+                if !needed.contains(&dependency.path.as_str()) {
+                    needed.push(&dependency.path);
+                }
+            }
+            index += 1;
+        }
+
+        Ok(self
+            .build_order()?
+            .into_iter()
+            .filter(|member| needed.contains(&member.path.as_str()))
+            .collect())
+    }
+
+    /// 所有成员的外部依赖，用于生成共享的jx.lock
+    ///
+    /// 同一构件在不同成员中的版本必须一致；scope不同时取最宽的scope
+    pub fn dependencies(&self) -> Result<Vec<Dependency>> {
+        let mut merged: Vec<(Dependency, &str)> = Vec::new();
+
+        for member in &self.members {
+            for dependency in member.config.lock_dependencies() {
+                let existing = merged.iter_mut().find(|(dep, _)| {
+                    dep.group_id == dependency.group_id && dep.artifact_id == dependency.artifact_id
+                });
+                match existing {
+                    None => merged.push((dependency, member.name())),
+                    Some((dep, owner)) if dep.version != dependency.version => {
+                        return Err(anyhow::anyhow!(
+                            "工作区成员对 {}:{} 声明了不同的版本: {} 使用 {}，{} 使用 {}，共享的jx.lock要求版本一致",
+                            dependency.group_id,
+                            dependency.artifact_id,
+                            owner,
+                            dep.version,
+                            member.name(),
+                            dependency.version
+                        ));
+                    }
+                    Some((dep, _)) => {
+                        if scope_rank(&dependency.scope) < scope_rank(&dep.scope) {
+                            dep.scope = dependency.scope.clone();
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(merged.into_iter().map(|(dep, _)| dep).collect())
+    }
+
+    /// 拓扑排序，路径依赖成环时报错
+    fn build_order(&self) -> Result<Vec<&Member>> {
+        let mut order: Vec<&Member> = Vec::new();
+        let mut visiting: Vec<&str> = Vec::new();
+
+        fn visit<'a>(
+            workspace: &'a Workspace,
+            member: &'a Member,
+            order: &mut Vec<&'a Member>,
+            visiting: &mut Vec<&'a str>,
+        ) -> Result<()> {
+            if order.iter().any(|m| m.path == member.path) {
+                return Ok(());
+            }
+            if visiting.contains(&member.name()) {
+                visiting.push(member.name());
+                return Err(anyhow::anyhow!(
+                    "工作区成员之间的路径依赖成环: {}",
+                    visiting.join(" -> ")
+                ));
+            }
+
+            visiting.push(member.name());
+            for dependency in workspace.path_targets(member)? {
+                visit(workspace, dependency, order, visiting)?;
+            }
+            visiting.pop();
+            order.push(member);
+            Ok(())
+        }
+
+        for member in &self.members {
+            visit(self, member, &mut order, &mut visiting)?;
+        }
+        Ok(order)
+    }
+
+    /// 成员的路径依赖指向的成员
+    fn path_targets(&self, member: &Member) -> Result<Vec<&Member>> {
+        member
+            .config
+            .path_dependencies()
+            .iter()
+            .map(|dependency| {
+                let dir = normalize(&member.dir.join(&dependency.path));
+                self.members
+                    .iter()
+                    .find(|m| normalize(&m.dir) == dir)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "{} 的路径依赖 {}:{} 指向 {}，它不是工作区成员",
+                            member.name(),
+                            dependency.group_id,
+                            dependency.artifact_id,
+                            dependency.path
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// 路径依赖的坐标必须与目标成员的 group_id:name 一致
+    fn check_path_dependencies(&self) -> Result<()> {
+        for member in &self.members {
+            let targets = self.path_targets(member)?;
+            for (dependency, target) in member.config.path_dependencies().iter().zip(targets) {
+                let group_id = target.config.project.group_id.as_deref();
+                if group_id != Some(dependency.group_id.as_str()) || target.name() != dependency.artifact_id {
+                    return Err(anyhow::anyhow!(
+                        "{} 的路径依赖 {}:{} 与成员 {} 的坐标 {}:{} 不一致",
+                        member.name(),
+                        dependency.group_id,
+                        dependency.artifact_id,
+                        target.path,
+                        group_id.unwrap_or("<未设置group_id>"),
+                        target.name()
+                    ));
+                }
+            }
+        }
+        self.build_order().map(|_| ())
+    }
+}
+
+/// 项目目录使用的锁文件: 工作区成员共用根目录的jx.lock
+pub fn lock_path(project_dir: &Path) -> Result<PathBuf> {
+    Ok(match Workspace::discover(project_dir)? {
+        Some(workspace) => workspace.lock_path(),
+        None => project_dir.join("jx.lock"),
+    })
+}
+
+/// 锁文件路径和用于生成它的依赖清单: 工作区中为所有成员依赖的并集
+pub fn lock_manifest(project_dir: &Path) -> Result<(PathBuf, Vec<Dependency>)> {
+    match Workspace::discover(project_dir)? {
+        Some(workspace) => Ok((workspace.lock_path(), workspace.dependencies()?)),
+        None => Ok((
+            project_dir.join("jx.lock"),
+            JxConfig::load_from_dir(project_dir)?.lock_dependencies(),
+        )),
+    }
+}
+
+/// `-p`选择的成员目录，没有指定时为当前目录
+pub fn project_dir(package: Option<&str>) -> Result<PathBuf> {
+    let current_dir = std::env::current_dir()?;
+    let Some(package) = package else {
+        return Ok(current_dir);
+    };
+
+    let workspace = Workspace::discover(&current_dir)?
+        .ok_or_else(|| anyhow::anyhow!("-p 只能在jx工作区中使用"))?;
+    Ok(workspace.member(package)?.dir.clone())
+}
+
+fn expand_member(root: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    if !pattern.contains(['*', '?', '[']) {
+        let dir = root.join(pattern);
+        if !dir.join("jx.toml").exists() {
+            return Err(anyhow::anyhow!("工作区成员 {} 中找不到jx.toml", pattern));
+        }
+        return Ok(vec![dir]);
+    }
+
+    let mut dirs: Vec<PathBuf> = glob::glob(&root.join(pattern).to_string_lossy())
+        .map_err(|e| anyhow::anyhow!("无效的成员通配符 {}: {}", pattern, e))?
+        .filter_map(|entry| entry.ok())
+        .filter(|dir| dir.join("jx.toml").exists())
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// 去掉路径中的`.`和`..`，不要求路径存在
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// 同一构件以多个scope出现时，数值小的优先
+pub fn scope_rank(scope: &DependencyScope) -> u8 {
+    match scope {
+        DependencyScope::Compile => 0,
+        DependencyScope::Runtime => 1,
+        DependencyScope::Provided => 2,
+        DependencyScope::System => 3,
+        DependencyScope::Test => 4,
+        // 同时在classpath上时按classpath的scope锁定，注解处理器路径按jx.toml中的声明查找
+        DependencyScope::AnnotationProcessor => 5,
+    }
+}